@@ -1,10 +1,16 @@
 use redis_module::{
-    redis_module, Context, RedisResult, RedisString, RedisValue, ThreadSafeContext,
+    redis_module, Context, NextArg, RedisResult, RedisString, RedisValue, ThreadSafeContext,
 };
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::thread;
 use std::time::Duration;
 
 fn block(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    // Inside MULTI or a script, reply right away instead.
+    if !ctx.can_block() {
+        return Ok("42".into());
+    }
+
     let blocked_client = ctx.block_client();
 
     thread::spawn(move || {
@@ -17,6 +23,50 @@ fn block(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::NoReply)
 }
 
+fn block_client_id(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let blocked_client = ctx.block_client();
+    // The id allows matching the blocked client with the client of another
+    // command, for example one that should abort it.
+    let client_id = blocked_client.client_id();
+
+    thread::spawn(move || {
+        let thread_ctx = ThreadSafeContext::with_blocked_client(blocked_client);
+        thread_ctx.reply(Ok((client_id as i64).into()));
+    });
+
+    Ok(RedisValue::NoReply)
+}
+
+/// The number of blocked clients whose resources were freed by `block.timeout`.
+static FREED: AtomicU64 = AtomicU64::new(0);
+
+fn block_timeout(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let timeout = Duration::from_millis(args.next_u64()?);
+    let work = Duration::from_millis(args.next_u64()?);
+    args.done()?;
+
+    let blocked_client = ctx.block_client_with_timeout(
+        timeout,
+        |_ctx| Ok("timeout".into()),
+        |_ctx| {
+            FREED.fetch_add(1, Ordering::Relaxed);
+        },
+    );
+
+    thread::spawn(move || {
+        let thread_ctx = ThreadSafeContext::with_blocked_client(blocked_client);
+        thread::sleep(work);
+        thread_ctx.reply(Ok("done".into()));
+    });
+
+    Ok(RedisValue::NoReply)
+}
+
+fn block_freed(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok((FREED.load(Ordering::Relaxed) as i64).into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -26,5 +76,8 @@ redis_module! {
     data_types: [],
     commands: [
         ["block", block, "", 0, 0, 0, ""],
+        ["block.client_id", block_client_id, "", 0, 0, 0, ""],
+        ["block.timeout", block_timeout, "", 0, 0, 0, ""],
+        ["block.freed", block_freed, "readonly", 0, 0, 0, ""],
     ],
 }
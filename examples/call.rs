@@ -1,9 +1,10 @@
 use redis_module::{
-    redis_module, BlockedClient, CallOptionResp, CallOptionsBuilder, CallReply, CallResult,
-    Context, FutureCallReply, PromiseCallReply, RedisError, RedisResult, RedisString, RedisValue,
-    ThreadSafeContext,
+    redis_module, BlockedClient, CallArgs, CallOptionResp, CallOptionsBuilder, CallReply,
+    CallResult, Context, FutureCallReply, NextArg, PromiseCallReply, RedisError, RedisResult,
+    RedisString, RedisValue, ReplicationTarget, Status, ThreadSafeContext,
 };
 
+use std::collections::HashMap;
 use std::thread;
 
 fn call_test(ctx: &Context, _: Vec<RedisString>) -> RedisResult {
@@ -156,6 +157,185 @@ fn call_blocking_from_detach_ctx(ctx: &Context, _: Vec<RedisString>) -> RedisRes
     Ok(RedisValue::NoReply)
 }
 
+fn call_typed(ctx: &Context, _: Vec<RedisString>) -> RedisResult {
+    let res: i64 = ctx.call_typed("INCRBY", &["typed_counter", "5"])?;
+    if res != 5 {
+        return Err(RedisError::Str("Failed calling 'INCRBY' typed"));
+    }
+
+    let res: Option<String> = ctx.call_typed("GET", &["typed_missing"])?;
+    if res.is_some() {
+        return Err(RedisError::Str(
+            "Failed calling 'GET' typed on a missing key",
+        ));
+    }
+
+    ctx.call("RPUSH", &["typed_list", "a", "b", "c"])?;
+    let res: Vec<String> = ctx.call_typed("LRANGE", &["typed_list", "0", "-1"])?;
+    if res != ["a", "b", "c"] {
+        return Err(RedisError::Str("Failed calling 'LRANGE' typed"));
+    }
+
+    ctx.call("HSET", &["typed_hash", "f1", "1", "f2", "2"])?;
+    let res: HashMap<String, String> = ctx.call_typed("HGETALL", &["typed_hash"])?;
+    if res.len() != 2 || res["f1"] != "1" || res["f2"] != "2" {
+        return Err(RedisError::Str("Failed calling 'HGETALL' typed"));
+    }
+
+    ctx.call("ZADD", &["typed_zset", "1.5", "m"])?;
+    let res: f64 = ctx.call_typed("ZSCORE", &["typed_zset", "m"])?;
+    if res != 1.5 {
+        return Err(RedisError::Str("Failed calling 'ZSCORE' typed"));
+    }
+
+    let res: bool = ctx.call_typed("EXISTS", &["typed_list"])?;
+    if !res {
+        return Err(RedisError::Str("Failed calling 'EXISTS' typed"));
+    }
+
+    // A reply that does not match the requested type is an error.
+    let res = ctx.call_typed::<i64, _>("LRANGE", &["typed_list", "0", "-1"]);
+    match res {
+        Err(RedisError::String(e)) if e == "Expected integer reply, got array reply" => {}
+        _ => return Err(RedisError::Str("Failed calling 'LRANGE' with a wrong type")),
+    }
+
+    Ok("pass".into())
+}
+
+fn call_on_key(ctx: &Context, _: Vec<RedisString>) -> RedisResult {
+    let key_name = ctx.create_string("on_key_hash");
+    let key = ctx.open_key_writable(&key_name);
+    if key.get_name() != key_name {
+        return Err(RedisError::Str("Failed getting the name of an opened key"));
+    }
+    key.call("HSET", &["f", "1"])?;
+    let res = key.call("HINCRBY", &["f", "2"])?;
+    if res != RedisValue::Integer(3) {
+        return Err(RedisError::Str("Failed calling 'HINCRBY' on an opened key"));
+    }
+
+    let key = ctx.open_key(&key_name);
+    let res: String = key.call("HGET", &["f"])?.try_into()?;
+    if res != "3" {
+        return Err(RedisError::Str("Failed calling 'HGET' on an opened key"));
+    }
+
+    let key = ctx.open_key(&ctx.create_string("on_key_missing"));
+    if key.call("HGET", &["f"]).is_ok() {
+        return Err(RedisError::Str("Failed rejecting a call on a missing key"));
+    }
+
+    Ok("pass".into())
+}
+
+fn call_mixed_args(ctx: &Context, _: Vec<RedisString>) -> RedisResult {
+    let key_name = ctx.create_string("mixed_args_hash");
+    let value = [b'v', 0, 0xff];
+    let args = CallArgs::new()
+        .push_redis_string(&key_name)
+        .push_str("f")
+        .push_bytes(&value);
+    ctx.call("HSET", args)?;
+
+    let options = CallOptionsBuilder::new().build();
+    let args = CallArgs::new().push_redis_string(&key_name).push_str("f");
+    match ctx.call_ext::<_, CallResult>("HGET", &options, args) {
+        Ok(CallReply::String(res)) if res.as_bytes() == value => {}
+        _ => {
+            return Err(RedisError::Str(
+                "Failed calling 'HGET' with mixed arguments",
+            ))
+        }
+    }
+
+    // The key name was not created by the arguments, so it is still valid.
+    let res: i64 = ctx.call_typed("HLEN", &[&key_name])?;
+    if res != 1 {
+        return Err(RedisError::Str(
+            "Failed calling 'HLEN' with a borrowed key name",
+        ));
+    }
+
+    Ok("pass".into())
+}
+
+fn call_incr_by_float(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let incr = args.next_f64()?;
+    args.done()?;
+
+    // Like INCRBYFLOAT, replicate the result instead of the increment, so
+    // replicas do not have to compute it again.
+    let current = ctx
+        .call("GET", &[&key_name])?
+        .try_into()
+        .map_or(0.0, |v: String| v.parse().unwrap_or(0.0));
+    let value = current + incr;
+    ctx.call("SET", &[key_name.as_slice(), value.to_string().as_bytes()])?;
+    ctx.replicate_typed(
+        "SET",
+        &[
+            RedisValue::BulkRedisString(key_name),
+            RedisValue::Float(value),
+        ],
+    )?;
+
+    // Aggregates can not be replicated as arguments.
+    if ctx
+        .replicate_typed("SET", &[RedisValue::Array(vec![])])
+        .is_ok()
+    {
+        return Err(RedisError::Str("Replicated an array argument"));
+    }
+
+    // Redis does not parse NaN as a number.
+    if ctx
+        .replicate_typed("SET", &[RedisValue::Float(f64::NAN)])
+        .is_ok()
+    {
+        return Err(RedisError::Str("Replicated a NaN argument"));
+    }
+
+    Ok(value.to_string().into())
+}
+
+fn call_set_echo(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 3 {
+        return Err(RedisError::WrongArity);
+    }
+
+    // Replicate the arguments of the command as a plain SET.
+    ctx.call("SET", &args[1..])?;
+    ctx.replicate_args("SET", &args[1..]);
+
+    // There is nowhere to replicate to without a target.
+    if ctx.replicate_with_flags("SET", &args[1..], ReplicationTarget::empty()) == Status::Ok {
+        return Err(RedisError::Str("Replicated to an empty target"));
+    }
+
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn call_forward_error(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let command = args.next_string()?;
+    let args: Vec<RedisString> = args.collect();
+    let args: Vec<&RedisString> = args.iter().collect();
+    let call_options = CallOptionsBuilder::new()
+        .resp(CallOptionResp::Auto)
+        .errors_as_replies()
+        .build();
+    match ctx.call_ext::<_, CallResult>(&command, &call_options, args.as_slice()) {
+        Ok(_) => Ok(RedisValue::SimpleStringStatic("OK")),
+        Err(err) => {
+            ctx.reply_with_error_from_call_reply(&err);
+            Ok(RedisValue::NoReply)
+        }
+    }
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -166,6 +346,12 @@ redis_module! {
     commands: [
         ["call.test", call_test, "", 0, 0, 0, ""],
         ["call.blocking", call_blocking, "", 0, 0, 0, ""],
+        ["call.forward_error", call_forward_error, "", 0, 0, 0, ""],
+        ["call.typed", call_typed, "", 0, 0, 0, ""],
+        ["call.on_key", call_on_key, "", 0, 0, 0, ""],
+        ["call.mixed_args", call_mixed_args, "", 0, 0, 0, ""],
+        ["call.incr_by_float", call_incr_by_float, "write", 1, 1, 1, ""],
+        ["call.set_echo", call_set_echo, "write", 1, 1, 1, ""],
         ["call.blocking_from_detached_ctx", call_blocking_from_detach_ctx, "", 0, 0, 0, ""],
     ],
 }
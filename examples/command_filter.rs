@@ -0,0 +1,131 @@
+use std::sync::Mutex;
+
+use redis_module::commandfilter::{CommandFilter, CommandFilterContext, CommandFilterFlags};
+use redis_module::{redis_module, Context, NextArg, RedisResult, RedisString, RedisValue, Status};
+
+/// The filter registered by `filter.echo_on`, until `filter.echo_off`.
+static ECHO_FILTER: Mutex<Option<CommandFilter>> = Mutex::new(None);
+
+fn is_command(fctx: &CommandFilterContext, name: &[u8]) -> bool {
+    matches!(fctx.arg_get(0), Some(cmd) if cmd.eq_ignore_ascii_case(name))
+}
+
+/// Rewrites `GET` into `filter.myget`, which calls `GET` itself. The filter is
+/// registered with `NOSELF`, so the inner `GET` is not rewritten again.
+fn rewrite_get(fctx: &mut CommandFilterContext) {
+    if is_command(fctx, b"get") {
+        let _ = fctx.arg_replace(0, b"filter.myget");
+    }
+}
+
+/// Exercises argument insertion, replacement and deletion on `filter.args`.
+fn rewrite_args(fctx: &mut CommandFilterContext) {
+    if !is_command(fctx, b"filter.args") {
+        return;
+    }
+    let _ = fctx.arg_insert(1, b"inserted");
+    let mut pos = 1;
+    while let Some(arg) = fctx.arg_get(pos) {
+        match arg.to_vec().as_slice() {
+            b"old" => {
+                let _ = fctx.arg_replace(pos, b"new");
+            }
+            b"drop" => {
+                let _ = fctx.arg_delete(pos);
+                continue;
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+}
+
+/// Appends the id of the client sending `filter.client_id`, as seen by the filter.
+fn append_client_id(fctx: &mut CommandFilterContext) {
+    if is_command(fctx, b"filter.client_id") {
+        let id = fctx
+            .client_id()
+            .map_or_else(|| "unknown".to_owned(), |id| id.to_string());
+        let count = fctx.args_count();
+        let _ = fctx.arg_insert(count, id.as_bytes());
+    }
+}
+
+fn myget(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+
+    match ctx.call("GET", &[&key])? {
+        RedisValue::Null => Ok(RedisValue::Null),
+        value => {
+            let value: String = value.try_into()?;
+            Ok(format!("filtered:{value}").into())
+        }
+    }
+}
+
+fn filter_args(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    Ok(args.into_iter().skip(1).collect::<Vec<_>>().into())
+}
+
+fn echo_on(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let mut echo_filter = ECHO_FILTER.lock().unwrap();
+    if echo_filter.is_none() {
+        let filter = ctx.register_command_filter(CommandFilterFlags::empty(), |fctx| {
+            if is_command(fctx, b"filter.echo") {
+                let _ = fctx.arg_replace(1, b"intercepted");
+            }
+        })?;
+        *echo_filter = Some(filter);
+    }
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn echo_off(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let filter = ECHO_FILTER.lock().unwrap().take();
+    if let Some(filter) = filter {
+        filter.unregister(ctx)?;
+    }
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn echo(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let arg = args.next_arg()?;
+    args.done()?;
+    Ok(arg.into())
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    let filter = ctx.register_command_filter(CommandFilterFlags::NOSELF, |fctx| {
+        rewrite_get(fctx);
+        rewrite_args(fctx);
+        append_client_id(fctx);
+    });
+    match filter {
+        Ok(_) => Status::Ok,
+        Err(err) => {
+            ctx.log_warning(&format!("{err}"));
+            Status::Err
+        }
+    }
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "command_filter",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    init: init,
+    commands: [
+        ["filter.myget", myget, "readonly", 1, 1, 1, ""],
+        ["filter.args", filter_args, "", 0, 0, 0, ""],
+        ["filter.client_id", filter_args, "", 0, 0, 0, ""],
+        ["filter.echo", echo, "", 0, 0, 0, ""],
+        ["filter.echo_on", echo_on, "", 0, 0, 0, ""],
+        ["filter.echo_off", echo_off, "", 0, 0, 0, ""],
+    ],
+}
@@ -1,4 +1,7 @@
-use redis_module::{redis_module, Context, ContextFlags, RedisResult, RedisString, RedisValue};
+use redis_module::{
+    redis_module, ClientInfoFlags, Context, ContextFlags, NextArg, RedisError, RedisResult,
+    RedisString, RedisValue,
+};
 
 fn role(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::SimpleStringStatic(
@@ -10,6 +13,70 @@ fn role(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     ))
 }
 
+fn client_addr(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = args.next_u64()?;
+    args.done()?;
+
+    match ctx.get_client_addr(id) {
+        Some((ip, port)) => Ok(RedisValue::Array(vec![
+            RedisValue::BulkString(ip.to_string()),
+            RedisValue::Integer(port as i64),
+        ])),
+        None => Err(RedisError::Str("No address for client")),
+    }
+}
+
+fn client_name(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let id = args.next_u64()?;
+    args.done()?;
+
+    Ok(ctx.get_client_name_by_id(id).into())
+}
+
+fn client_info(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let info = match args.next() {
+        Some(id) => ctx.get_client_info_by_id(id.parse_unsigned_integer()?)?,
+        None => ctx.get_client_info()?,
+    };
+    args.done()?;
+
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(info.id as i64),
+        RedisValue::BulkString(info.addr),
+        RedisValue::Integer(info.port as i64),
+        RedisValue::Integer(info.db as i64),
+        RedisValue::Bool(info.flags.contains(ClientInfoFlags::MULTI)),
+    ]))
+}
+
+fn client_list(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(
+        ctx.client_list()?
+            .into_iter()
+            .map(|info| {
+                RedisValue::Array(vec![
+                    RedisValue::Integer(info.id as i64),
+                    RedisValue::BulkString(info.addr),
+                    RedisValue::Integer(info.port as i64),
+                    RedisValue::Integer(info.db as i64),
+                ])
+            })
+            .collect(),
+    ))
+}
+
+fn check_oom(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let bytes = args.next_u64()?;
+    args.done()?;
+
+    ctx.check_oom_for(bytes as usize)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -19,5 +86,10 @@ redis_module! {
     data_types: [],
     commands: [
         ["my_role", role, "readonly", 0, 0, 0, ""],
+        ["client_addr", client_addr, "readonly", 0, 0, 0, ""],
+        ["client_name", client_name, "readonly", 0, 0, 0, ""],
+        ["client_info", client_info, "readonly", 0, 0, 0, ""],
+        ["client_list", client_list, "readonly", 0, 0, 0, ""],
+        ["check_oom", check_oom, "readonly", 0, 0, 0, ""],
     ],
 }
@@ -41,7 +41,7 @@ static MY_REDIS_TYPE: RedisType = RedisType::new(
         aux_save2: None,
         aux_save_triggers: 0,
 
-        free_effort: None,
+        free_effort: Some(free_effort),
         unlink: None,
         copy: None,
         defrag: Some(defrag),
@@ -57,14 +57,28 @@ unsafe extern "C" fn free(value: *mut c_void) {
     drop(Box::from_raw(value.cast::<MyType>()));
 }
 
+/// Freeing a value costs about one allocation per kilobyte of data, so big
+/// values are handed to a background thread on UNLINK.
+unsafe extern "C" fn free_effort(_key: *mut raw::RedisModuleString, value: *const c_void) -> usize {
+    let value = &*value.cast::<MyType>();
+    1 + value.data.len() / 1024
+}
+
 unsafe extern "C" fn defrag(
     ctx: *mut raw::RedisModuleDefragCtx,
     _key: *mut raw::RedisModuleString,
-    _value: *mut *mut c_void,
+    value: *mut *mut c_void,
 ) -> c_int {
     let defrag_ctx = DefragContext::new(ctx);
     let mut num_keys_defrag = NUM_KEYS_DEFRAG.lock(&defrag_ctx);
     *num_keys_defrag += 1;
+
+    let mut my_value = Box::from_raw((*value).cast::<MyType>());
+    defrag_ctx.defrag_box(&mut my_value);
+    let mut data = std::mem::take(&mut my_value.data).into_bytes();
+    defrag_ctx.defrag_vec(&mut data);
+    my_value.data = String::from_utf8_unchecked(data);
+    *value = Box::into_raw(my_value).cast();
     0
 }
 
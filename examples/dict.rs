@@ -0,0 +1,162 @@
+use std::ops::Deref;
+
+use lazy_static::lazy_static;
+use redis_module::{
+    redis_module, Context, DictDirection, NextArg, RedisDict, RedisError, RedisGILGuard,
+    RedisResult, RedisString, RedisStringInterner, RedisValue, SharedRedisString,
+};
+
+/// A value of the index, either owned by the key or shared with the other
+/// keys with the same value.
+enum IndexValue {
+    Owned(RedisString),
+    Shared(SharedRedisString),
+}
+
+impl Deref for IndexValue {
+    type Target = RedisString;
+
+    fn deref(&self) -> &RedisString {
+        match self {
+            IndexValue::Owned(value) => value,
+            IndexValue::Shared(value) => value,
+        }
+    }
+}
+
+lazy_static! {
+    static ref INDEX: RedisGILGuard<RedisDict<IndexValue>> = RedisGILGuard::default();
+    static ref VALUES: RedisGILGuard<RedisStringInterner> = RedisGILGuard::default();
+}
+
+fn dict_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    let mut index = INDEX.lock(ctx);
+    let old = index.set(&key, IndexValue::Owned(value.safe_clone(ctx)));
+    Ok(old.map_or(RedisValue::Null, |old| {
+        RedisValue::BulkRedisString(old.safe_clone(ctx))
+    }))
+}
+
+fn dict_set_interned(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    // Keys with the same value share a single copy of it.
+    let mut values = VALUES.lock(ctx);
+    let shared = values.contains(&value);
+    let mut index = INDEX.lock(ctx);
+    index.set(&key, IndexValue::Shared(values.intern(&value)));
+    Ok(RedisValue::Bool(shared))
+}
+
+fn dict_interned(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    args.done()?;
+
+    Ok(RedisValue::Integer(VALUES.lock(ctx).len() as i64))
+}
+
+fn dict_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+
+    let index = INDEX.lock(ctx);
+    Ok(index.get(&key).map_or(RedisValue::Null, |v| {
+        RedisValue::BulkRedisString(v.safe_clone(ctx))
+    }))
+}
+
+fn dict_del(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+
+    let mut index = INDEX.lock(ctx);
+    Ok(RedisValue::Bool(index.del(&key).is_some()))
+}
+
+fn dict_list(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 1 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let index = INDEX.lock(ctx);
+    let mut res = Vec::with_capacity(index.len() * 2);
+    for (key, value) in index.iter() {
+        res.push(RedisValue::BulkRedisString(key));
+        res.push(RedisValue::BulkRedisString(value.safe_clone(ctx)));
+    }
+    redis_module::redis_assert!(
+        res.len() == index.len() * 2,
+        "dict size changed during iteration"
+    );
+    Ok(RedisValue::Array(res))
+}
+
+fn dict_range(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let start = args.next_arg()?;
+    let direction = match args.next_str()?.to_ascii_uppercase().as_str() {
+        "ASC" => DictDirection::Forward,
+        "DESC" => DictDirection::Backward,
+        _ => return Err(RedisError::Str("Direction must be ASC or DESC")),
+    };
+    args.done()?;
+
+    let index = INDEX.lock(ctx);
+    Ok(RedisValue::array_from(
+        index
+            .iter_from(&start, direction)
+            .map(|(key, _)| RedisValue::BulkRedisString(key)),
+    ))
+}
+
+fn dict_prefix(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let prefix = args.next_arg()?;
+    args.done()?;
+
+    let index = INDEX.lock(ctx);
+    Ok(RedisValue::array_from(
+        index
+            .iter_prefix(&prefix)
+            .map(|(key, _)| RedisValue::BulkRedisString(key)),
+    ))
+}
+
+fn dict_memory(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    args.done()?;
+
+    let index = INDEX.lock(ctx);
+    let usage = index.memory_usage_with(|value| value.malloc_size());
+    Ok(RedisValue::Integer(usage as i64))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "dict",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["dict.set", dict_set, "", 0, 0, 0, ""],
+        ["dict.iset", dict_set_interned, "", 0, 0, 0, ""],
+        ["dict.interned", dict_interned, "readonly", 0, 0, 0, ""],
+        ["dict.get", dict_get, "readonly", 0, 0, 0, ""],
+        ["dict.del", dict_del, "", 0, 0, 0, ""],
+        ["dict.list", dict_list, "readonly", 0, 0, 0, ""],
+        ["dict.range", dict_range, "readonly", 0, 0, 0, ""],
+        ["dict.prefix", dict_prefix, "readonly", 0, 0, 0, ""],
+        ["dict.memory", dict_memory, "readonly", 0, 0, 0, ""],
+    ],
+}
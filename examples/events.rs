@@ -1,11 +1,15 @@
+use redis_module::metrics::Counter;
 use redis_module::{
     redis_module, Context, NotifyEvent, RedisError, RedisResult, RedisString, RedisValue, Status,
+    WriteBehindBuffer,
 };
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicI64, Ordering};
 
-static NUM_KEY_MISSES: AtomicI64 = AtomicI64::new(0);
-static NUM_KEYS: AtomicI64 = AtomicI64::new(0);
+static NUM_KEY_MISSES: Counter = Counter::new("num_key_misses");
+static NUM_KEYS: Counter = Counter::new("num_keys");
+static NUM_INDEX_FLUSHES: Counter = Counter::new("num_index_flushes");
+
+static INDEX_UPDATES: WriteBehindBuffer<Vec<u8>> = WriteBehindBuffer::new(update_index);
 
 fn on_event(ctx: &Context, event_type: NotifyEvent, event: &str, key: &[u8]) {
     if key == b"num_sets" {
@@ -28,6 +32,25 @@ fn on_event(ctx: &Context, event_type: NotifyEvent, event: &str, key: &[u8]) {
     });
 }
 
+fn update_index(ctx: &Context, keys: Vec<Vec<u8>>) {
+    NUM_INDEX_FLUSHES.inc();
+    let mut args: Vec<&[u8]> = vec![b"wb_index"];
+    args.extend(keys.iter().map(Vec::as_slice));
+    if let Err(e) = ctx.call("sadd", args.as_slice()) {
+        ctx.log_warning(&format!("Error on sadd command, {}.", e));
+    }
+}
+
+fn on_indexed_key(ctx: &Context, _event_type: NotifyEvent, _event: &str, key: &[u8]) {
+    if key.starts_with(b"wb:") {
+        let _ = INDEX_UPDATES.push(ctx, key.to_vec());
+    }
+}
+
+fn num_index_flushes(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Integer(NUM_INDEX_FLUSHES.get() as i64))
+}
+
 fn on_stream(ctx: &Context, _event_type: NotifyEvent, _event: &str, _key: &[u8]) {
     ctx.log_debug("Stream event received!");
 }
@@ -46,20 +69,44 @@ fn event_send(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
 }
 
 fn on_key_miss(_ctx: &Context, _event_type: NotifyEvent, _event: &str, _key: &[u8]) {
-    NUM_KEY_MISSES.fetch_add(1, Ordering::SeqCst);
+    NUM_KEY_MISSES.inc();
 }
 
 fn num_key_miss(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
-    Ok(RedisValue::Integer(NUM_KEY_MISSES.load(Ordering::SeqCst)))
+    Ok(RedisValue::Integer(NUM_KEY_MISSES.get() as i64))
 }
 
 fn on_new_key(_ctx: &Context, _event_type: NotifyEvent, _event: &str, _key: &[u8]) {
-    NUM_KEYS.fetch_add(1, Ordering::SeqCst);
+    NUM_KEYS.inc();
 }
 
 fn num_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
-    Ok(RedisValue::Integer(NUM_KEYS.load(Ordering::SeqCst)))
+    Ok(RedisValue::Integer(NUM_KEYS.get() as i64))
+}
+
+fn keyspace_events(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let (events, raw_events) = ctx.keyspace_events_detailed();
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(events.bits() as i64),
+        RedisValue::Integer(raw_events as i64),
+    ]))
+}
+
+fn keyspace_stats(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let stats = ctx.get_keyspace_stats();
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(stats.hits as i64),
+        RedisValue::Integer(stats.misses as i64),
+    ]))
+}
+
+fn init(_ctx: &Context, _args: &[RedisString]) -> Status {
+    NUM_KEY_MISSES.register();
+    NUM_KEYS.register();
+    NUM_INDEX_FLUSHES.register();
+    Status::Ok
 }
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -67,13 +114,18 @@ redis_module! {
     version: 1,
     allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
     data_types: [],
+    init: init,
     commands: [
         ["events.send", event_send, "", 0, 0, 0, ""],
         ["events.num_key_miss", num_key_miss, "", 0, 0, 0, ""],
         ["events.num_keys", num_keys, "", 0, 0, 0, ""],
+        ["events.keyspace_stats", keyspace_stats, "", 0, 0, 0, ""],
+        ["events.keyspace_events", keyspace_events, "", 0, 0, 0, ""],
+        ["events.num_index_flushes", num_index_flushes, "", 0, 0, 0, ""],
     ],
     event_handlers: [
         [@STRING: on_event],
+        [@STRING: on_indexed_key],
         [@STREAM: on_stream],
         [@MISSED: on_key_miss],
         [@NEW: on_new_key],
@@ -17,6 +17,54 @@ fn expire_cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     }
 }
 
+fn expire_micros(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let ttl_micros = args.next_u64()?;
+    args.done()?;
+    let key = ctx.open_key_writable(&key_name);
+    key.set_expire_checked(Duration::from_micros(ttl_micros))
+}
+
+fn expire_for(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let ttl = args.next_arg()?.parse_duration()?;
+    args.done()?;
+    let key = ctx.open_key_writable(&key_name);
+    key.set_expire(ttl)
+}
+
+fn expire_copy(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let src = args.next_arg()?;
+    let dst = args.next_arg()?;
+    args.done()?;
+    let snapshot = ctx
+        .open_key(&src)
+        .dump_with_metadata()?
+        .ok_or(RedisError::Str("ERR no such key"))?;
+    ctx.restore_key_snapshot(&dst, &snapshot, false)?;
+    Ok(snapshot.expire_at.unwrap_or(-1).into())
+}
+
+fn expire_copy_ttl(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let src = args.next_arg()?;
+    let dst = args.next_arg()?;
+    args.done()?;
+    let src = ctx.open_key_writable(&src);
+    let dst = ctx.open_key_writable(&dst);
+    match src.get_absolute_expire() {
+        Some(expire_at) => dst.set_absolute_expire(expire_at)?,
+        None => dst.remove_expire()?,
+    };
+    Ok(dst
+        .get_expire()
+        .map_or(-1, |ttl| ttl.as_millis() as i64)
+        .into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -26,5 +74,9 @@ redis_module! {
     data_types: [],
     commands: [
         ["expire.cmd", expire_cmd, "write fast deny-oom", 1, 1, 1, ""],
+        ["expire.micros", expire_micros, "write fast deny-oom", 1, 1, 1, ""],
+        ["expire.for", expire_for, "write fast deny-oom", 1, 1, 1, ""],
+        ["expire.copy", expire_copy, "write deny-oom", 1, 2, 1, ""],
+        ["expire.copy_ttl", expire_copy_ttl, "write fast", 1, 2, 1, ""],
     ],
 }
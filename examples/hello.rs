@@ -1,4 +1,7 @@
-use redis_module::{redis_module, Context, RedisError, RedisResult, RedisString};
+use redis_module::{
+    export_api, import_api, redis_module, Context, NextArg, RedisError, RedisResult, RedisString,
+    Status,
+};
 
 fn hello_mul(_: &Context, args: Vec<RedisString>) -> RedisResult {
     if args.len() < 2 {
@@ -19,6 +22,65 @@ fn hello_mul(_: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(response.into())
 }
 
+fn hello_sum(_: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 2 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let nums = args
+        .into_iter()
+        .skip(1)
+        .map(|s| s.parse_integer())
+        .collect::<Result<Vec<i64>, RedisError>>()?;
+
+    let sum = nums.iter().sum();
+
+    let mut response = nums;
+    response.push(sum);
+
+    Ok(response.into())
+}
+
+fn hello_swap(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let op = args.next_str()?;
+    args.done()?;
+
+    match op {
+        "sum" => ctx.replace_command_handler("hello.mul", hello_sum)?,
+        "mul" => ctx.restore_command_handler("hello.mul")?,
+        _ => return Err(RedisError::Str("Unknown operation")),
+    }
+
+    Ok("OK".into())
+}
+
+pub extern "C" fn hello_add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+fn hello_shared_add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let a = args.next_i64()?;
+    let b = args.next_i64()?;
+    args.done()?;
+
+    // Safety: `hello.add` is exported by this module with this type.
+    let add = unsafe { import_api!(ctx, "hello.add", extern "C" fn(i64, i64) -> i64) }?;
+    Ok(add(a, b).into())
+}
+
+fn init(ctx: &Context, _args: &[RedisString]) -> Status {
+    // Safety: `hello_add` has the exported type, and lives as long as the module.
+    match unsafe { export_api!(ctx, "hello.add", hello_add: extern "C" fn(i64, i64) -> i64) } {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            ctx.log_warning(&e.to_string());
+            Status::Err
+        }
+    }
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -26,7 +88,10 @@ redis_module! {
     version: 1,
     allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
     data_types: [],
+    init: init,
     commands: [
         ["hello.mul", hello_mul, "", 0, 0, 0, ""],
+        ["hello.swap", hello_swap, "", 0, 0, 0, ""],
+        ["hello.shared_add", hello_shared_add, "", 0, 0, 0, ""],
     ],
 }
@@ -1,5 +1,5 @@
-use redis_module::InfoContext;
 use redis_module::{redis_module, RedisResult};
+use redis_module::{InfoContext, InfoContextBuilderFieldBottomLevelValue};
 use redis_module_macros::info_command_handler;
 
 #[info_command_handler]
@@ -7,8 +7,16 @@ fn add_info(ctx: &InfoContext, _for_crash_report: bool) -> RedisResult<()> {
     ctx.builder()
         .add_section("info")
         .field("field", "value")?
+        .field(
+            "latency",
+            InfoContextBuilderFieldBottomLevelValue::Histogram(vec![(50.0, 1.0), (99.9, 2.5)]),
+        )?
         .add_dictionary("dictionary")
         .field("key", "value")?
+        .add_dictionary("sub")
+        .field("a", 1i64)?
+        .field("b", "two")?
+        .build_sub_dictionary()?
         .build_dictionary()?
         .build_section()?
         .build_info()?;
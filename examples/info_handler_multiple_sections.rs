@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
 use redis_module::InfoContext;
 use redis_module::{redis_module, RedisResult};
 use redis_module_macros::{info_command_handler, InfoSection};
@@ -12,6 +14,8 @@ struct InfoSection2 {
     field_2: String,
 }
 
+static EXPENSIVE_SECTION_BUILDS: AtomicI64 = AtomicI64::new(0);
+
 #[info_command_handler]
 fn add_info(ctx: &InfoContext, _for_crash_report: bool) -> RedisResult<()> {
     let data = InfoSection1 {
@@ -23,7 +27,13 @@ fn add_info(ctx: &InfoContext, _for_crash_report: bool) -> RedisResult<()> {
         field_2: "value2".to_owned(),
     };
 
-    ctx.build_one_section(data)
+    ctx.build_one_section(data)?;
+
+    // Only computed when the section is requested.
+    ctx.build_section_if_requested("expensive", |section| {
+        let computed = EXPENSIVE_SECTION_BUILDS.fetch_add(1, Ordering::Relaxed) + 1;
+        section.field("computed", computed)
+    })
 }
 
 //////////////////////////////////////////////////////
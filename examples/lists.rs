@@ -1,4 +1,6 @@
 use redis_module::raw::KeyType;
+use std::os::raw::c_long;
+
 use redis_module::{
     redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
 };
@@ -35,6 +37,67 @@ fn lpoprpush(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     }
 }
 
+// LINDEX.NATIVE key index
+// Returns the element at 'index' of the list stored at 'key', without
+// removing it.
+fn lindex_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let index = args.next_i64()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    if !matches!(key.key_type(), KeyType::Empty | KeyType::List) {
+        return Err(RedisError::WrongType);
+    }
+    Ok(key
+        .list_get(index as c_long)
+        .map_or(RedisValue::Null, RedisValue::BulkRedisString))
+}
+
+// LRANGE.NATIVE key
+// Returns all the elements of the list stored at 'key', from the head to the tail.
+fn lrange_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    if !matches!(key.key_type(), KeyType::Empty | KeyType::List) {
+        return Err(RedisError::WrongType);
+    }
+    Ok(RedisValue::array_from(key.list_iter()))
+}
+
+// LSET.NATIVE key index element
+// Replaces the element at 'index' of the list stored at 'key'.
+fn lset_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let index = args.next_i64()?;
+    let element = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    key.list_set(index as c_long, element)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+// LINSERT.NATIVE key index element
+// Inserts 'element' so it ends up at 'index' of the list stored at 'key',
+// and returns the length of the list.
+fn linsert_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let index = args.next_i64()?;
+    let element = args.next_arg()?;
+    args.done()?;
+
+    let mut key = ctx.open_key_writable(&key_name);
+    key.list_insert(index as c_long, element)?;
+    Ok(RedisValue::Integer(key.list_iter().len() as i64))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -44,5 +107,9 @@ redis_module! {
     data_types: [],
     commands: [
         ["LPOPRPUSH", lpoprpush, "write fast deny-oom", 1, 2, 1, ""],
+        ["LINDEX.NATIVE", lindex_native, "readonly", 1, 1, 1, ""],
+        ["LRANGE.NATIVE", lrange_native, "readonly", 1, 1, 1, ""],
+        ["LSET.NATIVE", lset_native, "write deny-oom", 1, 1, 1, ""],
+        ["LINSERT.NATIVE", linsert_native, "write deny-oom", 1, 1, 1, ""],
     ],
 }
@@ -2,7 +2,7 @@ use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use redis_module::RedisError;
 use redis_module::{redis_module, Context, RedisResult, RedisString, RedisValue};
-use redis_module_macros::{command, RedisValue};
+use redis_module_macros::{command, RedisHash, RedisValue};
 
 #[derive(RedisValue)]
 struct RedisValueDeriveInner {
@@ -25,6 +25,59 @@ struct RedisValueDerive {
     ordered_set: BTreeSet<String>,
 }
 
+#[derive(RedisValue)]
+struct RedisValueDeriveMiddle {
+    m: i64,
+    #[RedisValueAttr{flatten: true}]
+    inner: RedisValueDeriveInner,
+}
+
+#[derive(RedisValue)]
+struct RedisValueDeriveNested {
+    o: i64,
+    #[RedisValueAttr{flatten: true}]
+    middle: RedisValueDeriveMiddle,
+}
+
+#[derive(RedisValue)]
+#[RedisValueAttr{insertion_ordered: true}]
+struct RedisValueDeriveOrdered {
+    z: i64,
+    #[RedisValueAttr{flatten: true}]
+    middle: RedisValueDeriveMiddle,
+    a: i64,
+}
+
+#[derive(RedisValue)]
+struct RedisValueDeriveRenamed {
+    #[RedisValueAttr{rename: "maxLen"}]
+    max_len: i64,
+    #[RedisValueAttr{rename: "last-seen"}]
+    last_seen: i64,
+    plain: i64,
+}
+
+#[derive(RedisValue)]
+struct RedisValueDeriveOptional {
+    value: Option<i64>,
+}
+
+/// Not convertible to [RedisValue], so it can only be a skipped field.
+struct Bookkeeping {
+    _calls: u64,
+}
+
+#[derive(RedisValue)]
+struct RedisValueDeriveSkipped {
+    kept: i64,
+    #[RedisValueAttr{skip: true}]
+    _bookkeeping: Bookkeeping,
+    #[RedisValueAttr{skip_if_none: true}]
+    present: Option<i64>,
+    #[RedisValueAttr{skip_if_none: true}]
+    absent: Option<i64>,
+}
+
 #[derive(RedisValue)]
 enum RedisValueEnum {
     Str(String),
@@ -71,6 +124,134 @@ fn redis_value_derive(
     }
 }
 
+#[command(
+    {
+        flags: [ReadOnly, NoMandatoryKeys],
+        arity: 1,
+        key_spec: [
+            {
+                notes: "test redis value derive macro with nested flatten fields",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 0 }),
+                find_keys: Range({ last_key : 0, steps : 0, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn redis_value_derive_nested(
+    _ctx: &Context,
+    _args: Vec<RedisString>,
+) -> Result<RedisValueDeriveNested, RedisError> {
+    Ok(RedisValueDeriveNested {
+        o: 1,
+        middle: RedisValueDeriveMiddle {
+            m: 2,
+            inner: RedisValueDeriveInner { i1: 3 },
+        },
+    })
+}
+
+#[command(
+    {
+        flags: [ReadOnly, NoMandatoryKeys],
+        arity: 1,
+        key_spec: [
+            {
+                notes: "test redis value derive macro with insertion ordered fields",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 0 }),
+                find_keys: Range({ last_key : 0, steps : 0, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn redis_value_derive_ordered(
+    _ctx: &Context,
+    _args: Vec<RedisString>,
+) -> Result<RedisValueDeriveOrdered, RedisError> {
+    Ok(RedisValueDeriveOrdered {
+        z: 1,
+        middle: RedisValueDeriveMiddle {
+            m: 2,
+            inner: RedisValueDeriveInner { i1: 3 },
+        },
+        a: 4,
+    })
+}
+
+#[command(
+    {
+        flags: [ReadOnly, NoMandatoryKeys],
+        arity: 1,
+        key_spec: [
+            {
+                notes: "test redis value derive macro with renamed fields",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 0 }),
+                find_keys: Range({ last_key : 0, steps : 0, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn redis_value_derive_renamed(
+    _ctx: &Context,
+    _args: Vec<RedisString>,
+) -> Result<RedisValueDeriveRenamed, RedisError> {
+    Ok(RedisValueDeriveRenamed {
+        max_len: 1,
+        last_seen: 2,
+        plain: 3,
+    })
+}
+
+#[command(
+    {
+        flags: [ReadOnly, NoMandatoryKeys],
+        arity: -1,
+        key_spec: [
+            {
+                notes: "test redis value derive macro with an optional field",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 0 }),
+                find_keys: Range({ last_key : 0, steps : 0, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn redis_value_derive_optional(
+    _ctx: &Context,
+    args: Vec<RedisString>,
+) -> Result<RedisValueDeriveOptional, RedisError> {
+    let value = args.get(1).map(|v| v.parse_integer()).transpose()?;
+    Ok(RedisValueDeriveOptional { value })
+}
+
+#[command(
+    {
+        flags: [ReadOnly, NoMandatoryKeys],
+        arity: 1,
+        key_spec: [
+            {
+                notes: "test redis value derive macro with skipped fields",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 0 }),
+                find_keys: Range({ last_key : 0, steps : 0, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn redis_value_derive_skipped(
+    _ctx: &Context,
+    _args: Vec<RedisString>,
+) -> Result<RedisValueDeriveSkipped, RedisError> {
+    Ok(RedisValueDeriveSkipped {
+        kept: 1,
+        _bookkeeping: Bookkeeping { _calls: 7 },
+        present: Some(2),
+        absent: None,
+    })
+}
+
 #[command(
     {
         flags: [ReadOnly],
@@ -127,6 +308,144 @@ fn num_keys(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::SimpleStringStatic("OK"))
 }
 
+#[command(
+    {
+        name: "not_key_channel",
+        flags: [ReadOnly],
+        arity: 3,
+        key_spec: [
+            {
+                notes: "test command that routes by a channel which is not a key, like SPUBLISH",
+                flags: [NotKey],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn not_key_channel(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+#[command(
+    {
+        name: "current_keys",
+        flags: [ReadOnly],
+        arity: -2,
+        key_spec: [
+            {
+                notes: "test command that returns its own keys, all the arguments at even position",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key :- 1, steps : 2, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn current_keys(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let keys = ctx.current_command_keys(&args)?;
+    Ok(keys.into())
+}
+
+#[command(
+    {
+        name: "metered",
+        flags: [ReadOnly],
+        arity: 2,
+        key_spec: [],
+        metrics: true,
+    }
+)]
+fn metered(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    match args[1].try_as_str()? {
+        "fail" => Err(RedisError::Str("ERR failed on request")),
+        _ => Ok(RedisValue::SimpleStringStatic("OK")),
+    }
+}
+
+#[command(
+    {
+        name: "loaded_only",
+        flags: [ReadOnly, AllowLoading],
+        arity: 1,
+        key_spec: [],
+        deny_during_loading: true,
+    }
+)]
+fn loaded_only(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+#[command(
+    {
+        name: "arity_checked",
+        flags: [ReadOnly],
+        arity: -2,
+        key_spec: [],
+        enforce_arity: true,
+    }
+)]
+fn arity_checked(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    // The arity is checked before the handler is called.
+    Ok(args[1].clone().into())
+}
+
+#[command(
+    {
+        name: "replicated_incr",
+        flags: [Write, DenyOOM],
+        arity: 2,
+        key_spec: [
+            {
+                notes: "the incremented key",
+                flags: [ReadWrite, Access, Update],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ],
+        replicate_effects: true,
+    }
+)]
+fn replicated_incr(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    // Replicated as the `INCR` it runs, not as `replicated_incr`.
+    ctx.call("INCR", &[&args[1]])
+}
+
+#[derive(RedisHash)]
+struct RedisHashDerive {
+    name: String,
+    limit: i64,
+    owner: Option<String>,
+}
+
+#[command(
+    {
+        name: "redis_hash_copy",
+        flags: [Write, DenyOOM],
+        arity: 3,
+        key_spec: [
+            {
+                notes: "the source hash",
+                flags: [ReadOnly, Access],
+                begin_search: Index({ index : 1 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            },
+            {
+                notes: "the destination hash",
+                flags: [ReadWrite, Update],
+                begin_search: Index({ index : 2 }),
+                find_keys: Range({ last_key : 0, steps : 1, limit : 0 }),
+            }
+        ]
+    }
+)]
+fn redis_hash_copy(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut value = RedisHashDerive::load(&ctx.open_key(&args[1]))?;
+    value.limit += 1;
+    value.store(&ctx.open_key_writable(&args[2]))?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
 redis_module! {
     name: "server_events",
     version: 1,
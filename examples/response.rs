@@ -1,8 +1,11 @@
 use redis_module::{
-    redis_module, redisvalue::RedisValueKey, Context, NextArg, RedisError, RedisResult,
-    RedisString, RedisValue,
+    raw, redis_module,
+    redisvalue::{DoubleKey, DoubleKeyFormat, RedisValueKey},
+    CallOptionResp, CallOptionsBuilder, CallReply, CallResult, Context, NextArg, RedisError,
+    RedisResult, RedisString, RedisValue, Status, VerbatimStringFormat,
 };
 use std::collections::{BTreeMap, BTreeSet};
+use std::os::raw::c_char;
 
 fn map_mget(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     if args.len() < 2 {
@@ -59,6 +62,350 @@ fn map_unique(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(res)
 }
 
+fn map_hmget(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+
+    let fields: Vec<RedisString> = args.collect();
+
+    let key = ctx.open_key(&key_name);
+    let values = key.hash_get_multi(&fields)?;
+    let res = match values {
+        None => RedisValue::Array(fields.iter().map(|_| RedisValue::Null).collect()),
+        Some(values) => RedisValue::Array(
+            values
+                .into_vec_with_misses()
+                .into_iter()
+                .map(|(_, value)| value.map_or(RedisValue::Null, RedisValue::BulkRedisString))
+                .collect(),
+        ),
+    };
+
+    Ok(res)
+}
+
+fn map_hmget_bytes(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+
+    let fields: Vec<RedisString> = args.collect();
+
+    let key = ctx.open_key(&key_name);
+    let values = match key.hash_get_multi_bytes(&fields)? {
+        Some(values) => values,
+        None => fields.iter().map(|_| None).collect(),
+    };
+
+    Ok(RedisValue::Array(
+        values
+            .into_iter()
+            .map(|value| value.map_or(RedisValue::Null, RedisValue::StringBuffer))
+            .collect(),
+    ))
+}
+
+fn map_hget_bytes(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let field = args.next_string()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    Ok(key
+        .hash_get_bytes(&field)?
+        .map_or(RedisValue::Null, RedisValue::StringBuffer))
+}
+
+fn map_hset(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 4 || args.len() % 2 != 0 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+
+    let args: Vec<RedisString> = args.collect();
+    let pairs = args
+        .chunks(2)
+        .map(|pair| Ok((pair[0].try_as_str()?, pair[1].clone())))
+        .collect::<Result<Vec<_>, RedisError>>()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    if key.hash_set_multi(&pairs) == Status::Err {
+        return Err(RedisError::WrongType);
+    }
+
+    Ok(RedisValue::Integer(pairs.len() as i64))
+}
+
+fn map_hdel(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+
+    let fields: Vec<RedisString> = args.collect();
+    let fields = fields
+        .iter()
+        .map(RedisString::try_as_str)
+        .collect::<Result<Vec<&str>, _>>()?;
+
+    let key = ctx.open_key_writable(&key_name);
+    let deleted = key.hash_del_multi(&fields)?;
+
+    Ok(RedisValue::Integer(deleted as i64))
+}
+
+fn stream(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let count = args.next_u64()? as i64;
+    args.done()?;
+
+    // Reply with `[[0..count], [evens < count], {i: i * i for i < 3}]`, the
+    // lengths of the last two are only known once all their elements were added.
+    let reply = ctx.reply_builder();
+    reply.begin_array(3);
+
+    reply.begin_array(count as usize);
+    (0..count).for_each(|i| {
+        reply.push_long(i);
+    });
+
+    reply.begin_array_postponed();
+    let mut len = 0;
+    for i in (0..count).filter(|i| i % 2 == 0) {
+        reply.push_long(i);
+        len += 1;
+    }
+    reply.set_array_length(len);
+
+    reply.begin_map_postponed();
+    for i in 0..3 {
+        reply.push_string(&i.to_string());
+        reply.push_long(i * i);
+    }
+    reply.set_map_length(3);
+
+    Ok(RedisValue::NoReply)
+}
+
+fn nested(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let depth = args.next_u64()?;
+    let max_depth = args.next_u64()?;
+    args.done()?;
+
+    let mut value = RedisValue::Integer(1);
+    for _ in 0..depth {
+        value = RedisValue::Array(vec![value]);
+    }
+
+    ctx.reply_with_max_depth(Ok(value), max_depth as usize);
+    Ok(RedisValue::NoReply)
+}
+
+fn mixed(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let args = args.into_iter().skip(1);
+    // Reply with an EXEC like array, numeric arguments are echoed back and
+    // any other argument becomes an error element.
+    Ok(RedisValue::array_from(args.map(|arg| {
+        arg.parse_integer().map_or_else(
+            |_| RedisValue::Error(format!("ERR value is not an integer: {arg}")),
+            RedisValue::Integer,
+        )
+    })))
+}
+
+fn bool_reply(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let val = args.next_i64()? != 0;
+    args.done()?;
+    Ok(RedisValue::Bool(val))
+}
+
+fn error_with_code(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let code = args.next_string()?;
+    let message = args.next_string()?;
+    args.done()?;
+    Err(RedisError::WithCode { code, message })
+}
+
+fn bool_reply_types(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    // Report the reply type a client gets from `response.bool` on each protocol.
+    let types = [CallOptionResp::Resp2, CallOptionResp::Resp3].map(|resp| {
+        let options = CallOptionsBuilder::new().resp(resp).build();
+        match ctx.call_ext::<_, CallResult>("response.bool", &options, &["1"]) {
+            Ok(CallReply::Bool(_)) => "bool",
+            Ok(CallReply::I64(_)) => "integer",
+            _ => "other",
+        }
+    });
+    Ok(types.to_vec().into())
+}
+
+fn types(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    // Reply with the RESP3 only types, both from a value and element by element,
+    // RESP2 clients get them downgraded.
+    let value = RedisValue::Array(vec![
+        RedisValue::OrderedMap(BTreeMap::from([
+            ("flag".into(), RedisValue::Bool(true)),
+            (
+                "set".into(),
+                RedisValue::OrderedSet(BTreeSet::from([1.into(), 2.into()])),
+            ),
+        ])),
+        RedisValue::InsertionOrderedMap(vec![(
+            DoubleKey::new(1.5).into(),
+            RedisValue::Bool(false),
+        )]),
+    ]);
+
+    let reply = ctx.reply_builder();
+    reply.begin_array(2);
+    reply.push_value(value);
+    reply.begin_map_postponed();
+    reply.push_string("set");
+    reply.begin_set_postponed();
+    reply.push_bool(true);
+    reply.set_set_length(1);
+    reply.set_map_length(1);
+    Ok(RedisValue::NoReply)
+}
+
+fn attribute(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    // Attributes are only sent to RESP3 clients, RESP2 clients only get the value.
+    if raw::reply_with_attribute(ctx.ctx, 1) == Status::Ok {
+        ctx.reply(Ok("ttl".into()));
+        ctx.reply(Ok(10_i64.into()));
+    }
+    ctx.reply(Ok("value".into()));
+    Ok(RedisValue::NoReply)
+}
+
+fn attribute_read(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    // Read the attributes and the value `response.attribute` replies with.
+    let options = CallOptionsBuilder::new()
+        .resp(CallOptionResp::Resp3)
+        .build();
+    match ctx.call_ext::<_, CallResult>("response.attribute", &options, &[] as &[&str]) {
+        Ok(CallReply::Attribute(reply)) => {
+            let attributes = reply
+                .iter()
+                .flat_map(|(key, val)| [(&key).into(), (&val).into()])
+                .collect();
+            Ok(RedisValue::Array(vec![
+                RedisValue::Array(attributes),
+                reply.value().into(),
+                reply.to_string().into(),
+            ]))
+        }
+        _ => Err(RedisError::Str("Expected an attribute reply")),
+    }
+}
+
+fn config_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let pattern = args.next_str()?;
+    args.done()?;
+
+    // RESP3 calls get the configuration as a map keyed by name.
+    let options = CallOptionsBuilder::new()
+        .resp(CallOptionResp::Resp3)
+        .build();
+    match ctx.call_ext::<_, CallResult>("config", &options, &["get", pattern])? {
+        CallReply::Map(reply) => Ok(RedisValue::OrderedMap(
+            reply
+                .iter_str()
+                .map(|(name, val)| (name.into(), (&val).into()))
+                .collect(),
+        )),
+        _ => Err(RedisError::Str("Expected a map reply")),
+    }
+}
+
+fn help(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let markdown = args.next_str()? == "markdown";
+    args.done()?;
+
+    if markdown {
+        ctx.reply_markdown("# Help\n\n* `response.help`: show this help");
+    } else {
+        ctx.reply_text("response.help: show this help");
+    }
+    Ok(RedisValue::NoReply)
+}
+
+fn help_format(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    // Report the verbatim format a RESP3 client gets from `response.help`.
+    let mut args = args.into_iter().skip(1);
+    let kind = args.next_str()?;
+    args.done()?;
+
+    let options = CallOptionsBuilder::new()
+        .resp(CallOptionResp::Resp3)
+        .build();
+    match ctx.call_ext::<_, CallResult>("response.help", &options, &[kind]) {
+        Ok(CallReply::VerbatimString(reply)) => {
+            let (format, data) = reply
+                .as_parts()
+                .ok_or(RedisError::Str("Failed reading the verbatim string"))?;
+            Ok(RedisValue::Array(vec![
+                format.into(),
+                RedisValue::StringBuffer(data.to_vec()),
+            ]))
+        }
+        _ => Err(RedisError::Str("Expected a verbatim string reply")),
+    }
+}
+
+fn verbatim(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let format = args.next_arg()?;
+    let data = args.next_arg()?;
+    args.done()?;
+
+    // Build the format from the raw bytes so invalid formats reach the reply code.
+    let format: [u8; 3] = format
+        .as_slice()
+        .try_into()
+        .map_err(|_| RedisError::Str("Format must be 3 bytes long"))?;
+    Ok(RedisValue::VerbatimString((
+        VerbatimStringFormat(format.map(|c| c as c_char)),
+        data.as_slice().to_vec(),
+    )))
+}
+
+fn leaderboard(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 || args.len() % 2 == 0 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let map = args[1..]
+        .chunks(2)
+        .map(|pair| {
+            let score = pair[0].parse_float()?;
+            Ok((
+                RedisValueKey::from(DoubleKey::with_format(score, DoubleKeyFormat::Fixed(2))),
+                RedisValue::BulkRedisString(pair[1].clone()),
+            ))
+        })
+        .collect::<Result<BTreeMap<_, _>, RedisError>>()?;
+
+    Ok(RedisValue::OrderedMap(map))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -69,5 +416,24 @@ redis_module! {
     commands: [
         ["map.mget", map_mget, "readonly", 1, 1, 1, ""],
         ["map.unique", map_unique, "readonly", 1, 1, 1, ""],
+        ["map.hmget", map_hmget, "readonly", 1, 1, 1, ""],
+        ["map.hset", map_hset, "write deny-oom", 1, 1, 1, ""],
+        ["map.hdel", map_hdel, "write", 1, 1, 1, ""],
+        ["map.hmget_bytes", map_hmget_bytes, "readonly", 1, 1, 1, ""],
+        ["map.hget_bytes", map_hget_bytes, "readonly", 1, 1, 1, ""],
+        ["response.nested", nested, "readonly", 0, 0, 0, ""],
+        ["response.stream", stream, "readonly", 0, 0, 0, ""],
+        ["response.verbatim", verbatim, "readonly", 0, 0, 0, ""],
+        ["response.mixed", mixed, "readonly", 0, 0, 0, ""],
+        ["response.bool", bool_reply, "readonly", 0, 0, 0, ""],
+        ["response.bool_types", bool_reply_types, "readonly", 0, 0, 0, ""],
+        ["response.error", error_with_code, "readonly", 0, 0, 0, ""],
+        ["response.leaderboard", leaderboard, "readonly", 0, 0, 0, ""],
+        ["response.types", types, "readonly", 0, 0, 0, ""],
+        ["response.attribute", attribute, "readonly", 0, 0, 0, ""],
+        ["response.attribute_read", attribute_read, "readonly", 0, 0, 0, ""],
+        ["response.config_get", config_get, "readonly", 0, 0, 0, ""],
+        ["response.help", help, "readonly", 0, 0, 0, ""],
+        ["response.help_format", help_format, "readonly", 0, 0, 0, ""],
     ],
 }
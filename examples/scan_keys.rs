@@ -1,5 +1,6 @@
 use redis_module::{
-    key::RedisKey, redis_module, Context, KeysCursor, RedisResult, RedisString, RedisValue,
+    key::RedisKey, redis_module, Context, Cursor, CursorReply, KeyType, KeysCursor, NextArg,
+    RedisResult, RedisString, RedisValue,
 };
 
 fn scan_keys(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
@@ -16,6 +17,44 @@ fn scan_keys(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(RedisValue::Array(res))
 }
 
+fn scan_keys_paged(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let cursor = Cursor::try_from(&args.next_arg()?)?;
+    args.done()?;
+
+    let keys = KeysCursor::from_token(cursor.position());
+    let mut res = Vec::new();
+
+    let scan_callback = |_ctx: &Context, key_name: RedisString, _key: Option<&RedisKey>| {
+        res.push(key_name);
+    };
+
+    keys.try_scan(ctx, &scan_callback)?;
+
+    // The cursor was created from a token, so it always has one.
+    let next = keys.token().unwrap_or_default();
+    Ok(CursorReply::new(Cursor::new(next)).items(res).into())
+}
+
+fn scan_keys_iter(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let res: Vec<RedisValue> = ctx
+        .scan_iter()
+        .filter(|(_, key)| key.key_type() == KeyType::String)
+        .map(|(key_name, _)| key_name.into())
+        .collect();
+    Ok(RedisValue::Array(res))
+}
+
+fn scan_key_names(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    // Report the names of the keys themselves, the names the scan yields are not used.
+    let res: Vec<RedisValue> = ctx
+        .scan_iter()
+        .filter_map(|(_, key)| key.get_name())
+        .map(Into::into)
+        .collect();
+    Ok(RedisValue::Array(res))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -25,5 +64,8 @@ redis_module! {
     data_types: [],
     commands: [
         ["scan_keys", scan_keys, "readonly", 0, 0, 0, ""],
+        ["scan_keys_paged", scan_keys_paged, "readonly", 0, 0, 0, ""],
+        ["scan_keys_iter", scan_keys_iter, "readonly", 0, 0, 0, ""],
+        ["scan_key_names", scan_key_names, "readonly", 0, 0, 0, ""],
     ],
 }
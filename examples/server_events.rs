@@ -1,13 +1,17 @@
 use std::sync::atomic::{AtomicI64, Ordering};
 
 use redis_module::{
-    redis_module, server_events::FlushSubevent, Context, RedisResult, RedisString, RedisValue,
+    redis_module,
+    server_events::{FlushSubevent, ServerEvent},
+    Context, NextArg, RedisResult, RedisString, RedisValue,
 };
 use redis_module_macros::{config_changed_event_handler, cron_event_handler, flush_event_handler};
 
 static NUM_FLUSHES: AtomicI64 = AtomicI64::new(0);
 static NUM_CRONS: AtomicI64 = AtomicI64::new(0);
 static NUM_MAX_MEMORY_CONFIGURATION_CHANGES: AtomicI64 = AtomicI64::new(0);
+static NUM_CONFIGURATION_CHANGED_EVENTS: AtomicI64 = AtomicI64::new(0);
+static LAST_CONFIGURATION_CHANGED_EVENT_SIZE: AtomicI64 = AtomicI64::new(0);
 
 #[flush_event_handler]
 fn flushed_event_handler(_ctx: &Context, flush_event: FlushSubevent) {
@@ -18,6 +22,8 @@ fn flushed_event_handler(_ctx: &Context, flush_event: FlushSubevent) {
 
 #[config_changed_event_handler]
 fn config_changed_event_handler(_ctx: &Context, changed_configs: &[&str]) {
+    NUM_CONFIGURATION_CHANGED_EVENTS.fetch_add(1, Ordering::SeqCst);
+    LAST_CONFIGURATION_CHANGED_EVENT_SIZE.store(changed_configs.len() as i64, Ordering::SeqCst);
     changed_configs
         .iter()
         .find(|v| **v == "maxmemory")
@@ -43,6 +49,26 @@ fn num_maxmemory_changes(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult
     ))
 }
 
+fn config_changed_events(_ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    Ok(RedisValue::Array(vec![
+        RedisValue::Integer(NUM_CONFIGURATION_CHANGED_EVENTS.load(Ordering::SeqCst)),
+        RedisValue::Integer(LAST_CONFIGURATION_CHANGED_EVENT_SIZE.load(Ordering::SeqCst)),
+    ]))
+}
+
+fn flush_events(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let enable = args.next_i64()? != 0;
+    args.done()?;
+
+    if enable {
+        ctx.subscribe_server_event(ServerEvent::Flush)?;
+    } else {
+        ctx.unsubscribe_server_event(ServerEvent::Flush)?;
+    }
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -54,5 +80,7 @@ redis_module! {
         ["num_flushed", num_flushed, "readonly", 0, 0, 0, ""],
         ["num_max_memory_changes", num_maxmemory_changes, "readonly", 0, 0, 0, ""],
         ["num_crons", num_crons, "readonly", 0, 0, 0, ""],
+        ["config_changed_events", config_changed_events, "readonly", 0, 0, 0, ""],
+        ["flush_events", flush_events, "readonly", 0, 0, 0, ""],
     ],
 }
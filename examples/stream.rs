@@ -1,4 +1,5 @@
-use redis_module::raw::{KeyType, RedisModuleStreamID};
+use redis_module::raw::KeyType;
+use redis_module::stream::{StreamAddOption, StreamId};
 use redis_module::{
     redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
 };
@@ -17,22 +18,53 @@ fn stream_read_from(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
 
     let mut iter = stream.get_stream_iterator(false)?;
     let element = iter.next();
-    let id_to_keep = iter.next().as_ref().map_or_else(
-        || RedisModuleStreamID {
-            ms: u64::MAX,
-            seq: u64::MAX,
-        },
-        |e| e.id,
-    );
+    let id_to_keep = iter
+        .next()
+        .as_ref()
+        .map_or(StreamId::MAX, |e| StreamId::from(e.id));
 
     let stream = ctx.open_key_writable(&stream_key);
     stream.trim_stream_by_id(id_to_keep, false)?;
     Ok(match element {
-        Some(e) => RedisValue::BulkString(format!("{}-{}", e.id.ms, e.id.seq)),
+        Some(e) => RedisValue::BulkString(StreamId::from(e.id).to_string()),
         None => RedisValue::Null,
     })
 }
 
+fn stream_add(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 5 || args.len() % 2 == 0 {
+        return Err(RedisError::WrongArity);
+    }
+    let mut args = args.into_iter().skip(1);
+    let stream_key = args.next_arg()?;
+    let id = args.next_str()?;
+    let id = if id == "*" {
+        StreamAddOption::AutoId
+    } else {
+        StreamAddOption::Id(id.parse()?)
+    };
+    let args: Vec<RedisString> = args.collect();
+    let fields: Vec<(&str, &RedisString)> = args
+        .chunks(2)
+        .map(|pair| Ok((pair[0].try_as_str()?, &pair[1])))
+        .collect::<Result<_, RedisError>>()?;
+
+    let stream = ctx.open_key_writable(&stream_key);
+    let id = stream.stream_add(id, &fields)?;
+    Ok(RedisValue::BulkString(id.to_string()))
+}
+
+fn stream_delete(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let stream_key = args.next_arg()?;
+    let id: StreamId = args.next_str()?.parse()?;
+    args.done()?;
+
+    let stream = ctx.open_key_writable(&stream_key);
+    stream.stream_delete(id)?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -42,5 +74,7 @@ redis_module! {
     data_types: [],
     commands: [
         ["STREAM_POP", stream_read_from, "write", 1, 1, 1, ""],
+        ["STREAM_ADD", stream_add, "write deny-oom", 1, 1, 1, ""],
+        ["STREAM_DEL", stream_delete, "write", 1, 1, 1, ""],
     ],
 }
@@ -1,6 +1,8 @@
 use redis_module::{
-    redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+    redis_module, rformat, Context, NextArg, RedisError, RedisResult, RedisString,
+    RedisStringSliceExt, RedisValue,
 };
+use std::ptr::NonNull;
 
 fn string_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     if args.len() < 3 {
@@ -32,6 +34,52 @@ fn string_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(res)
 }
 
+fn string_format(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() < 3 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let mut args = args.into_iter().skip(1);
+    let prefix = args.next_arg()?;
+    let id = args.next_i64()?;
+
+    Ok(RedisValue::BulkRedisString(rformat!(
+        NonNull::new(ctx.ctx),
+        "{}:{}",
+        prefix,
+        id
+    )))
+}
+
+// Writes 'value' followed by the bytes 0xff 0x00 0xfe to the binary-safe key
+// name given in argv[1], and returns the written value.
+fn string_set_binary_key(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let value = args.next_arg()?;
+    args.done()?;
+
+    let mut value = RedisString::create_from_slice(ctx.ctx, value.as_slice());
+    value.append_bytes(&[0xff, 0x00, 0xfe]);
+
+    let key = ctx.open_key_writable(&key_name);
+    key.as_string_dma()?.write(value.as_slice())?;
+    Ok(RedisValue::BulkRedisString(value))
+}
+
+fn string_sort(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let by = args.next_str()?;
+    let mut values: Vec<RedisString> = args.collect();
+
+    match by.to_lowercase().as_str() {
+        "alpha" => values.sort_redis(),
+        "numeric" => values.sort_redis_by_numeric()?,
+        _ => return Err(RedisError::Str("ERR sort by must be ALPHA or NUMERIC")),
+    }
+    Ok(values.into())
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -42,5 +90,8 @@ redis_module! {
     commands: [
         ["string.set", string_set, "write fast deny-oom", 1, 1, 1, ""],
         ["string.get", string_get, "readonly", 1, 1, 1, ""],
+        ["string.format", string_format, "readonly", 0, 0, 0, ""],
+        ["string.sort", string_sort, "readonly", 0, 0, 0, ""],
+        ["string.set_binary_key", string_set_binary_key, "write", 1, 1, 1, ""],
     ],
 }
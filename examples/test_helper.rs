@@ -1,5 +1,5 @@
-use redis_module::{redis_module, Context, RedisError, RedisResult, RedisString};
-use redis_module::{InfoContext, Status};
+use redis_module::{logging::RedisLogLevel, InfoContext, Status};
+use redis_module::{raw, redis_module, Context, RedisError, RedisResult, RedisString};
 
 fn test_helper_version(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     let ver = ctx.get_redis_version()?;
@@ -15,6 +15,13 @@ fn test_helper_version_rm_call(ctx: &Context, _args: Vec<RedisString>) -> RedisR
     Ok(response.into())
 }
 
+fn test_helper_version_cached(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+    let ver = ctx.server_version_cached()?;
+    let response: Vec<i64> = vec![ver.major.into(), ver.minor.into(), ver.patch.into()];
+
+    Ok(response.into())
+}
+
 fn test_helper_command_name(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
     Ok(ctx.current_command_name()?.into())
 }
@@ -30,6 +37,18 @@ fn test_helper_err(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     Ok(().into())
 }
 
+fn test_helper_log(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    if args.len() != 2 {
+        return Err(RedisError::WrongArity);
+    }
+
+    // The message may hold null bytes, which must not fail the logging.
+    let msg = args[1].to_string_lossy();
+    ctx.log_notice(&msg);
+    raw::redis_log(ctx.ctx, RedisLogLevel::Notice, &msg);
+    Ok("OK".into())
+}
+
 fn add_info(ctx: &InfoContext, _for_crash_report: bool) {
     if ctx.add_info_section(Some("test_helper")) == Status::Ok {
         ctx.add_info_field_str("field", "value");
@@ -47,7 +66,9 @@ redis_module! {
     commands: [
         ["test_helper.version", test_helper_version, "", 0, 0, 0, ""],
         ["test_helper._version_rm_call", test_helper_version_rm_call, "", 0, 0, 0, ""],
+        ["test_helper.version_cached", test_helper_version_cached, "", 0, 0, 0, ""],
         ["test_helper.name", test_helper_command_name, "", 0, 0, 0, ""],
         ["test_helper.err", test_helper_err, "", 0, 0, 0, ""],
+        ["test_helper.log", test_helper_log, "", 0, 0, 0, ""],
     ],
 }
@@ -1,6 +1,6 @@
 use lazy_static::lazy_static;
 use redis_module::{
-    redis_module, Context, NextArg, RedisGILGuard, RedisResult, RedisString, RedisValue,
+    redis_module, Context, GilCell, NextArg, RedisGILGuard, RedisResult, RedisString, RedisValue,
     ThreadSafeContext,
 };
 use std::mem::drop;
@@ -57,6 +57,34 @@ fn get_static_data_on_thread(ctx: &Context, _args: Vec<RedisString>) -> RedisRes
     Ok(RedisValue::NoReply)
 }
 
+/// Increments the key from a thread, with a detached context of its own.
+fn detached_incr(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_string()?;
+    args.done()?;
+
+    let detached_ctx = ctx.create_detached_context();
+    let blocked_client = ctx.block_client();
+    let _ = thread::spawn(move || {
+        let res = detached_ctx.lock().call("INCR", &[key.as_str()]);
+        // The detached context is freed here, before replying.
+        drop(detached_ctx);
+        let thread_ctx = ThreadSafeContext::with_blocked_client(blocked_client);
+        thread_ctx.reply(res);
+    });
+
+    Ok(RedisValue::NoReply)
+}
+
+static ONCE_DATA: GilCell<String> = GilCell::new();
+
+fn init_once_data(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let val = args.next_string()?;
+    let once_data = ONCE_DATA.get_or_init(ctx, || val);
+    Ok(RedisValue::BulkString(once_data.clone()))
+}
+
 //////////////////////////////////////////////////////
 
 redis_module! {
@@ -69,5 +97,7 @@ redis_module! {
         ["set_static_data", set_static_data, "", 0, 0, 0, ""],
         ["get_static_data", get_static_data, "", 0, 0, 0, ""],
         ["get_static_data_on_thread", get_static_data_on_thread, "", 0, 0, 0, ""],
+        ["detached_incr", detached_incr, "", 0, 0, 0, ""],
+        ["init_once_data", init_once_data, "", 0, 0, 0, ""],
     ],
 }
@@ -0,0 +1,28 @@
+use redis_module::{alloc, redis_module, Context, NextArg, RedisError, RedisResult, RedisString};
+
+/// Fill a buffer of the requested size, rejecting the request instead of
+/// crashing the server if there is not enough memory for it.
+fn alloc_fill(_ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let size = args.next_u64()? as usize;
+    args.done()?;
+
+    let mut buf: Vec<u8> = unsafe { alloc::try_with_capacity(size) }.ok_or(RedisError::Str(
+        "ERR not enough memory for the requested size",
+    ))?;
+    buf.resize(size, b'x');
+
+    Ok((buf.len() as i64).into())
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "try_alloc",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["try_alloc.fill", alloc_fill, "", 0, 0, 0, ""],
+    ],
+}
@@ -0,0 +1,114 @@
+use redis_module::native_types::{rdb_load, RdbLoad, RedisType};
+use redis_module::{
+    raw, redis_module, Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+use std::os::raw::c_void;
+
+/// Version 1 of the type only stored the name, version 2 added the number
+/// of visits.
+#[derive(Debug)]
+struct Profile {
+    name: String,
+    visits: u64,
+}
+
+static PROFILE_TYPE: RedisType = RedisType::new(
+    "profile01",
+    2,
+    raw::RedisModuleTypeMethods {
+        version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+        rdb_load: Some(rdb_load::<Profile>),
+        rdb_save: Some(rdb_save),
+        aof_rewrite: None,
+        free: Some(free),
+
+        // Currently unused by Redis
+        mem_usage: None,
+        digest: None,
+
+        // Aux data
+        aux_load: None,
+        aux_save: None,
+        aux_save2: None,
+        aux_save_triggers: 0,
+
+        free_effort: None,
+        unlink: None,
+        copy: None,
+        defrag: None,
+
+        copy2: None,
+        free_effort2: None,
+        mem_usage2: None,
+        unlink2: None,
+    },
+);
+
+impl RdbLoad for Profile {
+    fn rdb_load(
+        rdb: *mut raw::RedisModuleIO,
+        encver: i32,
+    ) -> Result<Self, redis_module::error::Error> {
+        PROFILE_TYPE.check_encoding_version(encver)?;
+        let name = raw::load_string(rdb)?.to_string_lossy();
+        // Profiles saved by version 1 are upgraded with no visits.
+        let visits = if encver >= 2 {
+            raw::load_unsigned(rdb)?
+        } else {
+            0
+        };
+        Ok(Profile { name, visits })
+    }
+}
+
+unsafe extern "C" fn rdb_save(rdb: *mut raw::RedisModuleIO, value: *mut c_void) {
+    let profile = &*value.cast::<Profile>();
+    raw::save_string(rdb, &profile.name);
+    raw::save_unsigned(rdb, profile.visits);
+}
+
+unsafe extern "C" fn free(value: *mut c_void) {
+    drop(Box::from_raw(value.cast::<Profile>()));
+}
+
+fn profile_set(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    let name = args.next_string()?;
+    let visits = args.next_u64()?;
+    args.done()?;
+
+    let key = ctx.open_key_writable(&key);
+    key.set_value(&PROFILE_TYPE, Profile { name, visits })?;
+    Ok(RedisValue::SimpleStringStatic("OK"))
+}
+
+fn profile_get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key);
+    let profile = key
+        .get_value::<Profile>(&PROFILE_TYPE)?
+        .ok_or(RedisError::Str("ERR no such profile"))?;
+    Ok(RedisValue::Array(vec![
+        profile.name.as_str().into(),
+        RedisValue::Integer(profile.visits as i64),
+    ]))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "type_migration",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [
+        PROFILE_TYPE,
+    ],
+    commands: [
+        ["profile.set", profile_set, "write", 1, 1, 1, ""],
+        ["profile.get", profile_get, "readonly", 1, 1, 1, ""],
+    ],
+}
@@ -0,0 +1,108 @@
+use redis_module::{
+    redis_module,
+    zset::{ZaddFlags, ZsetRangeIterator},
+    Context, NextArg, RedisError, RedisResult, RedisString, RedisValue,
+};
+
+// ZADD.NATIVE key score member [NX|XX] [GT|LT]
+// Adds 'member' to the sorted set stored at 'key', and returns its score
+// after the call.
+fn zadd_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let score = args.next_f64()?;
+    let member = args.next_arg()?;
+    let flags = args.try_fold(ZaddFlags::empty(), |flags, arg| {
+        let flag = match arg.to_string_lossy().to_uppercase().as_str() {
+            "NX" => ZaddFlags::NX,
+            "XX" => ZaddFlags::XX,
+            "GT" => ZaddFlags::GT,
+            "LT" => ZaddFlags::LT,
+            _ => return Err(RedisError::Str("ERR syntax error")),
+        };
+        Ok(flags | flag)
+    })?;
+
+    let key = ctx.open_key_writable(&key_name);
+    Ok(key
+        .zset_add(score, &member, flags)?
+        .map_or(RedisValue::Null, RedisValue::Float))
+}
+
+// ZSCORE.NATIVE key member
+fn zscore_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let member = args.next_arg()?;
+    args.done()?;
+
+    let key = ctx.open_key(&key_name);
+    Ok(key
+        .zset_score(&member)
+        .map_or(RedisValue::Null, RedisValue::Float))
+}
+
+// Parse the optional EXCLUSIVE and REV arguments of the range commands.
+fn range_options(mut args: impl Iterator<Item = RedisString>) -> Result<(bool, bool), RedisError> {
+    args.try_fold((false, false), |(exclusive, reverse), arg| {
+        match arg.to_string_lossy().to_uppercase().as_str() {
+            "EXCLUSIVE" => Ok((true, reverse)),
+            "REV" => Ok((exclusive, true)),
+            _ => Err(RedisError::Str("ERR syntax error")),
+        }
+    })
+}
+
+fn range_reply(range: ZsetRangeIterator) -> RedisValue {
+    RedisValue::Array(
+        range
+            .flat_map(|(member, score)| [member.into(), RedisValue::Float(score)])
+            .collect(),
+    )
+}
+
+// ZRANGEBYSCORE.NATIVE key min max [EXCLUSIVE] [REV]
+// Returns the members whose score is between 'min' and 'max', with their scores.
+fn zrangebyscore_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let min = args.next_f64()?;
+    let max = args.next_f64()?;
+    let (exclusive, reverse) = range_options(args)?;
+
+    let mut key = ctx.open_key(&key_name);
+    let range = key.zset_score_range_iter(min, max, exclusive, reverse)?;
+    Ok(range_reply(range))
+}
+
+// ZRANGEBYLEX.NATIVE key min max [REV]
+// Returns the members between 'min' and 'max', with their scores.
+fn zrangebylex_native(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+    let key_name = args.next_arg()?;
+    let min = args.next_arg()?;
+    let max = args.next_arg()?;
+    let (exclusive, reverse) = range_options(args)?;
+    if exclusive {
+        return Err(RedisError::Str("ERR lex bounds set their own exclusivity"));
+    }
+
+    let mut key = ctx.open_key(&key_name);
+    let range = key.zset_lex_range_iter(&min, &max, reverse)?;
+    Ok(range_reply(range))
+}
+
+//////////////////////////////////////////////////////
+
+redis_module! {
+    name: "zset",
+    version: 1,
+    allocator: (redis_module::alloc::RedisAlloc, redis_module::alloc::RedisAlloc),
+    data_types: [],
+    commands: [
+        ["ZADD.NATIVE", zadd_native, "write deny-oom", 1, 1, 1, ""],
+        ["ZSCORE.NATIVE", zscore_native, "readonly", 1, 1, 1, ""],
+        ["ZRANGEBYSCORE.NATIVE", zrangebyscore_native, "readonly", 1, 1, 1, ""],
+        ["ZRANGEBYLEX.NATIVE", zrangebylex_native, "readonly", 1, 1, 1, ""],
+    ],
+}
@@ -234,6 +234,10 @@ struct Args {
     tips: Option<String>,
     arity: i64,
     key_spec: Vec<KeySpecArg>,
+    metrics: Option<bool>,
+    deny_during_loading: Option<bool>,
+    replicate_effects: Option<bool>,
+    enforce_arity: Option<bool>,
 }
 
 impl Parse for Args {
@@ -358,9 +362,91 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
         })
         .collect();
 
+    // With `metrics: true` the handler is wrapped with a `CommandMetrics`,
+    // registered together with the command.
+    let (metrics_static, metrics_register, call_handler) = if args.metrics.unwrap_or(false) {
+        let metrics_name = Ident::new(
+            &format!("_INNER_METRICS_{}", func.sig.ident).to_uppercase(),
+            func.sig.ident.span(),
+        );
+        let field_prefix: String = name_literal
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let calls_name = format!("cmd_{field_prefix}_calls");
+        let errors_name = format!("cmd_{field_prefix}_errors");
+        let usec_name = format!("cmd_{field_prefix}_usec");
+        (
+            quote! {
+                static #metrics_name: redis_module::metrics::CommandMetrics =
+                    redis_module::metrics::CommandMetrics::new(#calls_name, #errors_name, #usec_name);
+            },
+            quote! {
+                #metrics_name.register();
+            },
+            quote! {
+                #metrics_name.measure(|| #original_function_name(&context, args))
+            },
+        )
+    } else {
+        (
+            quote! {},
+            quote! {},
+            quote! {
+                #original_function_name(&context, args)
+            },
+        )
+    };
+
+    // With `replicate_effects: true` the commands called by the handler are
+    // replicated, see `Context::with_replicated_effects`.
+    let call_handler = if args.replicate_effects.unwrap_or(false) {
+        quote! {
+            context.with_replicated_effects(|| #call_handler)
+        }
+    } else {
+        call_handler
+    };
+
+    // With `deny_during_loading: true` the handler is not called while the
+    // server is loading, see `Context::refuse_if_loading`.
+    let call_handler = if args.deny_during_loading.unwrap_or(false) {
+        quote! {
+            match context.refuse_if_loading() {
+                Ok(()) => #call_handler,
+                Err(e) => Err(e.into()),
+            }
+        }
+    } else {
+        call_handler
+    };
+
+    // With `enforce_arity: true` the handler is only called with a number
+    // of arguments matching the arity, a negative arity being a minimum.
+    let call_handler = if args.enforce_arity.unwrap_or(false) {
+        let arity_check = if arity_literal >= 0 {
+            let arity = arity_literal as usize;
+            quote! { args.len() == #arity }
+        } else {
+            let min_arity = arity_literal.unsigned_abs() as usize;
+            quote! { args.len() >= #min_arity }
+        };
+        quote! {
+            if #arity_check {
+                #call_handler
+            } else {
+                Err(redis_module::RedisError::WrongArity.into())
+            }
+        }
+    } else {
+        call_handler
+    };
+
     let gen = quote! {
         #func
 
+        #metrics_static
+
         extern "C" fn #c_function_name(
             ctx: *mut redis_module::raw::RedisModuleCtx,
             argv: *mut *mut redis_module::raw::RedisModuleString,
@@ -369,12 +455,13 @@ pub(crate) fn redis_command(attr: TokenStream, item: TokenStream) -> TokenStream
             let context = redis_module::Context::new(ctx);
 
             let args = redis_module::decode_args(ctx, argv, argc);
-            let response = #original_function_name(&context, args);
+            let response = #call_handler;
             context.reply(response.map(|v| v.into())) as i32
         }
 
         #[linkme::distributed_slice(redis_module::commands::COMMANDS_LIST)]
         fn #get_command_info_function_name() -> Result<redis_module::commands::CommandInfo, redis_module::RedisError> {
+            #metrics_register
             let key_spec = vec![
                 #(
                     redis_module::commands::KeySpec::new(
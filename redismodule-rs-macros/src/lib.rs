@@ -4,6 +4,7 @@ use syn::ItemFn;
 
 mod command;
 mod info_section;
+mod redis_hash;
 mod redis_value;
 
 /// This proc macro allow to specify that the follow function is a Redis command.
@@ -56,6 +57,19 @@ mod redis_value;
 ///            which case it should be set to `keynumidx + 1`.)
 ///          * keystep - How many arguments should we skip after finding a
 ///            key, in order to find the next one?
+/// * metrics (optional) - If `true`, the number of calls, the number of errors and the accumulated
+///   latency of the command are exported in the module's INFO output as `cmd_<name>_calls`,
+///   `cmd_<name>_errors` and `cmd_<name>_usec`, see `redis_module::metrics::CommandMetrics`.
+/// * deny_during_loading (optional) - If `true`, the command replies with a `LOADING` error while the
+///   server is loading its dataset, see `redis_module::Context::refuse_if_loading`. Only useful together
+///   with the `AllowLoading` flag, as Redis does not call other commands while loading.
+/// * replicate_effects (optional) - If `true`, the commands invoked by the handler with `Context::call`
+///   are replicated to the replicas and the AOF, instead of the command itself, see
+///   `redis_module::Context::with_replicated_effects`.
+/// * enforce_arity (optional) - If `true`, the handler is only called when the number of arguments
+///   (including the command name) matches `arity`, or is at least `-arity` for a negative arity.
+///   Otherwise the command replies with a wrong arity error, so the handler can index `args`
+///   without checking its length.
 ///
 /// Example:
 /// The following example will register a command called `foo`.
@@ -311,6 +325,16 @@ pub fn cron_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream
 /// about the field. Supported attributes:
 ///
 /// * flatten - indicate to inlines keys from a field into the parent struct.
+/// * rename - the key of the field in the reply, instead of the field name,
+///   for example `#[RedisValueAttr{rename: "maxLen"}]`. Can not be set on a
+///   flatten field.
+/// * skip - leave the field out of the reply, its type does not need to
+///   implement `Into<RedisValue>`.
+/// * skip_if_none - leave an `Option` field out of the reply when it is
+///   `None`, instead of replying a null value.
+///
+/// `Option` fields are replied as null when they are `None`, and as their
+/// inner value otherwise.
 ///
 /// Example:
 ///
@@ -358,11 +382,59 @@ pub fn cron_event_handler(_attr: TokenStream, item: TokenStream) -> TokenStream
 /// 2# "i2" => 10
 /// ```
 ///
+/// The fields are replied sorted by name. To reply them in the order they are
+/// declared instead, set the `insertion_ordered` attribute on the struct, which
+/// generates a [RedisValue::InsertionOrderedMap]:
+///
+/// ```rust,no_run,ignore
+/// #[derive(RedisValue)]
+/// #[RedisValueAttr{insertion_ordered: true}]
+/// struct RedisValueDerive {
+///     name: String,
+///     #[RedisValueAttr{flatten: true}]
+///     inner: RedisValueDeriveInner,
+///     age: i64,
+/// }
+/// ```
+///
+/// Replies `1# "name"`, `2# "i2"` and `3# "age"`, the flatten fields are
+/// inlined at the position of the field.
+///
 #[proc_macro_derive(RedisValue, attributes(RedisValueAttr))]
 pub fn redis_value(item: TokenStream) -> TokenStream {
     redis_value::redis_value(item)
 }
 
+/// Generates `store` and `load` functions that map a struct with named
+/// fields to the fields of a Redis hash, and back.
+///
+/// Each struct field is kept in the hash field of the same name. Values are
+/// written using [ToString] and read back using [std::str::FromStr], so every
+/// field type must implement both. Fields of type [Option] are optional: `None`
+/// removes the hash field on `store`, and a missing hash field loads as `None`.
+/// A missing non optional field makes `load` fail.
+///
+/// Example:
+///
+/// ```rust,no_run,ignore
+/// #[derive(RedisHash)]
+/// struct Settings {
+///     name: String,
+///     limit: i64,
+///     owner: Option<String>,
+/// }
+///
+/// fn settings_copy(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+///     let settings = Settings::load(&ctx.open_key(&args[1]))?;
+///     settings.store(&ctx.open_key_writable(&args[2]))?;
+///     Ok(RedisValue::SimpleStringStatic("OK"))
+/// }
+/// ```
+#[proc_macro_derive(RedisHash)]
+pub fn redis_hash(item: TokenStream) -> TokenStream {
+    redis_hash::redis_hash(item)
+}
+
 /// A procedural macro which registers this function as the custom
 /// `INFO` command handler. There might be more than one handler, each
 /// adding new information to the context.
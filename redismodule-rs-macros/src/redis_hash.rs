@@ -0,0 +1,135 @@
+use proc_macro::TokenStream;
+use proc_macro2::Ident;
+use quote::quote;
+use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Fields, Type};
+
+/// Returns `true` if the given type is an [Option].
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Generate the `store` and `load` functions for a struct. Each named field
+/// is kept in a hash field with the same name, the values are written using
+/// [ToString] and read back using [std::str::FromStr].
+fn struct_redis_hash(struct_name: Ident, struct_data: DataStruct) -> TokenStream {
+    let fields = match struct_data.fields {
+        Fields::Named(f) => f,
+        _ => {
+            return quote! {compile_error!("RedisHash derive can only be apply on struct with named fields.")}.into()
+        }
+    };
+
+    let fields = fields
+        .named
+        .into_iter()
+        .map(|v| (v.ident.unwrap(), is_option(&v.ty)))
+        .collect::<Vec<_>>();
+
+    let fields_names: Vec<_> = fields.iter().map(|(name, _)| name.to_string()).collect();
+
+    let store_fields = fields.iter().zip(&fields_names).map(|((field, optional), name)| {
+        if *optional {
+            quote! {
+                match &self.#field {
+                    Some(v) => set.push((#name, redis_module::RedisString::create_from_slice(std::ptr::null_mut(), v.to_string().as_bytes()))),
+                    None => del.push(#name),
+                }
+            }
+        } else {
+            quote! {
+                set.push((#name, redis_module::RedisString::create_from_slice(std::ptr::null_mut(), self.#field.to_string().as_bytes())));
+            }
+        }
+    });
+
+    let load_fields = fields.iter().zip(&fields_names).map(|((field, optional), name)| {
+        let parse = quote! {
+            v.try_as_str()?.parse().map_err(|_| {
+                redis_module::RedisError::String(format!("Failed parsing hash field '{}'", #name))
+            })?
+        };
+        if *optional {
+            quote! {
+                #field: match values.next().flatten() {
+                    Some(v) => Some(#parse),
+                    None => None,
+                }
+            }
+        } else {
+            quote! {
+                #field: {
+                    let v = values.next().flatten().ok_or_else(|| {
+                        redis_module::RedisError::String(format!("Missing hash field '{}'", #name))
+                    })?;
+                    #parse
+                }
+            }
+        }
+    });
+
+    let res = quote! {
+        impl #struct_name {
+            /// Write all the fields into the hash stored at `key`. Fields set
+            /// to `None` are removed from the hash.
+            pub fn store(
+                &self,
+                key: &redis_module::key::RedisKeyWritable,
+            ) -> Result<(), redis_module::RedisError> {
+                match key.key_type() {
+                    redis_module::KeyType::Empty | redis_module::KeyType::Hash => {}
+                    _ => return Err(redis_module::RedisError::WrongType),
+                }
+                let mut set: Vec<(&str, redis_module::RedisString)> = Vec::new();
+                let mut del: Vec<&str> = Vec::new();
+                #(#store_fields)*
+                // `hash_set` and `hash_del` return the number of updated
+                // fields, the multi versions report the actual failures.
+                if key.hash_set_multi(&set) == redis_module::Status::Err {
+                    return Err(redis_module::RedisError::Str("ERR failed writing the hash fields"));
+                }
+                if !del.is_empty() {
+                    key.hash_del_multi(&del)?;
+                }
+                Ok(())
+            }
+
+            /// Read the struct from the hash stored at `key`. A missing key is
+            /// treated as an empty hash.
+            pub fn load(key: &redis_module::key::RedisKey) -> Result<Self, redis_module::RedisError> {
+                const FIELDS: &[&str] = &[#(#fields_names),*];
+                let values: Vec<Option<redis_module::RedisString>> =
+                    match key.hash_get_multi::<_, redis_module::RedisString>(FIELDS)? {
+                        Some(res) => res
+                            .into_vec_with_misses()
+                            .into_iter()
+                            .map(|(_, v)| v)
+                            .collect(),
+                        None => FIELDS.iter().map(|_| None).collect(),
+                    };
+                let mut values = values.into_iter();
+                Ok(Self {
+                    #(#load_fields,)*
+                })
+            }
+        }
+    };
+    res.into()
+}
+
+/// Implementation for [RedisHash] derive proc macro.
+/// Generates `store` and `load` functions for a struct with named fields.
+pub fn redis_hash(item: TokenStream) -> TokenStream {
+    let struct_input: DeriveInput = parse_macro_input!(item);
+    let struct_name = struct_input.ident;
+    match struct_input.data {
+        Data::Struct(s) => struct_redis_hash(struct_name, s),
+        _ => quote! {compile_error!("RedisHash derive can only be apply on struct.")}.into(),
+    }
+}
@@ -6,7 +6,7 @@ use serde_syn::{config, from_stream};
 use syn::{
     parse,
     parse::{Parse, ParseStream},
-    parse_macro_input, Data, DataEnum, DataStruct, DeriveInput, Fields,
+    parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields,
 };
 
 /// Generate [From] implementation for [RedisValue] for Enum.
@@ -36,7 +36,16 @@ fn enum_redis_value(struct_name: Ident, enum_data: DataEnum) -> TokenStream {
 /// Represent a single field attributes
 #[derive(Debug, Deserialize, Default)]
 struct FieldAttr {
+    #[serde(default)]
     flatten: bool,
+    /// The map key of the field, instead of the field name.
+    rename: Option<String>,
+    /// Leave the field out of the map.
+    #[serde(default)]
+    skip: bool,
+    /// Leave an `Option` field out of the map when it is `None`.
+    #[serde(default)]
+    skip_if_none: bool,
 }
 
 impl Parse for FieldAttr {
@@ -45,11 +54,44 @@ impl Parse for FieldAttr {
     }
 }
 
+/// Represent the attributes set on the struct itself
+#[derive(Debug, Deserialize, Default)]
+struct StructAttr {
+    insertion_ordered: bool,
+}
+
+impl Parse for StructAttr {
+    fn parse(input: ParseStream) -> parse::Result<Self> {
+        from_stream(config::JSONY, input)
+    }
+}
+
+fn struct_attr(attrs: Vec<Attribute>) -> Result<StructAttr, String> {
+    let mut attrs = attrs
+        .into_iter()
+        .filter(|attr| attr.path.is_ident("RedisValueAttr"));
+    let struct_attr = attrs.next().map_or(Ok(StructAttr::default()), |attr| {
+        parse_macro_input::parse(attr.tokens.into()).map_err(|e| format!("{e}"))
+    })?;
+    if attrs.next().is_some() {
+        return Err("Expected at most a single attribute for the struct".to_owned());
+    }
+    Ok(struct_attr)
+}
+
 /// Generate [From] implementation for [RedisValue] for a struct.
-/// The generated code will create a [RedisValue::Map] element such that
-/// the keys are the fields names and the value are the result of
-/// running [Into] on each field value to convert it to [RedisValue].
-fn struct_redis_value(struct_name: Ident, struct_data: DataStruct) -> TokenStream {
+/// The generated code will create a [RedisValue::OrderedMap] element such that
+/// the keys are the fields names (or their `rename` attribute) and the value
+/// are the result of running [Into] on each field value to convert it to
+/// [RedisValue].
+/// With the `insertion_ordered` struct attribute, a
+/// [RedisValue::InsertionOrderedMap] is created instead, keeping the fields
+/// in the order they are declared.
+fn struct_redis_value(
+    struct_name: Ident,
+    attrs: Vec<Attribute>,
+    struct_data: DataStruct,
+) -> TokenStream {
     let fields = match struct_data.fields {
         Fields::Named(f) => f,
         _ => {
@@ -57,6 +99,11 @@ fn struct_redis_value(struct_name: Ident, struct_data: DataStruct) -> TokenStrea
         }
     };
 
+    let struct_attr = match struct_attr(attrs) {
+        Ok(a) => a,
+        Err(e) => return quote! {compile_error!(#e)}.into(),
+    };
+
     let fields = fields
         .named
         .into_iter()
@@ -76,8 +123,17 @@ fn struct_redis_value(struct_name: Ident, struct_data: DataStruct) -> TokenStrea
                     Ok(field_attr)
                 },
             )?;
+            if field_attr.flatten && field_attr.rename.is_some() {
+                return Err(format!("Flatten field {name} can not be renamed"));
+            }
+            if field_attr.flatten && field_attr.skip_if_none {
+                return Err(format!("Flatten field {name} can not be skipped if none"));
+            }
             Ok((name, field_attr))
         })
+        // Skipped fields are not converted, so their type does not need to
+        // implement `Into<RedisValue>`.
+        .filter(|field| !matches!(field, Ok((_, attr)) if attr.skip))
         .collect::<Result<Vec<_>, String>>();
 
     let fields = match fields {
@@ -85,52 +141,139 @@ fn struct_redis_value(struct_name: Ident, struct_data: DataStruct) -> TokenStrea
         Err(e) => return quote! {compile_error!(#e)}.into(),
     };
 
-    let (fields, flattem_fields) = fields.into_iter().fold(
-        (Vec::new(), Vec::new()),
-        |(mut fields, mut flatten_fields), (field, attr)| {
+    if struct_attr.insertion_ordered {
+        return insertion_ordered_struct_redis_value(struct_name, fields);
+    }
+
+    let (fields, optional_fields, flattem_fields) = fields.into_iter().fold(
+        (Vec::new(), Vec::new(), Vec::new()),
+        |(mut fields, mut optional_fields, mut flatten_fields), (field, attr)| {
             if attr.flatten {
                 flatten_fields.push(field);
+            } else if attr.skip_if_none {
+                let field_name = field_key(&field, attr);
+                optional_fields.push((field, field_name));
             } else {
-                fields.push(field);
+                let field_name = field_key(&field, attr);
+                fields.push((field, field_name));
             }
 
-            (fields, flatten_fields)
+            (fields, optional_fields, flatten_fields)
         },
     );
 
-    let fields_names: Vec<_> = fields.iter().map(|v| v.to_string()).collect();
+    let (fields, fields_names): (Vec<_>, Vec<_>) = fields.into_iter().unzip();
+    let (optional_fields, optional_fields_names): (Vec<_>, Vec<_>) =
+        optional_fields.into_iter().unzip();
 
+    // The map conversion also includes the flatten fields, so a flatten
+    // field can itself have flatten fields. The entries conversion allows
+    // flattening this struct into an insertion ordered one.
     let res = quote! {
         impl From<#struct_name> for redis_module::redisvalue::RedisValue {
             fn from(val: #struct_name) -> redis_module::redisvalue::RedisValue {
+                redis_module::redisvalue::RedisValue::OrderedMap(val.into())
+            }
+        }
+
+        impl From<#struct_name> for std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> {
+            fn from(val: #struct_name) -> std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> {
                 let mut fields: std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> = std::collections::BTreeMap::from([
                     #((
                         redis_module::redisvalue::RedisValueKey::String(#fields_names.to_owned()),
                         val.#fields.into()
                     ), )*
                 ]);
+                #(
+                    if let Some(value) = val.#optional_fields {
+                        fields.insert(
+                            redis_module::redisvalue::RedisValueKey::String(#optional_fields_names.to_owned()),
+                            value.into(),
+                        );
+                    }
+                )*
                 #(
                     let flatten_field: std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> = val.#flattem_fields.into();
                     fields.extend(flatten_field.into_iter());
                 )*
-                redis_module::redisvalue::RedisValue::OrderedMap(fields)
+                fields
+            }
+        }
+
+        impl From<#struct_name> for Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> {
+            fn from(val: #struct_name) -> Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> {
+                let fields: std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> = val.into();
+                fields.into_iter().collect()
+            }
+        }
+    };
+    res.into()
+}
+
+/// Generate the [From] implementations of a struct with the `insertion_ordered`
+/// attribute. The fields, and the fields of the flatten fields, are added to
+/// the entries of a [RedisValue::InsertionOrderedMap] in declaration order.
+fn insertion_ordered_struct_redis_value(
+    struct_name: Ident,
+    fields: Vec<(Ident, FieldAttr)>,
+) -> TokenStream {
+    let entries = fields.into_iter().map(|(field, attr)| {
+        if attr.flatten {
+            quote! {
+                let flatten_field: Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> = val.#field.into();
+                fields.extend(flatten_field.into_iter());
+            }
+        } else if attr.skip_if_none {
+            let field_name = field_key(&field, attr);
+            quote! {
+                if let Some(value) = val.#field {
+                    fields.push((
+                        redis_module::redisvalue::RedisValueKey::String(#field_name.to_owned()),
+                        value.into(),
+                    ));
+                }
+            }
+        } else {
+            let field_name = field_key(&field, attr);
+            quote! {
+                fields.push((
+                    redis_module::redisvalue::RedisValueKey::String(#field_name.to_owned()),
+                    val.#field.into(),
+                ));
+            }
+        }
+    });
+
+    let res = quote! {
+        impl From<#struct_name> for redis_module::redisvalue::RedisValue {
+            fn from(val: #struct_name) -> redis_module::redisvalue::RedisValue {
+                redis_module::redisvalue::RedisValue::InsertionOrderedMap(val.into())
+            }
+        }
+
+        impl From<#struct_name> for Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> {
+            fn from(val: #struct_name) -> Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> {
+                let mut fields: Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> = Vec::new();
+                #(#entries)*
+                fields
             }
         }
 
         impl From<#struct_name> for std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> {
             fn from(val: #struct_name) -> std::collections::BTreeMap<redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue> {
-                std::collections::BTreeMap::from([
-                    #((
-                        redis_module::redisvalue::RedisValueKey::String(#fields_names.to_owned()),
-                        val.#fields.into()
-                    ), )*
-                ])
+                let fields: Vec<(redis_module::redisvalue::RedisValueKey, redis_module::redisvalue::RedisValue)> = val.into();
+                fields.into_iter().collect()
             }
         }
     };
     res.into()
 }
 
+/// The map key of a field, its `rename` attribute or its name.
+fn field_key(field: &Ident, attr: FieldAttr) -> String {
+    attr.rename.unwrap_or_else(|| field.to_string())
+}
+
 /// Implementation for [RedisValue] derive proc macro.
 /// Runs the relevant code generation base on the element
 /// the proc macro was used on. Currently supports Enums and
@@ -139,7 +282,7 @@ pub fn redis_value(item: TokenStream) -> TokenStream {
     let struct_input: DeriveInput = parse_macro_input!(item);
     let struct_name = struct_input.ident;
     match struct_input.data {
-        Data::Struct(s) => struct_redis_value(struct_name, s),
+        Data::Struct(s) => struct_redis_value(struct_name, struct_input.attrs, s),
         Data::Enum(e) => enum_redis_value(struct_name, e),
         _ => quote! {compile_error!("RedisValue derive can only be apply on struct.")}.into(),
     }
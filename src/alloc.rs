@@ -53,3 +53,46 @@ unsafe impl GlobalAlloc for RedisAlloc {
         };
     }
 }
+
+/// Allocate memory for `layout` with the Redis allocator, returning `None`
+/// instead of crashing the server if the allocation fails. This is meant
+/// for large allocations whose size is controlled by the user, so a command
+/// can reject the request gracefully.
+///
+/// The memory must be released with `RedisModule_Free` (or with
+/// [RedisAlloc::dealloc]). On Redis versions without `RedisModule_TryAlloc`
+/// this falls back to `RedisModule_Alloc`, which does not fail gracefully.
+pub fn try_alloc(layout: Layout) -> Option<*mut u8> {
+    let size = (layout.size() + layout.align() - 1) & (!(layout.align() - 1));
+
+    let ptr: *mut u8 = unsafe {
+        match (raw::RedisModule_TryAlloc, raw::RedisModule_Alloc) {
+            (Some(try_alloc), _) => try_alloc(size).cast(),
+            (None, Some(alloc)) => alloc(size).cast(),
+            (None, None) => allocation_free_panic(REDIS_ALLOCATOR_NOT_AVAILABLE_MESSAGE),
+        }
+    };
+    if ptr.is_null() {
+        None
+    } else {
+        Some(ptr)
+    }
+}
+
+/// Create an empty [Vec] able to hold `capacity` elements without
+/// reallocating, or `None` if the memory could not be allocated (see
+/// [try_alloc]).
+///
+/// # Safety
+///
+/// The vector is allocated with the Redis allocator but freed (or grown)
+/// by the global allocator, so [RedisAlloc] must be the global allocator,
+/// which is what [crate::redis_module] sets up by default.
+pub unsafe fn try_with_capacity<T>(capacity: usize) -> Option<Vec<T>> {
+    let layout = Layout::array::<T>(capacity).ok()?;
+    if layout.size() == 0 {
+        return Some(Vec::with_capacity(capacity));
+    }
+    let ptr = try_alloc(layout)?;
+    Some(Vec::from_raw_parts(ptr.cast::<T>(), 0, capacity))
+}
@@ -1,10 +1,18 @@
+use std::collections::BTreeMap;
+use std::os::raw::{c_int, c_longlong, c_void};
 use std::ptr;
+use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::raw;
-use crate::Context;
+use crate::{Context, RedisResult, RedisValue};
 
 pub struct BlockedClient {
     pub(crate) inner: *mut raw::RedisModuleBlockedClient,
+    client_id: u64,
+    /// Passed to `RedisModule_UnblockClient`, the [BlockedClientCallbacks] of
+    /// clients blocked with [Context::block_client_with_timeout].
+    privdata: *mut c_void,
 }
 
 // We need to be able to send the inner pointer to another thread
@@ -12,7 +20,63 @@ unsafe impl Send for BlockedClient {}
 
 impl Drop for BlockedClient {
     fn drop(&mut self) {
-        unsafe { raw::RedisModule_UnblockClient.unwrap()(self.inner, ptr::null_mut()) };
+        unsafe { raw::RedisModule_UnblockClient.unwrap()(self.inner, self.privdata) };
+    }
+}
+
+impl BlockedClient {
+    /// Return the id of the blocked client, the one returned by
+    /// [Context::get_client_id] for the context it was blocked from.
+    pub fn client_id(&self) -> u64 {
+        self.client_id
+    }
+}
+
+type TimeoutCallback = Box<dyn FnOnce(&Context) -> RedisResult>;
+type FreePrivdataCallback = Box<dyn FnOnce(&Context)>;
+
+/// The callbacks given to [Context::block_client_with_timeout], they are only
+/// called, and dropped, from the main thread.
+struct BlockedClientCallbacks {
+    blocked_client: usize,
+    on_timeout: Option<TimeoutCallback>,
+    free_privdata: Option<FreePrivdataCallback>,
+}
+
+/// The [BlockedClientCallbacks] of the clients which were not unblocked yet,
+/// by blocked client handle. The timeout callback only gets the handle, as the
+/// private data is only passed to Redis when the client is unblocked.
+static BLOCKED_CLIENT_CALLBACKS: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+extern "C" fn blocked_client_timeout(
+    ctx: *mut raw::RedisModuleCtx,
+    _argv: *mut *mut raw::RedisModuleString,
+    _argc: c_int,
+) -> c_int {
+    let context = Context::new(ctx);
+    let blocked_client = unsafe { raw::RedisModule_GetBlockedClientHandle.unwrap()(ctx) } as usize;
+    let callbacks = BLOCKED_CLIENT_CALLBACKS
+        .lock()
+        .unwrap()
+        .get(&blocked_client)
+        .copied();
+    let on_timeout = callbacks.and_then(|callbacks| unsafe {
+        (*(callbacks as *mut BlockedClientCallbacks))
+            .on_timeout
+            .take()
+    });
+    let res = on_timeout.map_or(Ok(RedisValue::Null), |on_timeout| on_timeout(&context));
+    context.reply(res) as c_int
+}
+
+extern "C" fn blocked_client_free(ctx: *mut raw::RedisModuleCtx, privdata: *mut c_void) {
+    let callbacks = unsafe { Box::from_raw(privdata.cast::<BlockedClientCallbacks>()) };
+    BLOCKED_CLIENT_CALLBACKS
+        .lock()
+        .unwrap()
+        .remove(&callbacks.blocked_client);
+    if let Some(free_privdata) = callbacks.free_privdata {
+        free_privdata(&Context::new(ctx));
     }
 }
 
@@ -30,6 +94,60 @@ impl Context {
 
         BlockedClient {
             inner: blocked_client,
+            client_id: self.get_client_id(),
+            privdata: ptr::null_mut(),
+        }
+    }
+
+    /// Same as [Context::block_client], but if the client is not unblocked
+    /// within `timeout` (a zero `timeout` never expires), `on_timeout` is
+    /// called and its result is replied to the client instead.
+    ///
+    /// `free_privdata` is called exactly once, when the returned
+    /// [BlockedClient] is dropped, whether the client timed out or not, so it
+    /// can release the resources of the blocking operation. Both callbacks are
+    /// called from the main thread.
+    #[must_use]
+    pub fn block_client_with_timeout<F, G>(
+        &self,
+        timeout: Duration,
+        on_timeout: F,
+        free_privdata: G,
+    ) -> BlockedClient
+    where
+        F: FnOnce(&Context) -> RedisResult + 'static,
+        G: FnOnce(&Context) + 'static,
+    {
+        // Round up, so a timeout below a millisecond does not become a zero
+        // timeout which never expires.
+        let timeout_ms = match timeout.as_millis() {
+            0 if !timeout.is_zero() => 1,
+            ms => c_longlong::try_from(ms).unwrap_or(c_longlong::MAX),
+        };
+        let blocked_client = unsafe {
+            raw::RedisModule_BlockClient.unwrap()(
+                self.ctx,
+                None,
+                Some(blocked_client_timeout),
+                Some(blocked_client_free),
+                timeout_ms,
+            )
+        };
+
+        let callbacks = Box::into_raw(Box::new(BlockedClientCallbacks {
+            blocked_client: blocked_client as usize,
+            on_timeout: Some(Box::new(on_timeout)),
+            free_privdata: Some(Box::new(free_privdata)),
+        }));
+        BLOCKED_CLIENT_CALLBACKS
+            .lock()
+            .unwrap()
+            .insert(blocked_client as usize, callbacks as usize);
+
+        BlockedClient {
+            inner: blocked_client,
+            client_id: self.get_client_id(),
+            privdata: callbacks.cast::<c_void>(),
         }
     }
 }
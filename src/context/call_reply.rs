@@ -1,4 +1,5 @@
 use core::slice;
+use std::collections::HashMap;
 use std::os::raw::c_char;
 use std::{
     fmt,
@@ -69,6 +70,10 @@ impl<'root> ErrorCallReply<'root> {
         String::from_utf8(self.as_bytes().to_vec()).ok()
     }
 
+    pub(crate) fn as_ptr(&self) -> *mut RedisModuleCallReply {
+        self.reply.as_ptr()
+    }
+
     /// Return the ErrorCallReply data as &[u8]
     pub fn as_bytes(&self) -> &[u8] {
         let mut len: usize = 0;
@@ -299,12 +304,50 @@ impl<'root> MapCallReply<'root> {
         ))
     }
 
+    /// Return an iterator over the elements in the [MapCallReply] whose
+    /// keys are strings (`CONFIG GET` like replies), the keys are borrowed
+    /// from the reply instead of being wrapped in a [StringCallReply].
+    /// Elements whose key is not a valid UTF-8 string are skipped.
+    pub fn iter_str(&self) -> MapCallReplyStrIterator<'root, '_> {
+        MapCallReplyStrIterator {
+            reply: self,
+            index: 0,
+        }
+    }
+
     /// Return the number of elements in the [MapCallReply].
     pub fn len(&self) -> usize {
         call_reply_length(self.reply.as_ptr())
     }
 }
 
+pub struct MapCallReplyStrIterator<'root, 'curr> {
+    reply: &'curr MapCallReply<'root>,
+    index: usize,
+}
+
+impl<'root, 'curr> Iterator for MapCallReplyStrIterator<'root, 'curr> {
+    type Item = (&'curr str, CallResult<'curr>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.reply.len() {
+            let (key, val) = call_reply_map_element(self.reply.reply.as_ptr(), self.index);
+            self.index += 1;
+            if key.is_null() || call_reply_type(key) != ReplyType::String {
+                continue;
+            }
+            let mut len: usize = 0;
+            let key = call_reply_string_ptr(key, &mut len);
+            // The key is owned by the reply, which outlives the iterator.
+            let key = unsafe { slice::from_raw_parts(key.cast::<u8>(), len) };
+            if let (Ok(key), Some(val)) = (std::str::from_utf8(key), NonNull::new(val)) {
+                return Some((key, create_call_reply(val)));
+            }
+        }
+        None
+    }
+}
+
 pub struct MapCallReplyIterator<'root, 'curr> {
     reply: &'curr MapCallReply<'root>,
     index: usize,
@@ -557,19 +600,29 @@ impl TryFrom<&str> for VerbatimStringFormat {
             )));
         }
         let mut res = VerbatimStringFormat::default();
-        value.chars().take(3).enumerate().try_for_each(|(i, c)| {
-            if c as u32 >= 127 {
-                return Err(RedisError::String(
-                    "Verbatim format must contains only ASCI values.".to_owned(),
-                ));
-            }
-            res.0[i] = c as c_char;
-            Ok(())
-        })?;
+        value
+            .bytes()
+            .enumerate()
+            .for_each(|(i, c)| res.0[i] = c as c_char);
+        res.validate()?;
         Ok(res)
     }
 }
 
+impl VerbatimStringFormat {
+    /// Verify that the format consists only of printable ASCII characters
+    /// so it can be safely written to a RESP3 verbatim string frame.
+    pub fn validate(&self) -> Result<(), RedisError> {
+        if self.0.iter().all(|c| (*c as u8).is_ascii_graphic()) {
+            Ok(())
+        } else {
+            Err(RedisError::String(
+                "Verbatim format must contains only ASCI values.".to_owned(),
+            ))
+        }
+    }
+}
+
 impl<'root> VerbatimStringCallReply<'root> {
     /// Return the verbatim string value of the [VerbatimStringCallReply] as a tuple.
     /// The first entry represents the format, the second entry represent the data.
@@ -624,6 +677,101 @@ impl<'root> Display for VerbatimStringCallReply<'root> {
     }
 }
 
+/// A reply with RESP3 attributes, out of band metadata (for example about
+/// client side caching) attached to the actual value of the reply. Only
+/// RESP3 calls get attributes.
+pub struct AttributeCallReply<'root> {
+    attribute: NonNull<RedisModuleCallReply>,
+    value: Box<CallReply<'root>>,
+}
+
+impl<'root> AttributeCallReply<'root> {
+    /// Return an iterator over the attributes, as tuples of the attribute
+    /// name and its value.
+    pub fn iter(&self) -> AttributeCallReplyIterator<'root, '_> {
+        AttributeCallReplyIterator {
+            reply: self,
+            index: 0,
+        }
+    }
+
+    /// Return the attribute on the given index.
+    pub fn get(&self, idx: usize) -> Option<(CallResult<'_>, CallResult<'_>)> {
+        let (key, val) = call_reply_attribute_element(self.attribute.as_ptr(), idx);
+        Some((
+            create_call_reply(NonNull::new(key)?),
+            create_call_reply(NonNull::new(val)?),
+        ))
+    }
+
+    /// Return the number of attributes.
+    pub fn len(&self) -> usize {
+        call_reply_length(self.attribute.as_ptr())
+    }
+
+    /// Return the value the attributes are attached to.
+    pub fn value(&self) -> &CallReply<'root> {
+        &self.value
+    }
+
+    pub fn into_value(self) -> CallReply<'root> {
+        *self.value
+    }
+}
+
+pub struct AttributeCallReplyIterator<'root, 'curr> {
+    reply: &'curr AttributeCallReply<'root>,
+    index: usize,
+}
+
+impl<'root, 'curr> Iterator for AttributeCallReplyIterator<'root, 'curr> {
+    type Item = (CallResult<'curr>, CallResult<'curr>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.reply.get(self.index);
+        if res.is_some() {
+            self.index += 1;
+        }
+        res
+    }
+}
+
+// The attribute is owned by the reply it is attached to, which is freed
+// with the value.
+
+impl<'root> Debug for AttributeCallReply<'root> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttributeCallReply")
+            .field("attribute", &self.attribute)
+            .field(
+                "elements",
+                &self.iter().collect::<Vec<(CallResult, CallResult)>>(),
+            )
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<'root> Display for AttributeCallReply<'root> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("|{")?;
+
+        self.iter()
+            .enumerate()
+            .try_for_each(|(index, (key, val))| -> fmt::Result {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                fmt_call_result(key, f)?;
+                f.write_str(": ")?;
+                fmt_call_result(val, f)
+            })?;
+
+        f.write_str("}| ")?;
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
 #[derive(Debug)]
 pub enum CallReply<'root> {
     Unknown,
@@ -637,6 +785,7 @@ pub enum CallReply<'root> {
     Double(DoubleCallReply<'root>),
     BigNumber(BigNumberCallReply<'root>),
     VerbatimString(VerbatimStringCallReply<'root>),
+    Attribute(AttributeCallReply<'root>),
 }
 
 /// Send implementation to [CallReply].
@@ -660,11 +809,164 @@ impl<'root> Display for CallReply<'root> {
             CallReply::Double(inner) => fmt::Display::fmt(&inner, f),
             CallReply::BigNumber(inner) => fmt::Display::fmt(&inner, f),
             CallReply::VerbatimString(inner) => fmt::Display::fmt(&inner, f),
+            CallReply::Attribute(inner) => fmt::Display::fmt(&inner, f),
+        }
+    }
+}
+
+impl<'root> CallReply<'root> {
+    /// Return the reply without its attributes, if it has any.
+    pub fn without_attribute(&self) -> &CallReply<'root> {
+        match self {
+            CallReply::Attribute(inner) => inner.value(),
+            _ => self,
+        }
+    }
+
+    /// The name of the reply type, used in error messages.
+    fn type_name(&self) -> &'static str {
+        match self {
+            CallReply::Unknown => "unknown",
+            CallReply::I64(_) => "integer",
+            CallReply::String(_) => "string",
+            CallReply::Array(_) => "array",
+            CallReply::Null(_) => "null",
+            CallReply::Map(_) => "map",
+            CallReply::Set(_) => "set",
+            CallReply::Bool(_) => "bool",
+            CallReply::Double(_) => "double",
+            CallReply::BigNumber(_) => "big number",
+            CallReply::VerbatimString(_) => "verbatim string",
+            CallReply::Attribute(inner) => inner.value().type_name(),
+        }
+    }
+}
+
+/// Conversion of a [CallReply] into a Rust type, used by [Context::call_typed]
+/// to read a command reply without going through [crate::RedisValue].
+///
+/// The attributes of the reply, if any, are ignored.
+pub trait FromCallReply: Sized {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError>;
+}
+
+fn unexpected_reply(expected: &str, reply: &CallReply<'_>) -> RedisError {
+    RedisError::String(format!(
+        "Expected {expected} reply, got {} reply",
+        reply.type_name()
+    ))
+}
+
+fn from_call_result<T: FromCallReply>(res: CallResult<'_>) -> Result<T, RedisError> {
+    T::from_call_reply(res?.without_attribute())
+}
+
+impl FromCallReply for i64 {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        match reply {
+            CallReply::I64(v) => Ok(v.to_i64()),
+            _ => Err(unexpected_reply("integer", reply)),
+        }
+    }
+}
+
+impl FromCallReply for String {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        let res = match reply {
+            CallReply::String(v) => v.to_string(),
+            CallReply::BigNumber(v) => v.to_string(),
+            CallReply::VerbatimString(v) => v
+                .as_parts()
+                .and_then(|(_, s)| String::from_utf8(s.to_vec()).ok()),
+            _ => return Err(unexpected_reply("string", reply)),
+        };
+        res.ok_or(RedisError::Str("Reply is not a valid UTF-8 string"))
+    }
+}
+
+/// Integer replies are accepted too, as RESP2 replies booleans with `0` and `1`.
+impl FromCallReply for bool {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        match reply {
+            CallReply::Bool(v) => Ok(v.to_bool()),
+            CallReply::I64(v) => Ok(v.to_i64() != 0),
+            _ => Err(unexpected_reply("bool", reply)),
+        }
+    }
+}
+
+/// Integer and string replies are accepted too, as RESP2 replies doubles
+/// with strings (`ZSCORE` for example).
+impl FromCallReply for f64 {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        match reply {
+            CallReply::Double(v) => Ok(v.to_double()),
+            CallReply::I64(v) => Ok(v.to_i64() as f64),
+            CallReply::String(v) => v
+                .to_string()
+                .and_then(|s| s.parse().ok())
+                .ok_or(RedisError::Str("Reply is not a valid double")),
+            _ => Err(unexpected_reply("double", reply)),
+        }
+    }
+}
+
+/// A null reply is converted to [None].
+impl<T: FromCallReply> FromCallReply for Option<T> {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        match reply {
+            CallReply::Null(_) => Ok(None),
+            CallReply::Attribute(inner) => Self::from_call_reply(inner.value()),
+            _ => T::from_call_reply(reply).map(Some),
+        }
+    }
+}
+
+impl<T: FromCallReply> FromCallReply for Vec<T> {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        match reply {
+            CallReply::Array(v) => v.iter().map(from_call_result).collect(),
+            CallReply::Set(v) => v.iter().map(from_call_result).collect(),
+            _ => Err(unexpected_reply("array", reply)),
+        }
+    }
+}
+
+/// Flat arrays of key value pairs are accepted too, as RESP2 replies maps
+/// with arrays (`HGETALL` for example).
+impl<T: FromCallReply> FromCallReply for HashMap<String, T> {
+    fn from_call_reply(reply: &CallReply<'_>) -> Result<Self, RedisError> {
+        match reply {
+            CallReply::Map(v) => v
+                .iter()
+                .map(|(key, val)| Ok((from_call_result(key)?, from_call_result(val)?)))
+                .collect(),
+            CallReply::Array(v) if v.len() % 2 == 0 => {
+                let mut items = v.iter();
+                let mut res = HashMap::with_capacity(v.len() / 2);
+                while let (Some(key), Some(val)) = (items.next(), items.next()) {
+                    res.insert(from_call_result(key)?, from_call_result(val)?);
+                }
+                Ok(res)
+            }
+            _ => Err(unexpected_reply("map", reply)),
         }
     }
 }
 
 fn create_call_reply<'root>(reply: NonNull<RedisModuleCallReply>) -> CallResult<'root> {
+    let attribute = NonNull::new(call_reply_attribute(reply.as_ptr()));
+    match (create_call_reply_value(reply), attribute) {
+        (Ok(value), Some(attribute)) => Ok(CallReply::Attribute(AttributeCallReply {
+            attribute,
+            value: Box::new(value),
+        })),
+        (res, _) => res,
+    }
+}
+
+/// Same as [create_call_reply], but ignoring the attributes of the reply.
+fn create_call_reply_value<'root>(reply: NonNull<RedisModuleCallReply>) -> CallResult<'root> {
     let ty = call_reply_type(reply.as_ptr());
     match ty {
         ReplyType::Unknown => Ok(CallReply::Unknown), // unknown means NULL so no need to free free anything
@@ -0,0 +1,215 @@
+use std::ffi::CStr;
+use std::net::IpAddr;
+use std::os::raw::c_void;
+use std::str::FromStr;
+
+use bitflags::bitflags;
+use redis_module_macros_internals::api;
+
+use crate::{raw, Context, RedisError};
+
+bitflags! {
+    /// The `REDISMODULE_CLIENTINFO_FLAG_*` flags of a [ClientInfo].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ClientInfoFlags : u64 {
+        /// The client is connected over TLS.
+        const SSL = raw::REDISMODULE_CLIENTINFO_FLAG_SSL as u64;
+
+        /// The client is in Pub/Sub mode.
+        const PUBSUB = raw::REDISMODULE_CLIENTINFO_FLAG_PUBSUB as u64;
+
+        /// The client is blocked in a command.
+        const BLOCKED = raw::REDISMODULE_CLIENTINFO_FLAG_BLOCKED as u64;
+
+        /// The client has client side caching enabled.
+        const TRACKING = raw::REDISMODULE_CLIENTINFO_FLAG_TRACKING as u64;
+
+        /// The client is connected over a unix socket.
+        const UNIXSOCKET = raw::REDISMODULE_CLIENTINFO_FLAG_UNIXSOCKET as u64;
+
+        /// The client is in a `MULTI` block.
+        const MULTI = raw::REDISMODULE_CLIENTINFO_FLAG_MULTI as u64;
+    }
+}
+
+/// Information about a connected client, see [Context::get_client_info].
+/// The fields are copied out of Redis, so they stay valid after the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub id: u64,
+    pub flags: ClientInfoFlags,
+    /// The remote address of the client, empty for unix socket clients.
+    pub addr: String,
+    pub port: u16,
+    /// The currently selected database.
+    pub db: u16,
+}
+
+impl ClientInfo {
+    /// Return the remote address of the client, or `None` if the client is
+    /// connected over a unix socket.
+    pub fn ip(&self) -> Option<IpAddr> {
+        if self.flags.contains(ClientInfoFlags::UNIXSOCKET) {
+            return None;
+        }
+        self.addr.parse().ok()
+    }
+}
+
+impl FromStr for ClientInfo {
+    type Err = RedisError;
+
+    /// Parse a line of the `CLIENT LIST` (or `CLIENT INFO`) output, which is
+    /// made of space separated `key=value` fields. Only the fields of
+    /// [ClientInfo] are kept, and only the flags of [ClientInfoFlags] which
+    /// are reported there (`SSL` is not).
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut id = None;
+        let mut addr = None;
+        let mut flags = ClientInfoFlags::empty();
+        let mut db = 0;
+        for (key, value) in line.split_whitespace().filter_map(|f| f.split_once('=')) {
+            match key {
+                "id" => id = value.parse().ok(),
+                "addr" => addr = Some(value),
+                "db" => db = value.parse().unwrap_or(0),
+                "flags" => flags = client_list_flags(value),
+                _ => {}
+            }
+        }
+
+        let id = id.ok_or(RedisError::Str("Missing client id"))?;
+        let (addr, port) = if flags.contains(ClientInfoFlags::UNIXSOCKET) {
+            (String::new(), 0)
+        } else {
+            let (host, port) = addr
+                .and_then(|addr| addr.rsplit_once(':'))
+                .ok_or(RedisError::Str("Missing client address"))?;
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            (host.to_owned(), port.parse().unwrap_or(0))
+        };
+
+        Ok(ClientInfo {
+            id,
+            flags,
+            addr,
+            port,
+            db,
+        })
+    }
+}
+
+/// Convert the flag letters of `CLIENT LIST` to [ClientInfoFlags].
+fn client_list_flags(letters: &str) -> ClientInfoFlags {
+    letters
+        .chars()
+        .fold(ClientInfoFlags::empty(), |flags, letter| match letter {
+            'P' => flags | ClientInfoFlags::PUBSUB,
+            'b' => flags | ClientInfoFlags::BLOCKED,
+            't' => flags | ClientInfoFlags::TRACKING,
+            'U' => flags | ClientInfoFlags::UNIXSOCKET,
+            'x' => flags | ClientInfoFlags::MULTI,
+            _ => flags,
+        })
+}
+
+impl Context {
+    /// Return the information about all the connected clients, parsed from
+    /// the output of `CLIENT LIST`, see [ClientInfo::from_str].
+    pub fn client_list(&self) -> Result<Vec<ClientInfo>, RedisError> {
+        let list: String = self.call_typed("CLIENT", &["LIST"])?;
+        list.lines()
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Return the id of the client running the current command, or 0 if the
+    /// context has no client (for example in a server event callback).
+    pub fn get_client_id(&self) -> u64 {
+        unsafe { raw::RedisModule_GetClientId.unwrap()(self.ctx) }
+    }
+
+    /// Return the information about the client running the current command.
+    pub fn get_client_info(&self) -> Result<ClientInfo, RedisError> {
+        let id = self.get_client_id();
+        if id == 0 {
+            return Err(RedisError::Str("No client for the current context"));
+        }
+        self.get_client_info_by_id(id)
+    }
+
+    api!(
+        [RedisModule_GetClientInfoById],
+        /// Return the information about the client with the given id.
+        pub fn get_client_info_by_id(&self, id: u64) -> Result<ClientInfo, RedisError> {
+            let mut info = raw::RedisModuleClientInfo {
+                version: raw::REDISMODULE_CLIENTINFO_VERSION as u64,
+                flags: 0,
+                id: 0,
+                addr: [0; 46],
+                port: 0,
+                db: 0,
+            };
+            let res: raw::Status = unsafe {
+                RedisModule_GetClientInfoById(
+                    &mut info as *mut raw::RedisModuleClientInfo as *mut c_void,
+                    id,
+                )
+            }
+            .into();
+            if res == raw::Status::Err {
+                return Err(RedisError::String(format!("No client with id {id}")));
+            }
+            let addr = unsafe { CStr::from_ptr(info.addr.as_ptr()) };
+            Ok(ClientInfo {
+                id: info.id,
+                flags: ClientInfoFlags::from_bits_truncate(info.flags),
+                addr: addr.to_string_lossy().into_owned(),
+                port: info.port,
+                db: info.db,
+            })
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientInfo, ClientInfoFlags};
+
+    #[test]
+    fn parse_client_list_line() {
+        let info: ClientInfo = "id=3 addr=127.0.0.1:50188 laddr=127.0.0.1:6379 fd=8 name= \
+            age=0 idle=0 flags=x db=2 sub=0 psub=0 multi=1 cmd=client|list user=default"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            info,
+            ClientInfo {
+                id: 3,
+                flags: ClientInfoFlags::MULTI,
+                addr: "127.0.0.1".to_owned(),
+                port: 50188,
+                db: 2,
+            }
+        );
+
+        let info: ClientInfo = "id=4 addr=[::1]:6000 flags=bt db=0".parse().unwrap();
+        assert_eq!(info.ip(), Some("::1".parse().unwrap()));
+        assert_eq!(info.port, 6000);
+        assert_eq!(
+            info.flags,
+            ClientInfoFlags::BLOCKED | ClientInfoFlags::TRACKING
+        );
+
+        let info: ClientInfo = "id=5 addr=/tmp/redis.sock:0 flags=U db=0".parse().unwrap();
+        assert_eq!(info.ip(), None);
+        assert_eq!(info.addr, "");
+    }
+
+    #[test]
+    fn parse_client_list_line_invalid() {
+        assert!("addr=127.0.0.1:1 flags=N".parse::<ClientInfo>().is_err());
+        assert!("id=1 flags=N".parse::<ClientInfo>().is_err());
+    }
+}
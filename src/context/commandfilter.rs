@@ -0,0 +1,292 @@
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::os::raw::{c_char, c_int};
+use std::ptr::NonNull;
+use std::sync::{Mutex, OnceLock};
+
+use bitflags::bitflags;
+
+use crate::{raw, Context, RedisError, RedisLockIndicator, RedisString, Status};
+
+bitflags! {
+    /// The flags a command filter is registered with.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CommandFilterFlags: c_int {
+        /// Do not filter the commands called by the module which registered
+        /// the filter, see [CommandFilterContext].
+        const NOSELF = raw::REDISMODULE_CMDFILTER_NOSELF as c_int;
+    }
+}
+
+type GetClientId = unsafe extern "C" fn(*mut raw::RedisModuleCommandFilterCtx) -> u64;
+
+/// `RedisModule_CommandFilterGetClientId`, resolved on the first call to
+/// [CommandFilterContext::client_id] since filters run before every command.
+static COMMAND_FILTER_GET_CLIENT_ID: OnceLock<Option<GetClientId>> = OnceLock::new();
+
+/// The context passed to a command filter callback, giving access to the
+/// arguments of the filtered command (the command name is argument 0).
+///
+/// Filters are registered with [Context::register_command_filter] and are
+/// called for every command before it is executed, including the commands
+/// the module itself runs with [crate::Context::call]. A filter that rewrites
+/// a command into a module command which calls the original command again
+/// (for example `GET` into `MYGET`, which calls `GET`) would recurse forever.
+/// Registering the filter with [CommandFilterFlags::NOSELF] skips the
+/// commands called by the module that registered the filter, which breaks
+/// this loop.
+pub struct CommandFilterContext {
+    filter_ctx: NonNull<raw::RedisModuleCommandFilterCtx>,
+    flags: CommandFilterFlags,
+}
+
+/// Command filters are invoked by Redis on the main thread,
+/// while the Redis GIL is held.
+unsafe impl RedisLockIndicator for CommandFilterContext {}
+
+impl CommandFilterContext {
+    /// Creates a new [`CommandFilterContext`] from a pointer to [`raw::RedisModuleCommandFilterCtx`].
+    /// The function is considered unsafe because the provided pointer must be
+    /// a valid pointer to [`raw::RedisModuleCommandFilterCtx`] given to the
+    /// current filter callback, and the returned context must not be used after
+    /// the callback returns.
+    pub unsafe fn new(filter_ctx: *mut raw::RedisModuleCommandFilterCtx) -> CommandFilterContext {
+        Self::with_flags(filter_ctx, CommandFilterFlags::empty())
+    }
+
+    /// Same as [CommandFilterContext::new], for a filter registered with
+    /// `flags`, which are then returned by [CommandFilterContext::flags].
+    pub unsafe fn with_flags(
+        filter_ctx: *mut raw::RedisModuleCommandFilterCtx,
+        flags: CommandFilterFlags,
+    ) -> CommandFilterContext {
+        CommandFilterContext {
+            filter_ctx: NonNull::new(filter_ctx).expect("filter context must not be null"),
+            flags,
+        }
+    }
+
+    /// Return the flags the filter was registered with. A filter registered
+    /// without [CommandFilterFlags::NOSELF] also sees the commands it injects
+    /// through the module's own [crate::Context::call], and should recognize
+    /// them before rewriting them.
+    pub fn flags(&self) -> CommandFilterFlags {
+        self.flags
+    }
+
+    /// Return the id of the client which sent the filtered command. Commands
+    /// loaded from the AOF are run by a fake client whose id is `u64::MAX`.
+    ///
+    /// The id can be passed to [Context::get_client_info_by_id], but its
+    /// [crate::ClientInfoFlags] do not tell whether the command comes from
+    /// the master link or from a script, so a filter can not skip those.
+    ///
+    /// Return `None` if the server does not expose
+    /// `RedisModule_CommandFilterGetClientId`, which was added in Redis 7.2.
+    /// The vendored `redismodule.h` predates it, so it is looked up by name.
+    pub fn client_id(&self) -> Option<u64> {
+        let get_client_id = (*COMMAND_FILTER_GET_CLIENT_ID.get_or_init(|| {
+            let mut get_client_id: Option<GetClientId> = None;
+            let res: Status = unsafe {
+                raw::RedisModule_GetApi?(
+                    b"RedisModule_CommandFilterGetClientId\0".as_ptr().cast(),
+                    (&mut get_client_id as *mut Option<GetClientId>).cast(),
+                )
+            }
+            .into();
+            get_client_id.filter(|_| res == Status::Ok)
+        }))?;
+        Some(unsafe { get_client_id(self.filter_ctx.as_ptr()) })
+    }
+
+    /// Return the number of arguments, including the command name.
+    pub fn args_count(&self) -> usize {
+        unsafe {
+            raw::RedisModule_CommandFilterArgsCount.unwrap()(self.filter_ctx.as_ptr()) as usize
+        }
+    }
+
+    /// Return the argument at `pos`, or `None` if `pos` is out of range. The
+    /// argument may be freed once the arguments are modified, so it must not
+    /// be held across [CommandFilterContext::arg_replace] and the like.
+    pub fn arg_get(&self, pos: usize) -> Option<&[u8]> {
+        let arg = unsafe {
+            raw::RedisModule_CommandFilterArgGet.unwrap()(self.filter_ctx.as_ptr(), pos as c_int)
+        };
+        if arg.is_null() {
+            None
+        } else {
+            Some(RedisString::string_as_slice(arg))
+        }
+    }
+
+    /// Return the argument at `pos` as a UTF-8 string.
+    pub fn arg_get_try_as_str(&self, pos: usize) -> Result<&str, RedisError> {
+        let arg = self
+            .arg_get(pos)
+            .ok_or(RedisError::Str("Invalid argument position"))?;
+        std::str::from_utf8(arg).map_err(|_| RedisError::Str("Couldn't parse as UTF-8 string"))
+    }
+
+    /// Insert `arg` at `pos`, shifting the following arguments to the right.
+    /// The command name can not be moved, so `pos` must be at least 1.
+    pub fn arg_insert(&mut self, pos: usize, arg: &[u8]) -> Result<(), RedisError> {
+        let arg = Self::create_arg(arg);
+        let res: Status = unsafe {
+            raw::RedisModule_CommandFilterArgInsert.unwrap()(
+                self.filter_ctx.as_ptr(),
+                pos as c_int,
+                arg,
+            )
+        }
+        .into();
+        Self::check(res, arg)
+    }
+
+    /// Replace the argument at `pos` with `arg`.
+    pub fn arg_replace(&mut self, pos: usize, arg: &[u8]) -> Result<(), RedisError> {
+        let arg = Self::create_arg(arg);
+        let res: Status = unsafe {
+            raw::RedisModule_CommandFilterArgReplace.unwrap()(
+                self.filter_ctx.as_ptr(),
+                pos as c_int,
+                arg,
+            )
+        }
+        .into();
+        Self::check(res, arg)
+    }
+
+    /// Delete the argument at `pos`, shifting the following arguments to the left.
+    pub fn arg_delete(&mut self, pos: usize) -> Result<(), RedisError> {
+        let res: Status = unsafe {
+            raw::RedisModule_CommandFilterArgDelete.unwrap()(self.filter_ctx.as_ptr(), pos as c_int)
+        }
+        .into();
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => Err(RedisError::Str("Invalid argument position")),
+        }
+    }
+
+    /// Create a string owned by Redis once it is given to the filter context.
+    fn create_arg(arg: &[u8]) -> *mut raw::RedisModuleString {
+        unsafe {
+            raw::RedisModule_CreateString.unwrap()(
+                std::ptr::null_mut(),
+                arg.as_ptr().cast::<c_char>(),
+                arg.len(),
+            )
+        }
+    }
+
+    /// On failure Redis did not take the argument, so it is freed here.
+    fn check(res: Status, arg: *mut raw::RedisModuleString) -> Result<(), RedisError> {
+        match res {
+            Status::Ok => Ok(()),
+            Status::Err => {
+                unsafe { raw::RedisModule_FreeString.unwrap()(std::ptr::null_mut(), arg) };
+                Err(RedisError::Str("Invalid argument position"))
+            }
+        }
+    }
+}
+
+/// The filters registered with [Context::register_command_filter], by the
+/// type of their closure. Redis does not give any private data to a filter,
+/// so each closure type gets its own callback, which finds the closure here.
+static COMMAND_FILTERS: Mutex<BTreeMap<TypeId, usize>> = Mutex::new(BTreeMap::new());
+
+extern "C" fn command_filter<F: Fn(&mut CommandFilterContext) + 'static>(
+    filter_ctx: *mut raw::RedisModuleCommandFilterCtx,
+) {
+    let filter = COMMAND_FILTERS
+        .lock()
+        .unwrap()
+        .get(&TypeId::of::<F>())
+        .copied();
+    if let Some(filter) = filter {
+        let (flags, filter) = unsafe { &*(filter as *const (CommandFilterFlags, F)) };
+        let mut fctx = unsafe { CommandFilterContext::with_flags(filter_ctx, *flags) };
+        filter(&mut fctx);
+    }
+}
+
+/// A command filter registered with [Context::register_command_filter]. The
+/// filter stays registered until [CommandFilter::unregister] is called, or
+/// the module is unloaded.
+#[derive(Debug)]
+pub struct CommandFilter {
+    inner: *mut raw::RedisModuleCommandFilter,
+    type_id: TypeId,
+    free: unsafe fn(usize),
+}
+
+// The filter can only be unregistered with a [Context], from the main thread.
+unsafe impl Send for CommandFilter {}
+
+impl CommandFilter {
+    /// Unregister the filter, it is not called anymore once this returns.
+    pub fn unregister(self, ctx: &Context) -> Result<(), RedisError> {
+        let res: Status =
+            unsafe { raw::RedisModule_UnregisterCommandFilter.unwrap()(ctx.ctx, self.inner) }
+                .into();
+        if res == Status::Err {
+            return Err(RedisError::Str("Failed unregistering the command filter"));
+        }
+        let filter = COMMAND_FILTERS.lock().unwrap().remove(&self.type_id);
+        if let Some(filter) = filter {
+            unsafe { (self.free)(filter) };
+        }
+        Ok(())
+    }
+}
+
+unsafe fn free_command_filter<F>(filter: usize) {
+    drop(Box::from_raw(filter as *mut (CommandFilterFlags, F)));
+}
+
+impl Context {
+    /// Register `filter` to be called for every command before it is
+    /// executed, see [CommandFilterContext]. Filters are called in the order
+    /// they were registered.
+    ///
+    /// Since Redis gives no private data to filters, a closure type can only
+    /// be registered once at a time: registering the same closure again
+    /// before unregistering it fails.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_RegisterCommandFilter` is missing in redismodule.h
+    pub fn register_command_filter<F>(
+        &self,
+        flags: CommandFilterFlags,
+        filter: F,
+    ) -> Result<CommandFilter, RedisError>
+    where
+        F: Fn(&mut CommandFilterContext) + 'static,
+    {
+        let type_id = TypeId::of::<F>();
+        let mut filters = COMMAND_FILTERS.lock().unwrap();
+        if filters.contains_key(&type_id) {
+            return Err(RedisError::Str("Command filter already registered"));
+        }
+        let inner = unsafe {
+            raw::RedisModule_RegisterCommandFilter.unwrap()(
+                self.ctx,
+                Some(command_filter::<F>),
+                flags.bits(),
+            )
+        };
+        if inner.is_null() {
+            return Err(RedisError::Str("Failed registering the command filter"));
+        }
+        let filter = Box::into_raw(Box::new((flags, filter)));
+        filters.insert(type_id, filter as usize);
+        Ok(CommandFilter {
+            inner,
+            type_id,
+            free: free_command_filter::<F>,
+        })
+    }
+}
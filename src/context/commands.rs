@@ -1,15 +1,19 @@
 use crate::raw;
 use crate::Context;
 use crate::RedisError;
+use crate::RedisResult;
+use crate::RedisString;
 use crate::Status;
 use bitflags::bitflags;
 use libc::c_char;
 use linkme::distributed_slice;
 use redis_module_macros_internals::api;
+use std::collections::BTreeMap;
 use std::ffi::CString;
 use std::mem::MaybeUninit;
 use std::os::raw::c_int;
 use std::ptr;
+use std::sync::{Mutex, RwLock};
 
 const COMMNAD_INFO_VERSION: raw::RedisModuleCommandInfoVersion =
     raw::RedisModuleCommandInfoVersion {
@@ -353,6 +357,88 @@ impl CommandInfo {
     }
 }
 
+/// A command handler that can replace the handler of a command registered
+/// with [`redis_command!`] at runtime, see [`Context::replace_command_handler`].
+pub type CommandHandler = fn(&Context, Vec<RedisString>) -> RedisResult;
+
+/// Holds the replacement handler (if any) of a single command registered
+/// with [`redis_command!`]. Not intended to be used directly.
+#[doc(hidden)]
+pub struct CommandHandlerSlot {
+    handler: RwLock<Option<CommandHandler>>,
+}
+
+impl CommandHandlerSlot {
+    pub const fn new() -> CommandHandlerSlot {
+        CommandHandlerSlot {
+            handler: RwLock::new(None),
+        }
+    }
+
+    pub fn get(&self) -> Option<CommandHandler> {
+        *self.handler.read().unwrap()
+    }
+
+    fn set(&self, handler: Option<CommandHandler>) {
+        *self.handler.write().unwrap() = handler;
+    }
+}
+
+impl Default for CommandHandlerSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static COMMAND_HANDLER_SLOTS: Mutex<BTreeMap<String, &'static CommandHandlerSlot>> =
+    Mutex::new(BTreeMap::new());
+
+/// Register the handler slot of a command, called by [`redis_command!`]
+/// once the command was created.
+#[doc(hidden)]
+pub fn register_command_handler_slot(name: &str, slot: &'static CommandHandlerSlot) {
+    COMMAND_HANDLER_SLOTS
+        .lock()
+        .unwrap()
+        .insert(name.to_lowercase(), slot);
+}
+
+fn get_command_handler_slot(name: &str) -> Result<&'static CommandHandlerSlot, RedisError> {
+    COMMAND_HANDLER_SLOTS
+        .lock()
+        .unwrap()
+        .get(&name.to_lowercase())
+        .copied()
+        .ok_or_else(|| {
+            RedisError::String(format!("Command {name} was not registered by the module"))
+        })
+}
+
+impl Context {
+    /// Replace the handler of a command registered with [`redis_command!`]
+    /// (or the `commands` section of [`redis_module!`]) without reloading
+    /// the module. The Redis module API does not allow to unregister a
+    /// command, so the registered command keeps its name, flags and key
+    /// positions, and only the function that handles it is swapped.
+    ///
+    /// Returns an error if the command was not registered by this module.
+    pub fn replace_command_handler(
+        &self,
+        name: &str,
+        handler: CommandHandler,
+    ) -> Result<(), RedisError> {
+        get_command_handler_slot(name)?.set(Some(handler));
+        Ok(())
+    }
+
+    /// Restore the handler the command was originally registered with,
+    /// undoing [`Context::replace_command_handler`].
+    pub fn restore_command_handler(&self, name: &str) -> Result<(), RedisError> {
+        get_command_handler_slot(name)?.set(None);
+        Ok(())
+    }
+}
+
 #[distributed_slice()]
 pub static COMMANDS_LIST: [fn() -> Result<CommandInfo, RedisError>] = [..];
 
@@ -1,6 +1,7 @@
 use std::alloc::Layout;
 use std::fmt::{Debug, Formatter};
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::{
     raw, Context, RedisModule_DefragAlloc, RedisModule_DefragCursorGet,
@@ -139,6 +140,45 @@ impl DefragContext {
         ptr
     }
 
+    /// Defrag the allocation owned by a [Box], updating the [Box] in place
+    /// if the value was moved.
+    ///
+    /// # Safety
+    ///
+    /// The [Box] must have been allocated by the Redis allocator, which is the
+    /// case when [crate::alloc::RedisAlloc] is the module global allocator.
+    pub unsafe fn defrag_box<T>(&self, b: &mut Box<T>) {
+        if std::mem::size_of::<T>() == 0 {
+            return;
+        }
+        let ptr: *mut T = &mut **b;
+        let new_ptr = self.defrag_realloc(ptr);
+        if new_ptr != ptr {
+            // The old allocation was already freed by Redis, so it must not be dropped.
+            std::ptr::write(b, Box::from_raw(new_ptr));
+        }
+    }
+
+    /// Defrag the buffer owned by a [Vec], updating the [Vec] in place
+    /// if the buffer was moved. The elements themselves are not defragged.
+    ///
+    /// # Safety
+    ///
+    /// The [Vec] buffer must have been allocated by the Redis allocator, which is
+    /// the case when [crate::alloc::RedisAlloc] is the module global allocator.
+    pub unsafe fn defrag_vec<T>(&self, v: &mut Vec<T>) {
+        if v.capacity() == 0 || std::mem::size_of::<T>() == 0 {
+            return;
+        }
+        let ptr = v.as_mut_ptr();
+        let new_ptr = self.defrag_realloc(ptr);
+        if new_ptr != ptr {
+            let (len, capacity) = (v.len(), v.capacity());
+            // The old buffer was already freed by Redis, so it must not be dropped.
+            std::ptr::write(v, Vec::from_raw_parts(new_ptr, len, capacity));
+        }
+    }
+
     /// Allocate memory using defrag allocator if supported by the
     /// current Redis server, fallback to regular allocation otherwise.
     pub fn defrag_alloc<T>(&self, layout: Layout) -> *mut T {
@@ -207,8 +247,30 @@ extern "C" fn defrag_end_function(defrag_ctx: *mut raw::RedisModuleDefragCtx) {
     });
 }
 
-/// Register defrag functions if exists.
+/// Set once [register_defrag_functions] succeeded.
+static DEFRAG_FUNCTIONS_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Register the global defrag function and the defrag start and end callbacks
+/// declared with the `defrag_function`, `defrag_start_function` and
+/// `defrag_end_function` attributes, if there are any.
+///
+/// The [redis_module](crate::redis_module) macro calls it from the module
+/// `OnLoad`. Modules with their own `RedisModule_OnLoad` should call it from
+/// there, once their commands are created. Calling it again after it succeeded
+/// does nothing.
+///
+/// Registration is skipped, with a warning, on Redis servers which do not
+/// support defrag functions.
 pub fn register_defrag_functions(ctx: &Context) -> Result<(), RedisError> {
+    if DEFRAG_FUNCTIONS_REGISTERED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    register_defrag_functions_once(ctx)?;
+    DEFRAG_FUNCTIONS_REGISTERED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+fn register_defrag_functions_once(ctx: &Context) -> Result<(), RedisError> {
     let register_defrag_function = match unsafe { raw::RedisModule_RegisterDefragFunc } {
         Some(f) => f,
         None => {
@@ -1,8 +1,10 @@
 use std::ffi::CString;
 use std::ptr::NonNull;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use crate::Context;
 use crate::{raw, RedisString};
+use crate::{Context, ContextFlags, RedisError, RedisResult};
 
 pub struct ServerInfo {
     ctx: *mut raw::RedisModuleCtx,
@@ -29,6 +31,46 @@ impl ServerInfo {
     }
 }
 
+/// Keyspace hit/miss counters, as reported by the `stats` section of `INFO`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyspaceStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl KeyspaceStats {
+    /// The ratio of lookups that missed, or `0.0` if there were no lookups yet.
+    pub fn miss_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.misses as f64 / total as f64
+        }
+    }
+
+    /// The ratio of lookups that hit, or `0.0` if there were no lookups yet.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct KeyspaceStatsCache {
+    last_refresh: Instant,
+    cron_period: Duration,
+    stats: KeyspaceStats,
+}
+
+/// The error Redis replies with when a write command is rejected on OOM.
+const OOM_ERROR: &str = "OOM command not allowed when used memory > 'maxmemory'.";
+
+static KEYSPACE_STATS_CACHE: Mutex<Option<KeyspaceStatsCache>> = Mutex::new(None);
+
 impl Context {
     #[must_use]
     pub fn server_info(&self, section: &str) -> ServerInfo {
@@ -45,4 +87,76 @@ impl Context {
             inner: server_info,
         }
     }
+
+    /// Return the keyspace hit/miss counters.
+    ///
+    /// The values are read from the `stats` section of `INFO` and cached, so
+    /// they are refreshed at most once per server cron tick (`1000 / hz`
+    /// milliseconds). This makes it cheap to call on every command.
+    pub fn get_keyspace_stats(&self) -> KeyspaceStats {
+        let mut cache = KEYSPACE_STATS_CACHE.lock().unwrap();
+        if let Some(cache) = cache.as_ref() {
+            if cache.last_refresh.elapsed() < cache.cron_period {
+                return cache.stats;
+            }
+        }
+
+        let parse_field = |info: &ServerInfo, field: &str| {
+            info.field(field)
+                .and_then(|v| v.parse_unsigned_integer().ok())
+        };
+
+        let stats_info = self.server_info("stats");
+        let stats = KeyspaceStats {
+            hits: parse_field(&stats_info, "keyspace_hits").unwrap_or(0),
+            misses: parse_field(&stats_info, "keyspace_misses").unwrap_or(0),
+        };
+        let hz = parse_field(&self.server_info("server"), "hz")
+            .filter(|hz| *hz > 0)
+            .unwrap_or(10);
+
+        *cache = Some(KeyspaceStatsCache {
+            last_refresh: Instant::now(),
+            cron_period: Duration::from_millis(1000 / hz),
+            stats,
+        });
+        stats
+    }
+
+    /// Return the ratio of keyspace lookups that missed, see [`Context::get_keyspace_stats`].
+    pub fn get_keyspace_miss_ratio(&self) -> f64 {
+        self.get_keyspace_stats().miss_ratio()
+    }
+
+    /// Return an OOM error if writing about `bytes` more bytes would make the
+    /// used memory exceed `maxmemory`, the same error a write command gets
+    /// when the server is out of memory. This is the equivalent of
+    /// [crate::CallOptionsBuilder::verify_oom] for modules writing to keys
+    /// directly, and should be called before a large write.
+    ///
+    /// The check is based on the `used_memory` and `maxmemory` values of the
+    /// `memory` section of `INFO`, so it does not take into account memory
+    /// that could be freed by eviction. Without a `maxmemory` limit only the
+    /// OOM context flag is checked.
+    pub fn check_oom_for(&self, bytes: usize) -> RedisResult<()> {
+        if self.get_flags().contains(ContextFlags::OOM) {
+            return Err(RedisError::Str(OOM_ERROR));
+        }
+
+        let memory_info = self.server_info("memory");
+        let parse_field = |field: &str| {
+            memory_info
+                .field(field)
+                .and_then(|v| v.parse_unsigned_integer().ok())
+                .unwrap_or(0)
+        };
+        let maxmemory = parse_field("maxmemory");
+        if maxmemory == 0 {
+            return Ok(());
+        }
+        if parse_field("used_memory").saturating_add(bytes as u64) > maxmemory {
+            return Err(RedisError::Str(OOM_ERROR));
+        }
+        Ok(())
+    }
 }
@@ -1,12 +1,31 @@
+use crate::context::call_reply::{CallReply, CallResult};
 use crate::context::Context;
 use crate::key::RedisKey;
 use crate::raw;
 use crate::redismodule::RedisString;
+use crate::RedisError;
+use std::cell::Cell;
+use std::collections::VecDeque;
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
 pub struct KeysCursor {
-    inner_cursor: *mut raw::RedisModuleScanCursor,
+    inner: CursorInner,
+}
+
+enum CursorInner {
+    /// A cursor created with [KeysCursor::new], whose position is kept by Redis.
+    Native(*mut raw::RedisModuleScanCursor),
+    /// A cursor created with [KeysCursor::from_token].
+    Position(ScanPosition),
+}
+
+/// Redis does not expose the position of a `RedisModuleScanCursor`, so the
+/// cursors which must report it are scanned with the `SCAN` command instead,
+/// which returns it.
+struct ScanPosition {
+    cursor: Cell<u64>,
+    done: Cell<bool>,
 }
 
 extern "C" fn scan_callback<C: FnMut(&Context, RedisString, Option<&RedisKey>)>(
@@ -29,30 +48,153 @@ extern "C" fn scan_callback<C: FnMut(&Context, RedisString, Option<&RedisKey>)>(
     redis_key.map(|v| v.take());
 }
 
+// Scan a batch of keys with `SCAN`, returning the position of the next batch
+// and the names of the keys.
+fn scan_command(ctx: &Context, cursor: u64) -> Result<(u64, Vec<RedisString>), RedisError> {
+    const UNEXPECTED_REPLY: RedisError = RedisError::Str("ERR unexpected reply to SCAN");
+
+    let cursor = cursor.to_string();
+    let reply: CallResult = ctx.call_internal("SCAN", raw::FMT, &[cursor.as_str()]);
+    let reply = match reply? {
+        CallReply::Array(reply) => reply,
+        _ => return Err(UNEXPECTED_REPLY),
+    };
+    let next = match reply.get(0).ok_or(UNEXPECTED_REPLY)?? {
+        CallReply::String(next) => next
+            .to_string()
+            .and_then(|next| next.parse().ok())
+            .ok_or(UNEXPECTED_REPLY)?,
+        _ => return Err(UNEXPECTED_REPLY),
+    };
+    let names = match reply.get(1).ok_or(UNEXPECTED_REPLY)?? {
+        CallReply::Array(names) => names
+            .iter()
+            .map(|name| match name {
+                Ok(CallReply::String(name)) => {
+                    Ok(RedisString::create_from_slice(ctx.ctx, name.as_bytes()))
+                }
+                _ => Err(UNEXPECTED_REPLY),
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err(UNEXPECTED_REPLY),
+    };
+    Ok((next, names))
+}
+
 impl KeysCursor {
     pub fn new() -> Self {
         let inner_cursor = unsafe { raw::RedisModule_ScanCursorCreate.unwrap()() };
-        Self { inner_cursor }
+        Self {
+            inner: CursorInner::Native(inner_cursor),
+        }
     }
 
+    /// Scan the next batch of keys, calling `callback` for each of them.
+    /// Returns `false` once the scan is done.
+    ///
+    /// Scanning a cursor created with [`KeysCursor::from_token`] can fail,
+    /// in which case this returns `false` as well, and [`KeysCursor::token`]
+    /// keeps returning the position of the failed batch. Use
+    /// [`KeysCursor::try_scan`] to tell the two apart.
     pub fn scan<F: FnMut(&Context, RedisString, Option<&RedisKey>)>(
         &self,
         ctx: &Context,
         callback: &F,
     ) -> bool {
+        self.try_scan(ctx, callback).unwrap_or(false)
+    }
+
+    /// Same as [`KeysCursor::scan`], returning the error of a failed batch
+    /// instead of `false`.
+    pub fn try_scan<F: FnMut(&Context, RedisString, Option<&RedisKey>)>(
+        &self,
+        ctx: &Context,
+        callback: &F,
+    ) -> Result<bool, RedisError> {
+        let inner_cursor = match &self.inner {
+            CursorInner::Native(inner_cursor) => *inner_cursor,
+            CursorInner::Position(position) => {
+                return Self::scan_position(ctx, position, callback, scan_command)
+            }
+        };
         let res = unsafe {
             raw::RedisModule_Scan.unwrap()(
                 ctx.ctx,
-                self.inner_cursor,
+                inner_cursor,
                 Some(scan_callback::<F>),
                 callback as *const F as *mut c_void,
             )
         };
-        res != 0
+        Ok(res != 0)
+    }
+
+    // Same as `RedisModule_Scan`, each key is opened for reading while the
+    // callback is called, and the key is `None` if it does not exist anymore.
+    // The position is only moved once the batch was scanned successfully.
+    fn scan_position<F: FnMut(&Context, RedisString, Option<&RedisKey>)>(
+        ctx: &Context,
+        position: &ScanPosition,
+        callback: &F,
+        scan_batch: impl FnOnce(&Context, u64) -> Result<(u64, Vec<RedisString>), RedisError>,
+    ) -> Result<bool, RedisError> {
+        if position.done.get() {
+            return Ok(false);
+        }
+        let (next, names) = scan_batch(ctx, position.cursor.get())?;
+        for key_name in names {
+            let key = ctx.open_key(&key_name);
+            scan_callback::<F>(
+                ctx.ctx,
+                key_name.inner,
+                key.key_inner,
+                callback as *const F as *mut c_void,
+            );
+        }
+        position.cursor.set(next);
+        position.done.set(next == 0);
+        Ok(next != 0)
     }
 
     pub fn restart(&self) {
-        unsafe { raw::RedisModule_ScanCursorRestart.unwrap()(self.inner_cursor) };
+        match &self.inner {
+            CursorInner::Native(inner_cursor) => unsafe {
+                raw::RedisModule_ScanCursorRestart.unwrap()(*inner_cursor)
+            },
+            CursorInner::Position(position) => {
+                position.cursor.set(0);
+                position.done.set(false);
+            }
+        }
+    }
+
+    /// Create a cursor that resumes a scan from a token previously returned
+    /// by [`KeysCursor::token`]. A token of `0` starts a new scan, same as
+    /// the native `SCAN` command.
+    ///
+    /// Redis does not expose the position of the cursors it scans with, so
+    /// these cursors are scanned with the `SCAN` command, which has the same
+    /// guarantees but might return a different number of keys per batch.
+    pub fn from_token(token: u64) -> Self {
+        Self {
+            inner: CursorInner::Position(ScanPosition {
+                cursor: Cell::new(token),
+                done: Cell::new(false),
+            }),
+        }
+    }
+
+    /// Return an opaque token representing the current scan position, which
+    /// can be handed to the client and later passed to [`KeysCursor::from_token`].
+    /// Returns `0` once the scan is done, same as the native `SCAN` command.
+    ///
+    /// Returns `None` for a cursor created with [`KeysCursor::new`], whose
+    /// position is kept by Redis.
+    pub fn token(&self) -> Option<u64> {
+        match &self.inner {
+            CursorInner::Native(_) => None,
+            CursorInner::Position(position) if position.done.get() => Some(0),
+            CursorInner::Position(position) => Some(position.cursor.get()),
+        }
     }
 }
 
@@ -64,6 +206,102 @@ impl Default for KeysCursor {
 
 impl Drop for KeysCursor {
     fn drop(&mut self) {
-        unsafe { raw::RedisModule_ScanCursorDestroy.unwrap()(self.inner_cursor) };
+        if let CursorInner::Native(inner_cursor) = self.inner {
+            unsafe { raw::RedisModule_ScanCursorDestroy.unwrap()(inner_cursor) };
+        }
+    }
+}
+
+/// An iterator over the whole keyspace, see [Context::scan_iter].
+///
+/// The keyspace is scanned one `RedisModule_Scan` batch at a time, and only
+/// the key names are kept between calls to [Iterator::next]. Each key is
+/// opened for reading when it is yielded, so keys deleted since their batch
+/// was scanned are skipped, and no key is kept open by the iterator itself.
+/// Same as `SCAN`, a key might be yielded more than once.
+pub struct ScanIter<'ctx> {
+    ctx: &'ctx Context,
+    cursor: KeysCursor,
+    batch: VecDeque<RedisString>,
+    done: bool,
+}
+
+impl<'ctx> ScanIter<'ctx> {
+    fn new(ctx: &'ctx Context) -> Self {
+        Self {
+            ctx,
+            cursor: KeysCursor::new(),
+            batch: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    fn scan_batch(&mut self) {
+        let batch = &mut self.batch;
+        let callback = |_ctx: &Context, key_name: RedisString, _key: Option<&RedisKey>| {
+            batch.push_back(key_name);
+        };
+        self.done = !self.cursor.scan(self.ctx, &callback);
+    }
+}
+
+impl<'ctx> Iterator for ScanIter<'ctx> {
+    type Item = (RedisString, RedisKey);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(key_name) = self.batch.pop_front() {
+                let key = self.ctx.open_key(&key_name);
+                if key.is_null() {
+                    continue;
+                }
+                return Some((key_name, key));
+            }
+            if self.done {
+                return None;
+            }
+            self.scan_batch();
+        }
+    }
+}
+
+impl Context {
+    /// Return an iterator over all the keys of the selected database, with
+    /// each key opened for reading, see [ScanIter].
+    pub fn scan_iter(&self) -> ScanIter<'_> {
+        ScanIter::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CursorInner, KeysCursor};
+    use crate::context::Context;
+    use crate::key::RedisKey;
+    use crate::redismodule::RedisString;
+    use crate::RedisError;
+
+    #[test]
+    fn failed_batch_is_not_the_end_of_the_scan() {
+        let ctx = Context::dummy();
+        let cursor = KeysCursor::from_token(42);
+        let position = match &cursor.inner {
+            CursorInner::Position(position) => position,
+            CursorInner::Native(_) => unreachable!(),
+        };
+        let callback = |_ctx: &Context, _key_name: RedisString, _key: Option<&RedisKey>| {};
+
+        let res = KeysCursor::scan_position(&ctx, position, &callback, |_, _| {
+            Err(RedisError::Str("ERR scan failed"))
+        });
+        assert_eq!(res.unwrap_err().to_string(), "ERR scan failed");
+        assert_eq!(cursor.token(), Some(42));
+
+        let res = KeysCursor::scan_position(&ctx, position, &callback, |_, cursor| {
+            assert_eq!(cursor, 42);
+            Ok((0, Vec::new()))
+        });
+        assert!(!res.unwrap());
+        assert_eq!(cursor.token(), Some(0));
     }
 }
@@ -2,39 +2,55 @@ use bitflags::bitflags;
 use redis_module_macros_internals::api;
 use std::collections::{BTreeMap, HashMap};
 use std::ffi::CString;
+use std::net::IpAddr;
 use std::os::raw::c_void;
 use std::os::raw::{c_char, c_int, c_long, c_longlong};
 use std::ptr::{self, NonNull};
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::OnceLock;
 
-use crate::key::{KeyFlags, RedisKey, RedisKeyWritable};
+use crate::key::{KeyFlags, KeySnapshot, RedisKey, RedisKeyWritable};
 use crate::logging::RedisLogLevel;
 use crate::raw::{ModuleOptions, Version};
-use crate::redisvalue::RedisValueKey;
+use crate::rediserror::error_with_code;
+use crate::redisvalue::{RedisValueKey, DEFAULT_MAX_REPLY_DEPTH};
 use crate::{
     add_info_begin_dict_field, add_info_end_dict_field, add_info_field_double,
     add_info_field_long_long, add_info_field_str, add_info_field_unsigned_long_long, raw, utils,
     Status,
 };
 use crate::{add_info_section, RedisResult};
-use crate::{RedisError, RedisString, RedisValue};
+use crate::{RedisDict, RedisError, RedisString, RedisValue};
+use std::marker::PhantomData;
 use std::ops::Deref;
 
 use std::ffi::CStr;
 
-use self::call_reply::{create_promise_call_reply, CallResult, PromiseCallReply};
+use self::call_reply::{
+    create_promise_call_reply, CallResult, ErrorReply, FromCallReply, PromiseCallReply,
+};
+use self::reply::ReplyProtocol;
 use self::thread_safe::RedisLockIndicator;
 
 mod timer;
 
 pub mod blocked;
 pub mod call_reply;
+pub mod client;
+pub mod commandfilter;
 pub mod commands;
 pub mod defrag;
 pub mod info;
 pub mod keys_cursor;
+pub mod reply;
 pub mod server_events;
 pub mod thread_safe;
+pub mod write_behind;
+
+const LOADING_ERROR: &str = "LOADING Redis is loading the dataset in memory";
+
+/// The id Redis gives to the fake client replaying the AOF.
+const AOF_CLIENT_ID: u64 = u64::MAX;
 
 pub struct CallOptionsBuilder {
     options: String,
@@ -247,6 +263,58 @@ impl DetachedContext {
 unsafe impl Send for DetachedContext {}
 unsafe impl Sync for DetachedContext {}
 
+/// A detached thread safe context owned by its holder, unlike the
+/// [DetachedContext] statics which keep their context for the lifetime of the
+/// module. The context is freed when this is dropped, so it suits background
+/// workers which each need their own context. Created with
+/// [Context::create_detached_context].
+pub struct OwnedDetachedContext {
+    ctx: NonNull<raw::RedisModuleCtx>,
+}
+
+unsafe impl Send for OwnedDetachedContext {}
+
+impl OwnedDetachedContext {
+    pub fn log(&self, level: RedisLogLevel, message: &str) {
+        crate::logging::log_internal(self.ctx.as_ptr(), level, message);
+    }
+
+    /// Lock Redis for command invocation, same as [DetachedContext::lock].
+    /// The returned guard can not outlive this context.
+    pub fn lock(&self) -> OwnedDetachedContextGuard<'_> {
+        unsafe { raw::RedisModule_ThreadSafeContextLock.unwrap()(self.ctx.as_ptr()) };
+        OwnedDetachedContextGuard {
+            guard: DetachedContextGuard {
+                ctx: Context::new(self.ctx.as_ptr()),
+            },
+            _detached_context: PhantomData,
+        }
+    }
+}
+
+impl Drop for OwnedDetachedContext {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_FreeThreadSafeContext.unwrap()(self.ctx.as_ptr()) };
+    }
+}
+
+/// The [DetachedContextGuard] of an [OwnedDetachedContext], unlocking Redis
+/// when dropped.
+pub struct OwnedDetachedContextGuard<'a> {
+    guard: DetachedContextGuard,
+    _detached_context: PhantomData<&'a OwnedDetachedContext>,
+}
+
+unsafe impl<'a> RedisLockIndicator for OwnedDetachedContextGuard<'a> {}
+
+impl<'a> Deref for OwnedDetachedContextGuard<'a> {
+    type Target = Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
 /// `Context` is a structure that's designed to give us a high-level interface to
 /// the Redis module API by abstracting away the raw C FFI calls.
 #[derive(Debug)]
@@ -254,6 +322,23 @@ pub struct Context {
     pub ctx: *mut raw::RedisModuleCtx,
 }
 
+/// Set while running [Context::with_replicated_effects]. Commands only run
+/// while the GIL is held, so a single flag is enough.
+static REPLICATE_EFFECTS: AtomicBool = AtomicBool::new(false);
+
+/// The server version, see [Context::server_version_cached].
+static SERVER_VERSION: OnceLock<Version> = OnceLock::new();
+
+/// Add the `!` flag, which replicates the called command, to the `fmt` of
+/// `RedisModule_Call`.
+fn with_replicate_flag(fmt: *const c_char) -> CString {
+    let mut flags = unsafe { CStr::from_ptr(fmt) }.to_bytes().to_vec();
+    if !flags.contains(&b'!') {
+        flags.push(b'!');
+    }
+    CString::new(flags).unwrap()
+}
+
 /// A guerd that protected a user that has
 /// been set on a context using `autenticate_user`.
 /// This guerd make sure to unset the user when freed.
@@ -281,6 +366,9 @@ impl<'ctx> ContextUserScope<'ctx> {
 pub struct StrCallArgs<'a> {
     is_owner: bool,
     args: Vec<*mut raw::RedisModuleString>,
+    // The arguments created by a [CallArgs], freed with the call arguments
+    // whether or not `is_owner` is set.
+    _created: Vec<RedisString>,
     // Phantom is used to make sure the object will not live longer than actual arguments slice
     phantom: std::marker::PhantomData<&'a raw::RedisModuleString>,
 }
@@ -303,6 +391,7 @@ impl<'a, T: AsRef<[u8]> + ?Sized> From<&'a [&T]> for StrCallArgs<'a> {
                 .iter()
                 .map(|v| RedisString::create_from_slice(std::ptr::null_mut(), v.as_ref()).take())
                 .collect(),
+            _created: Vec::new(),
             phantom: std::marker::PhantomData,
         }
     }
@@ -313,6 +402,18 @@ impl<'a> From<&'a [&RedisString]> for StrCallArgs<'a> {
         StrCallArgs {
             is_owner: false,
             args: vals.iter().map(|v| v.inner).collect(),
+            _created: Vec::new(),
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a> From<&'a [RedisString]> for StrCallArgs<'a> {
+    fn from(vals: &'a [RedisString]) -> Self {
+        StrCallArgs {
+            is_owner: false,
+            args: vals.iter().map(|v| v.inner).collect(),
+            _created: Vec::new(),
             phantom: std::marker::PhantomData,
         }
     }
@@ -327,6 +428,69 @@ where
     }
 }
 
+/// Builds the arguments of a call from arguments of different types, for
+/// example a key name given to the command followed by binary values:
+///
+/// ```ignore
+/// let args = CallArgs::new()
+///     .push_redis_string(&key_name)
+///     .push_str("field")
+///     .push_bytes(&value);
+/// ctx.call("HSET", args)?;
+/// ```
+///
+/// Strings are only created for the `&str` and `&[u8]` arguments, and only
+/// those are freed once the call is done.
+#[derive(Default)]
+pub struct CallArgs<'a> {
+    args: Vec<*mut raw::RedisModuleString>,
+    created: Vec<RedisString>,
+    phantom: std::marker::PhantomData<&'a RedisString>,
+}
+
+impl<'a> CallArgs<'a> {
+    pub fn new() -> CallArgs<'a> {
+        Self::default()
+    }
+
+    pub fn push_str(self, arg: &str) -> CallArgs<'a> {
+        self.push_bytes(arg.as_bytes())
+    }
+
+    pub fn push_bytes(mut self, arg: &[u8]) -> CallArgs<'a> {
+        let arg = RedisString::create_from_slice(std::ptr::null_mut(), arg);
+        self.args.push(arg.inner);
+        self.created.push(arg);
+        self
+    }
+
+    /// Pass `arg` as is, without copying it.
+    pub fn push_redis_string(mut self, arg: &'a RedisString) -> CallArgs<'a> {
+        self.args.push(arg.inner);
+        self
+    }
+
+    /// Return the number of arguments.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+}
+
+impl<'a> From<CallArgs<'a>> for StrCallArgs<'a> {
+    fn from(args: CallArgs<'a>) -> Self {
+        StrCallArgs {
+            is_owner: false,
+            args: args.args,
+            _created: args.created,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
 impl<'a> StrCallArgs<'a> {
     pub(crate) fn args_mut(&mut self) -> &mut [*mut raw::RedisModuleString] {
         &mut self.args
@@ -410,6 +574,22 @@ impl Context {
         args: T,
     ) -> R {
         let mut call_args: StrCallArgs = args.into();
+        self.call_with_args(command, fmt, &mut call_args)
+    }
+
+    fn call_with_args<'ctx, R: From<PromiseCallReply<'static, 'ctx>>>(
+        &'ctx self,
+        command: &str,
+        fmt: *const c_char,
+        call_args: &mut StrCallArgs,
+    ) -> R {
+        let replicate_fmt;
+        let fmt = if REPLICATE_EFFECTS.load(Ordering::Relaxed) {
+            replicate_fmt = with_replicate_flag(fmt);
+            replicate_fmt.as_ptr()
+        } else {
+            fmt
+        };
         let final_args = call_args.args_mut();
 
         let cmd = CString::new(command).unwrap();
@@ -427,11 +607,60 @@ impl Context {
         R::from(promise)
     }
 
+    /// Invoke a command on Redis and return the result as an owned [RedisValue].
+    /// The underlying [crate::CallReply] is converted and freed before returning, so the
+    /// result has no lifetime tied to the call. Error replies are returned as [Err].
+    /// Use [Context::call_ext] to control the invocation or to access the [crate::CallReply].
     pub fn call<'a, T: Into<StrCallArgs<'a>>>(&self, command: &str, args: T) -> RedisResult {
         self.call_internal::<_, CallResult>(command, raw::FMT, args)
             .map_or_else(|e| Err(e.into()), |v| Ok((&v).into()))
     }
 
+    /// Invoke a command on the already opened key `key_inner`, the name of the
+    /// key is passed as the first argument, followed by `args`. Fails if the
+    /// key has no name, like a missing key opened for reading.
+    ///
+    /// `RedisModule_Call` has no variant accepting an opened key, so Redis
+    /// still looks up the key, but the key name is taken from the key itself
+    /// instead of creating a new string for it.
+    pub(crate) fn call_on_key<'a, T: Into<StrCallArgs<'a>>>(
+        &self,
+        command: &str,
+        key_inner: *mut raw::RedisModuleKey,
+        args: T,
+    ) -> RedisResult {
+        if key_inner.is_null() {
+            return Err(RedisError::Str("ERR key does not exist"));
+        }
+        let key_name = unsafe { raw::RedisModule_GetKeyNameFromModuleKey.unwrap()(key_inner) };
+        if key_name.is_null() {
+            return Err(RedisError::Str("ERR key does not exist"));
+        }
+        let mut call_args: StrCallArgs = args.into();
+        call_args
+            .args
+            .insert(0, key_name as *mut raw::RedisModuleString);
+        let res = self
+            .call_with_args::<CallResult>(command, raw::FMT, &mut call_args)
+            .map_or_else(|e| Err(e.into()), |v| Ok((&v).into()));
+        // The key name is owned by the key, make sure it is not freed with the arguments.
+        call_args.args.remove(0);
+        res
+    }
+
+    /// Invoke a command on Redis and convert the reply into `R`, see [FromCallReply].
+    /// Unlike [Context::call], the reply is converted directly from the
+    /// [crate::CallReply], without creating a [RedisValue]. Error replies, and replies
+    /// that do not match `R`, are returned as [Err].
+    pub fn call_typed<'a, R: FromCallReply, T: Into<StrCallArgs<'a>>>(
+        &self,
+        command: &str,
+        args: T,
+    ) -> Result<R, RedisError> {
+        let reply = self.call_internal::<_, CallResult>(command, raw::FMT, args)?;
+        R::from_call_reply(reply.without_attribute())
+    }
+
     /// Invoke a command on Redis and return the result
     /// Unlike 'call' this API also allow to pass a CallOption to control different aspects
     /// of the command invocation.
@@ -490,8 +719,64 @@ impl Context {
         unsafe { raw::RedisModule_ReplyWithError.unwrap()(self.ctx, msg.as_ptr()).into() }
     }
 
+    /// Reply with an error of the given code, formatted as `CODE message`.
+    /// The code must be made of uppercase ASCII letters, digits and
+    /// underscores, otherwise the generic `ERR` code is used.
+    #[allow(clippy::must_use_candidate)]
+    pub fn reply_error_with_code(&self, code: &str, message: &str) -> raw::Status {
+        self.reply_error_string(&error_with_code(code, message))
+    }
+
+    /// Forward an error returned from [Context::call_ext] to the client as is,
+    /// including the attributes attached to it on RESP3. Falls back to replying
+    /// with the error message when the reply can not be forwarded, for example
+    /// a RESP3 reply to a RESP2 client.
+    pub fn reply_with_error_from_call_reply(&self, error: &ErrorReply) -> raw::Status {
+        let msg = match error {
+            ErrorReply::RedisError(reply) => {
+                let res: raw::Status = unsafe {
+                    raw::RedisModule_ReplyWithCallReply.unwrap()(self.ctx, reply.as_ptr())
+                }
+                .into();
+                if res == raw::Status::Ok {
+                    return res;
+                }
+                error.to_string()
+            }
+            ErrorReply::Message(msg) => msg.clone(),
+        };
+        self.reply_error_string(&msg)
+    }
+
+    /// Reply with a RESP3 verbatim string in markdown (`mkd`) format, which
+    /// redis-cli displays as is. RESP2 clients get a plain bulk string.
+    #[allow(clippy::must_use_candidate)]
+    pub fn reply_markdown(&self, s: &str) -> raw::Status {
+        self.reply_with_verbatim(s, b"mkd")
+    }
+
+    /// Reply with a RESP3 verbatim string in plain text (`txt`) format.
+    /// RESP2 clients get a plain bulk string.
+    #[allow(clippy::must_use_candidate)]
+    pub fn reply_text(&self, s: &str) -> raw::Status {
+        self.reply_with_verbatim(s, b"txt")
+    }
+
+    fn reply_with_verbatim(&self, s: &str, format: &[u8; 3]) -> raw::Status {
+        raw::reply_with_verbatim_string(
+            self.ctx,
+            s.as_ptr().cast::<c_char>(),
+            s.len(),
+            format.as_ptr().cast::<c_char>(),
+        )
+    }
+
     pub fn reply_with_key(&self, result: RedisValueKey) -> raw::Status {
-        match result {
+        self.reply_key(result, &ReplyProtocol::new(self))
+    }
+
+    fn reply_key(&self, key: RedisValueKey, protocol: &ReplyProtocol) -> raw::Status {
+        match key {
             RedisValueKey::Integer(i) => raw::reply_with_long_long(self.ctx, i),
             RedisValueKey::String(s) => {
                 raw::reply_with_string_buffer(self.ctx, s.as_ptr().cast::<c_char>(), s.len())
@@ -500,7 +785,8 @@ impl Context {
                 raw::reply_with_string_buffer(self.ctx, b.as_ptr().cast::<c_char>(), b.len())
             }
             RedisValueKey::BulkRedisString(s) => raw::reply_with_string(self.ctx, s.inner),
-            RedisValueKey::Bool(b) => raw::reply_with_bool(self.ctx, b.into()),
+            RedisValueKey::Bool(b) => protocol.get().reply_with_bool(self.ctx, b),
+            RedisValueKey::Double(d) => protocol.get().reply_with_double_key(self.ctx, &d),
         }
     }
 
@@ -509,8 +795,43 @@ impl Context {
     /// Will panic if methods used are missing in redismodule.h
     #[allow(clippy::must_use_candidate)]
     pub fn reply(&self, result: RedisResult) -> raw::Status {
+        self.reply_with_max_depth(result, DEFAULT_MAX_REPLY_DEPTH)
+    }
+
+    /// Same as [Context::reply], but replies nested deeper than `max_depth`
+    /// aggregates (arrays, maps and sets) are replaced with an error reply at
+    /// the position where the limit was reached, instead of recursing further.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if methods used are missing in redismodule.h
+    #[allow(clippy::must_use_candidate)]
+    pub fn reply_with_max_depth(&self, result: RedisResult, max_depth: usize) -> raw::Status {
+        self.reply_value(result, max_depth, &ReplyProtocol::new(self))
+    }
+
+    /// Reply with `result` in a form a client using `protocol` can parse, the
+    /// protocol is only checked once for the whole reply, if at all.
+    fn reply_value(
+        &self,
+        result: RedisResult,
+        max_depth: usize,
+        protocol: &ReplyProtocol,
+    ) -> raw::Status {
         match result {
-            Ok(RedisValue::Bool(v)) => raw::reply_with_bool(self.ctx, v.into()),
+            Ok(
+                RedisValue::Array(_)
+                | RedisValue::Map(_)
+                | RedisValue::OrderedMap(_)
+                | RedisValue::InsertionOrderedMap(_)
+                | RedisValue::Set(_)
+                | RedisValue::OrderedSet(_),
+            ) if max_depth == 0 => {
+                self.reply_error_string("Reply nesting exceeds the maximum allowed depth");
+                raw::Status::Err
+            }
+
+            Ok(RedisValue::Bool(v)) => protocol.get().reply_with_bool(self.ctx, v),
             Ok(RedisValue::Integer(v)) => raw::reply_with_long_long(self.ctx, v),
             Ok(RedisValue::Float(v)) => raw::reply_with_double(self.ctx, v),
             Ok(RedisValue::SimpleStringStatic(s)) => {
@@ -531,12 +852,18 @@ impl Context {
                 raw::reply_with_big_number(self.ctx, s.as_ptr().cast::<c_char>(), s.len())
             }
 
-            Ok(RedisValue::VerbatimString((format, data))) => raw::reply_with_verbatim_string(
-                self.ctx,
-                data.as_ptr().cast(),
-                data.len(),
-                format.0.as_ptr().cast(),
-            ),
+            Ok(RedisValue::VerbatimString((format, data))) => match format.validate() {
+                Ok(()) => raw::reply_with_verbatim_string(
+                    self.ctx,
+                    data.as_ptr().cast(),
+                    data.len(),
+                    format.0.as_ptr().cast(),
+                ),
+                Err(e) => {
+                    self.log_warning(&format!("Refusing to reply with verbatim string: {e}"));
+                    self.reply_value(Err(e), max_depth, protocol)
+                }
+            },
 
             Ok(RedisValue::BulkRedisString(s)) => raw::reply_with_string(self.ctx, s.inner),
 
@@ -548,47 +875,58 @@ impl Context {
                 raw::reply_with_array(self.ctx, array.len() as c_long);
 
                 for elem in array {
-                    self.reply(Ok(elem));
+                    self.reply_value(Ok(elem), max_depth - 1, protocol);
                 }
 
                 raw::Status::Ok
             }
 
             Ok(RedisValue::Map(map)) => {
-                raw::reply_with_map(self.ctx, map.len() as c_long);
+                protocol.get().reply_with_map(self.ctx, map.len() as c_long);
 
                 for (key, value) in map {
-                    self.reply_with_key(key);
-                    self.reply(Ok(value));
+                    self.reply_key(key, protocol);
+                    self.reply_value(Ok(value), max_depth - 1, protocol);
                 }
 
                 raw::Status::Ok
             }
 
             Ok(RedisValue::OrderedMap(map)) => {
-                raw::reply_with_map(self.ctx, map.len() as c_long);
+                protocol.get().reply_with_map(self.ctx, map.len() as c_long);
 
                 for (key, value) in map {
-                    self.reply_with_key(key);
-                    self.reply(Ok(value));
+                    self.reply_key(key, protocol);
+                    self.reply_value(Ok(value), max_depth - 1, protocol);
+                }
+
+                raw::Status::Ok
+            }
+
+            Ok(RedisValue::InsertionOrderedMap(map)) => {
+                protocol.get().reply_with_map(self.ctx, map.len() as c_long);
+
+                for (key, value) in map {
+                    self.reply_key(key, protocol);
+                    self.reply_value(Ok(value), max_depth - 1, protocol);
                 }
 
                 raw::Status::Ok
             }
 
             Ok(RedisValue::Set(set)) => {
-                raw::reply_with_set(self.ctx, set.len() as c_long);
+                protocol.get().reply_with_set(self.ctx, set.len() as c_long);
                 set.into_iter().for_each(|e| {
-                    self.reply_with_key(e);
+                    self.reply_key(e, protocol);
                 });
 
                 raw::Status::Ok
             }
 
             Ok(RedisValue::OrderedSet(set)) => {
-                raw::reply_with_set(self.ctx, set.len() as c_long);
+                protocol.get().reply_with_set(self.ctx, set.len() as c_long);
                 set.into_iter().for_each(|e| {
-                    self.reply_with_key(e);
+                    self.reply_key(e, protocol);
                 });
 
                 raw::Status::Ok
@@ -600,6 +938,8 @@ impl Context {
 
             Ok(RedisValue::StaticError(s)) => self.reply_error_string(s),
 
+            Ok(RedisValue::Error(s)) => self.reply_error_string(&s),
+
             Err(RedisError::WrongArity) => unsafe {
                 if self.is_keys_position_request() {
                     // We can't return a result since we don't have a client
@@ -616,6 +956,10 @@ impl Context {
             Err(RedisError::String(s)) => self.reply_error_string(s.as_str()),
 
             Err(RedisError::Str(s)) => self.reply_error_string(s),
+
+            Err(RedisError::WithCode { code, message }) => {
+                self.reply_error_with_code(&code, &message)
+            }
         }
     }
 
@@ -643,20 +987,95 @@ impl Context {
         RedisKeyWritable::open_with_flags(self.ctx, key, flags)
     }
 
+    /// Run `f`, replicating the effects of the commands it invokes with
+    /// [Context::call] (and the other `call` methods) to the replicas and the
+    /// AOF, as if they were all invoked with [CallOptionsBuilder::replicate].
+    /// The command running `f` should then not be replicated verbatim, so it
+    /// is replicated as what it did instead of what it was asked to do.
+    pub fn with_replicated_effects<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = REPLICATE_EFFECTS.swap(true, Ordering::Relaxed);
+        let res = f();
+        REPLICATE_EFFECTS.store(previous, Ordering::Relaxed);
+        res
+    }
+
     pub fn replicate_verbatim(&self) {
         raw::replicate_verbatim(self.ctx);
     }
 
     /// Replicate command to the replica and AOF.
+    ///
+    /// The replicated commands are propagated once the running command
+    /// returns, in the order they were replicated, along with the command
+    /// itself if [Context::replicate_verbatim] was called (at the position
+    /// it was called). If more than one command is propagated, they are
+    /// wrapped in a `MULTI`/`EXEC` so replicas apply them atomically.
     pub fn replicate<'a, T: Into<StrCallArgs<'a>>>(&self, command: &str, args: T) {
         raw::replicate(self.ctx, command, args);
     }
 
+    /// Same as [Context::replicate], for arguments which are already
+    /// [RedisString]s, for example the processed arguments of the running
+    /// command.
+    pub fn replicate_args(&self, command: &str, args: &[RedisString]) {
+        self.replicate(command, args);
+    }
+
+    /// Same as [Context::replicate], for arguments which are [RedisValue]s.
+    /// Only strings and numbers can be used as arguments, any other value
+    /// is an error and nothing is replicated. Infinite floats are replicated
+    /// as `inf` and `-inf`, as Redis parses them, and NaN is an error.
+    pub fn replicate_typed(&self, command: &str, args: &[RedisValue]) -> Result<(), RedisError> {
+        let args = args
+            .iter()
+            .try_fold(CallArgs::new(), |call_args, arg| match arg {
+                RedisValue::SimpleStringStatic(s) => Ok(call_args.push_str(s)),
+                RedisValue::SimpleString(s) | RedisValue::BulkString(s) => {
+                    Ok(call_args.push_str(s))
+                }
+                RedisValue::BulkRedisString(s) => Ok(call_args.push_redis_string(s)),
+                RedisValue::StringBuffer(s) => Ok(call_args.push_bytes(s)),
+                RedisValue::Integer(i) => Ok(call_args.push_str(&i.to_string())),
+                RedisValue::Float(f) if f.is_nan() => Err(RedisError::Str(
+                    "NaN can not be replicated as a command argument",
+                )),
+                RedisValue::Float(f) if f.is_infinite() => {
+                    Ok(call_args.push_str(if *f > 0.0 { "inf" } else { "-inf" }))
+                }
+                RedisValue::Float(f) => Ok(call_args.push_str(&f.to_string())),
+                _ => Err(RedisError::Str(
+                    "Only strings and numbers can be replicated as command arguments",
+                )),
+            })?;
+        self.replicate(command, args);
+        Ok(())
+    }
+
+    /// Replicate command only to the given targets, for example only to the
+    /// AOF (for crash recovery) and not to the replicas (which compute the
+    /// data themselves). Returns [raw::Status::Err] without replicating
+    /// anything if `target` is empty.
+    pub fn replicate_with_flags<'a, T: Into<StrCallArgs<'a>>>(
+        &self,
+        command: &str,
+        args: T,
+        target: raw::ReplicationTarget,
+    ) -> raw::Status {
+        raw::replicate_with_flags(self.ctx, command, args, target)
+    }
+
     #[must_use]
     pub fn create_string<T: Into<Vec<u8>>>(&self, s: T) -> RedisString {
         RedisString::create(NonNull::new(self.ctx), s)
     }
 
+    /// Create an empty [RedisDict]. The dictionary is not bound to this
+    /// context and may be kept after the command returns.
+    #[must_use]
+    pub fn create_dict<V>(&self) -> RedisDict<V> {
+        RedisDict::new()
+    }
+
     #[must_use]
     pub const fn get_raw(&self) -> *mut raw::RedisModuleCtx {
         self.ctx
@@ -673,6 +1092,94 @@ impl Context {
         raw::export_shared_api(self.ctx, func, name);
     }
 
+    /// Export `func` under `name`, together with its `signature`, so that
+    /// other modules can compare it when importing `func` with
+    /// [Context::import_typed_shared_api]. Use [crate::export_api] instead
+    /// of calling this directly.
+    ///
+    /// # Safety
+    ///
+    /// `func` must be a function of the type described by `signature`, and
+    /// must stay valid as long as the module is loaded. The signature is
+    /// only compared as text, it catches mistakes but does not prove that
+    /// both modules agree on the ABI of the function.
+    #[doc(hidden)]
+    pub unsafe fn export_typed_shared_api(
+        &self,
+        name: &str,
+        func: *const c_void,
+        signature: &'static CStr,
+    ) -> Result<(), RedisError> {
+        let export = |name: String, ptr: *const c_void| {
+            let c_name =
+                CString::new(name.as_str()).map_err(|e| RedisError::String(e.to_string()))?;
+            let res = unsafe {
+                raw::RedisModule_ExportSharedAPI.unwrap()(
+                    self.ctx,
+                    c_name.as_ptr(),
+                    ptr as *mut c_void,
+                )
+            };
+            if res != raw::Status::Ok as c_int {
+                return Err(RedisError::String(format!(
+                    "Failed exporting shared API {name}, name is already taken"
+                )));
+            }
+            // Redis keeps a reference to the name, so it must outlive the module.
+            let _ = c_name.into_raw();
+            Ok(())
+        };
+
+        export(format!("{name}.signature"), signature.as_ptr().cast())?;
+        export(name.to_owned(), func)
+    }
+
+    /// Import a function exported with [Context::export_typed_shared_api],
+    /// failing if it was exported with a different `signature`. Use
+    /// [crate::import_api] instead of calling this directly.
+    ///
+    /// # Safety
+    ///
+    /// The returned pointer may only be cast to the function type described
+    /// by `signature`. Matching signatures are written the same way, which
+    /// does not guarantee that the exporting module uses the same types
+    /// (for example a type of the same name from another crate) or ABI.
+    #[doc(hidden)]
+    pub unsafe fn import_typed_shared_api(
+        &self,
+        name: &str,
+        signature: &CStr,
+    ) -> Result<*mut c_void, RedisError> {
+        let get = |name: String| {
+            let name = CString::new(name).map_err(|e| RedisError::String(e.to_string()))?;
+            Ok::<_, RedisError>(unsafe {
+                raw::RedisModule_GetSharedAPI.unwrap()(self.ctx, name.as_ptr())
+            })
+        };
+
+        let exported_signature = get(format!("{name}.signature"))?;
+        if exported_signature.is_null() {
+            return Err(RedisError::String(format!(
+                "Shared API {name} was not exported"
+            )));
+        }
+        let exported_signature = unsafe { CStr::from_ptr(exported_signature.cast()) };
+        if exported_signature != signature {
+            return Err(RedisError::String(format!(
+                "Shared API {name} signature mismatch, exported as `{}`",
+                exported_signature.to_string_lossy()
+            )));
+        }
+
+        let func = get(name.to_owned())?;
+        if func.is_null() {
+            return Err(RedisError::String(format!(
+                "Shared API {name} was not exported"
+            )));
+        }
+        Ok(func)
+    }
+
     /// # Safety
     ///
     /// See [raw::notify_keyspace_event].
@@ -686,15 +1193,70 @@ impl Context {
         unsafe { raw::notify_keyspace_event(self.ctx, event_type, event, keyname) }
     }
 
+    /// Returns the `notify-keyspace-events` flags both parsed into
+    /// [raw::NotifyEvent] and as the raw value reported by the server.
+    /// Bits set on the raw value but missing from the parsed flags are
+    /// not known to this version of the crate.
+    #[must_use]
+    pub fn keyspace_events_detailed(&self) -> (raw::NotifyEvent, c_int) {
+        let events = raw::get_keyspace_events_raw();
+        (raw::NotifyEvent::from_bits_truncate(events), events)
+    }
+
     pub fn current_command_name(&self) -> Result<String, RedisError> {
-        unsafe {
-            match raw::RedisModule_GetCurrentCommandName {
-                Some(cmd) => Ok(CStr::from_ptr(cmd(self.ctx)).to_str().unwrap().to_string()),
-                None => Err(RedisError::Str(
-                    "API RedisModule_GetCurrentCommandName is not available",
+        self.current_command_name_str().map(str::to_string)
+    }
+
+    /// Same as [Context::current_command_name] but borrows the name from
+    /// the Redis owned buffer instead of allocating a new [String].
+    pub fn current_command_name_str(&self) -> Result<&str, RedisError> {
+        let cmd = unsafe { raw::RedisModule_GetCurrentCommandName }.ok_or(RedisError::Str(
+            "API RedisModule_GetCurrentCommandName is not available",
+        ))?;
+        let name = unsafe { cmd(self.ctx) };
+        if name.is_null() {
+            return Err(RedisError::Str("No command is currently running"));
+        }
+        unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .map_err(|_| RedisError::Str("Command name is not a valid utf8"))
+    }
+
+    /// Return the keys of the current command, computed from the key specs the
+    /// command declared. `args` are the arguments the command was invoked with,
+    /// including the command name itself.
+    pub fn current_command_keys(
+        &self,
+        args: &[RedisString],
+    ) -> Result<Vec<RedisString>, RedisError> {
+        let get_command_keys = unsafe { raw::RedisModule_GetCommandKeys }.ok_or(
+            RedisError::Str("API RedisModule_GetCommandKeys is not available"),
+        )?;
+        let mut argv: Vec<*mut raw::RedisModuleString> = args.iter().map(|a| a.inner).collect();
+        let mut num_keys: c_int = 0;
+        let positions = unsafe {
+            get_command_keys(
+                self.ctx,
+                argv.as_mut_ptr(),
+                argv.len() as c_int,
+                &mut num_keys,
+            )
+        };
+        if positions.is_null() {
+            // Redis sets errno to 0 when the command is valid but has no keys.
+            return match std::io::Error::last_os_error().raw_os_error() {
+                Some(0) => Ok(Vec::new()),
+                _ => Err(RedisError::Str(
+                    "Could not get the command keys, invalid command or arity",
                 )),
-            }
+            };
         }
+        let keys = unsafe { std::slice::from_raw_parts(positions, num_keys as usize) }
+            .iter()
+            .map(|pos| args[*pos as usize].safe_clone(self))
+            .collect();
+        unsafe { raw::RedisModule_Free.unwrap()(positions.cast()) };
+        Ok(keys)
     }
 
     /// Returns the redis version either by calling `RedisModule_GetServerVersion` API,
@@ -724,6 +1286,17 @@ impl Context {
         Err(RedisError::Str("Error getting redis_version"))
     }
 
+    /// Returns the redis version, like [Context::get_redis_version], but only
+    /// retrieves it once per process (the server version can not change at
+    /// runtime). A failure to retrieve it is not cached.
+    pub fn server_version_cached(&self) -> Result<Version, RedisError> {
+        if let Some(version) = SERVER_VERSION.get() {
+            return Ok(*version);
+        }
+        let version = self.get_redis_version()?;
+        Ok(*SERVER_VERSION.get_or_init(|| version))
+    }
+
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
     fn get_redis_version_internal(&self, force_use_rm_call: bool) -> Result<Version, RedisError> {
         match unsafe { raw::RedisModule_GetServerVersion } {
@@ -756,6 +1329,33 @@ impl Context {
         })
     }
 
+    /// Return `false` if the current command is not allowed to block the
+    /// client, for example inside `MULTI` or a Lua script. Such commands should
+    /// reply right away (or fail) instead of calling [Context::block_client].
+    pub fn can_block(&self) -> bool {
+        !self
+            .get_flags()
+            .intersects(ContextFlags::DENY_BLOCKING | ContextFlags::MULTI | ContextFlags::LUA)
+    }
+
+    /// Return a `LOADING` error while the server is loading its dataset, the
+    /// same error Redis returns for commands which are not allowed during
+    /// loading. Commands replayed from the AOF, which also run while loading,
+    /// are not refused.
+    ///
+    /// Redis only calls commands with the `allow-loading` flag while loading,
+    /// so this is needed by such commands for the parts that should wait
+    /// until the dataset is loaded.
+    pub fn refuse_if_loading(&self) -> RedisResult<()> {
+        if !self.get_flags().contains(ContextFlags::LOADING) {
+            return Ok(());
+        }
+        if self.get_client_id() == AOF_CLIENT_ID {
+            return Ok(());
+        }
+        Err(RedisError::Str(LOADING_ERROR))
+    }
+
     /// Return the current user name attached to the context
     pub fn get_current_user(&self) -> RedisString {
         let user = unsafe { raw::RedisModule_GetCurrentUserName.unwrap()(self.ctx) };
@@ -808,6 +1408,64 @@ impl Context {
         acl_permission_result.map_err(|_e| RedisError::Str("User does not have permissions on key"))
     }
 
+    /// Recreate a key from a [KeySnapshot] taken with [crate::key::RedisKey::dump_with_metadata],
+    /// keeping its absolute expire. If `replace` is set, an existing key is overwritten,
+    /// otherwise restoring over an existing key returns an error.
+    pub fn restore_key_snapshot(
+        &self,
+        key_name: &RedisString,
+        snapshot: &KeySnapshot,
+        replace: bool,
+    ) -> Result<(), RedisError> {
+        let ttl = snapshot.expire_at.unwrap_or(0).to_string();
+        let mut args: Vec<&[u8]> = vec![
+            key_name.as_slice(),
+            ttl.as_bytes(),
+            snapshot.payload.as_slice(),
+        ];
+        if snapshot.expire_at.is_some() {
+            args.push(b"ABSTTL");
+        }
+        if replace {
+            args.push(b"REPLACE");
+        }
+        self.call("RESTORE", args.as_slice()).map(|_| ())
+    }
+
+    /// Return the IP address and port of the client with the given id.
+    /// Return `None` if no such client exists, if the client is connected
+    /// over a unix socket or if its address can not be parsed.
+    pub fn get_client_addr(&self, id: u64) -> Option<(IpAddr, u16)> {
+        let info = self.get_client_info_by_id(id).ok()?;
+        Some((info.ip()?, info.port))
+    }
+
+    /// Create a detached thread safe context, which is freed when the
+    /// returned [OwnedDetachedContext] is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetDetachedThreadSafeContext` is missing in redismodule.h
+    pub fn create_detached_context(&self) -> OwnedDetachedContext {
+        let ctx = unsafe { raw::RedisModule_GetDetachedThreadSafeContext.unwrap()(self.ctx) };
+        OwnedDetachedContext {
+            ctx: NonNull::new(ctx).expect("detached context must not be null"),
+        }
+    }
+
+    /// Return the name of the client with the given id, as set with
+    /// `CLIENT SETNAME`. Return `None` if no such client exists, if the
+    /// client has no name, or if the server does not expose
+    /// `RedisModule_GetClientNameById`.
+    pub fn get_client_name_by_id(&self, id: u64) -> Option<RedisString> {
+        let name = unsafe { raw::RedisModule_GetClientNameById?(self.ctx, id) };
+        if name.is_null() {
+            None
+        } else {
+            Some(RedisString::from_redis_module_string(ptr::null_mut(), name))
+        }
+    }
+
     api!(
         [RedisModule_AddPostNotificationJob],
         /// When running inside a key space notification callback, it is dangerous and highly discouraged to perform any write
@@ -1044,6 +1702,32 @@ pub enum InfoContextBuilderFieldBottomLevelValue {
     U64(u64),
     /// A numeric value ([`f64`]).
     F64(f64),
+    /// Percentiles, as `(percentile, value)` pairs, rendered like the
+    /// `latencystats` section of `INFO`: `p50=1.003,p99=3.007,p99.9=5.023`.
+    /// Being rendered as a dictionary itself, it can not be used within an
+    /// info dictionary.
+    Histogram(Vec<(f64, f64)>),
+}
+
+impl InfoContextBuilderFieldBottomLevelValue {
+    /// Fail for the values which can not be used in the info dictionary
+    /// `dictionary`, as their rendering would corrupt it.
+    fn check_dictionary_value(&self, dictionary: &str, name: &str) -> RedisResult<()> {
+        match self {
+            Self::Histogram(_) => Err(RedisError::String(format!(
+                "Histogram '{name}' can not be used in the info dictionary '{dictionary}'"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn histogram_to_string(percentiles: &[(f64, f64)]) -> String {
+        percentiles
+            .iter()
+            .map(|(percentile, value)| format!("p{percentile}={value:.3}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
 }
 
 impl From<String> for InfoContextBuilderFieldBottomLevelValue {
@@ -1124,7 +1808,9 @@ pub struct InfoContextBuilderDictionaryBuilder<'a> {
 }
 
 impl<'a> InfoContextBuilderDictionaryBuilder<'a> {
-    /// Adds a field within this section.
+    /// Adds a field within this dictionary. Fails for a
+    /// [`InfoContextBuilderFieldBottomLevelValue::Histogram`], which can not
+    /// be used within a dictionary.
     pub fn field<F: Into<InfoContextBuilderFieldBottomLevelValue>>(
         mut self,
         name: &str,
@@ -1137,10 +1823,30 @@ impl<'a> InfoContextBuilderDictionaryBuilder<'a> {
             )));
         }
 
-        self.fields.push((name.to_owned(), value.into()).into());
+        let value = value.into();
+        value.check_dictionary_value(&self.name, name)?;
+        self.fields.push((name.to_owned(), value).into());
         Ok(self)
     }
 
+    /// Adds a dictionary within this dictionary. Redis dictionaries can
+    /// not be nested, so the fields of the sub-dictionary are added to this
+    /// dictionary with the name of the sub-dictionary as a prefix:
+    ///
+    /// ```no_run,ignore,
+    /// <dictionary name>:<key 1>=<value 1>,<sub name>.<sub key 1>=<sub value 1>
+    /// ```
+    pub fn add_dictionary(
+        self,
+        dictionary_name: &str,
+    ) -> InfoContextBuilderSubDictionaryBuilder<'a> {
+        InfoContextBuilderSubDictionaryBuilder {
+            dictionary_builder: self,
+            name: dictionary_name.to_owned(),
+            fields: InfoContextFieldBottomLevelData::default(),
+        }
+    }
+
     /// Builds the dictionary with the fields provided.
     pub fn build_dictionary(self) -> RedisResult<InfoContextBuilderSectionBuilder<'a>> {
         let name = self.name;
@@ -1155,6 +1861,53 @@ impl<'a> InfoContextBuilderDictionaryBuilder<'a> {
     }
 }
 
+/// Builds a dictionary within an info dictionary, see
+/// [`InfoContextBuilderDictionaryBuilder::add_dictionary`].
+#[derive(Debug)]
+pub struct InfoContextBuilderSubDictionaryBuilder<'a> {
+    /// The dictionary builder this sub-dictionary builder is for.
+    dictionary_builder: InfoContextBuilderDictionaryBuilder<'a>,
+    /// The name of the sub-dictionary, prefixing its fields.
+    name: String,
+    /// The fields this sub-dictionary contains.
+    fields: InfoContextFieldBottomLevelData,
+}
+
+impl<'a> InfoContextBuilderSubDictionaryBuilder<'a> {
+    /// Adds a field within this sub-dictionary, see
+    /// [`InfoContextBuilderDictionaryBuilder::field`].
+    pub fn field<F: Into<InfoContextBuilderFieldBottomLevelValue>>(
+        mut self,
+        name: &str,
+        value: F,
+    ) -> RedisResult<Self> {
+        if self.fields.iter().any(|k| k.0 .0 == name) {
+            return Err(RedisError::String(format!(
+                "Found duplicate key '{name}' in the info dictionary '{}'",
+                self.name
+            )));
+        }
+
+        let value = value.into();
+        value.check_dictionary_value(&self.name, name)?;
+        self.fields.push((name.to_owned(), value).into());
+        Ok(self)
+    }
+
+    /// Adds the fields of the sub-dictionary to the parent dictionary,
+    /// prefixed with the name of the sub-dictionary.
+    pub fn build_sub_dictionary(self) -> RedisResult<InfoContextBuilderDictionaryBuilder<'a>> {
+        let name = self.name;
+        self.fields
+            .0
+            .into_iter()
+            .try_fold(self.dictionary_builder, |builder, field| {
+                let (key, value) = field.0;
+                builder.field(&format!("{name}.{key}"), value)
+            })
+    }
+}
+
 /// Builds a section within the [`InfoContext`].
 #[derive(Debug)]
 pub struct InfoContextBuilderSectionBuilder<'a> {
@@ -1310,6 +2063,11 @@ impl<'a> InfoContextBuilder<'a> {
                 add_info_field_unsigned_long_long(self.context.ctx, key, *number)
             }
             BottomLevel::F64(number) => add_info_field_double(self.context.ctx, key, *number),
+            BottomLevel::Histogram(percentiles) => add_info_field_str(
+                self.context.ctx,
+                key,
+                &BottomLevel::histogram_to_string(percentiles),
+            ),
         }
         .into()
     }
@@ -1321,6 +2079,9 @@ impl<'a> InfoContextBuilder<'a> {
         fields.iter().try_for_each(|(key, value)| match value {
             TopLevel::Value(bottom_level) => self.add_bottom_level_field(key, bottom_level),
             TopLevel::Dictionary { name, fields } => {
+                fields
+                    .iter()
+                    .try_for_each(|f| f.0 .1.check_dictionary_value(name, &f.0 .0))?;
                 std::convert::Into::<RedisResult<()>>::into(add_info_begin_dict_field(
                     self.context.ctx,
                     name,
@@ -1392,6 +2153,26 @@ impl InfoContext {
         InfoContextBuilder::from(self)
     }
 
+    /// Builds the section `name` with `build`, only if the section was
+    /// requested. Redis does not tell the info handlers which sections were
+    /// requested, so asking for each section is the only way to skip the
+    /// work of building the sections which would be discarded.
+    ///
+    /// The section is added when this is called, so the sections of a
+    /// builder built afterwards follow it.
+    pub fn build_section_if_requested<'a, F>(&'a self, name: &'a str, build: F) -> RedisResult<()>
+    where
+        F: FnOnce(
+            InfoContextBuilderSectionBuilder<'a>,
+        ) -> RedisResult<InfoContextBuilderSectionBuilder<'a>>,
+    {
+        if add_info_section(self.ctx, Some(name)) != Status::Ok {
+            return Ok(());
+        }
+        let section = build(self.builder().add_section(name))?;
+        section.info_builder.add_top_level_fields(&section.fields)
+    }
+
     /// Returns a build result for the passed [`OneInfoSectionData`].
     pub fn build_one_section<T: Into<OneInfoSectionData>>(&self, data: T) -> RedisResult<()> {
         self.builder()
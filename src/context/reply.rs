@@ -0,0 +1,279 @@
+use std::cell::Cell;
+use std::os::raw::{c_char, c_long};
+
+use crate::redisvalue::{DoubleKey, DEFAULT_MAX_REPLY_DEPTH};
+use crate::{raw, Context, ContextFlags, RedisString, RedisValue};
+
+/// The protocol of the client being replied to, the RESP3 only reply types are
+/// downgraded here for RESP2 clients: maps are replied as flat arrays of keys
+/// and values, sets as arrays, booleans as integers of 1 or 0 and double keys
+/// as bulk strings. Doubles, big numbers and verbatim strings are downgraded
+/// by Redis itself.
+///
+/// The protocol is checked once per reply, not once per element, see
+/// [ReplyProtocol].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    Resp2,
+    Resp3,
+}
+
+/// The [Protocol] of a reply, checked the first time it is needed, so the
+/// replies which are the same for both protocols (like integers, strings and
+/// arrays of them) do not check it at all.
+pub(crate) struct ReplyProtocol<'ctx> {
+    ctx: &'ctx Context,
+    protocol: Cell<Option<Protocol>>,
+}
+
+impl<'ctx> ReplyProtocol<'ctx> {
+    pub(crate) fn new(ctx: &'ctx Context) -> Self {
+        Self {
+            ctx,
+            protocol: Cell::new(None),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Protocol {
+        match self.protocol.get() {
+            Some(protocol) => protocol,
+            None => {
+                let protocol = Protocol::of(self.ctx);
+                self.protocol.set(Some(protocol));
+                protocol
+            }
+        }
+    }
+}
+
+impl Protocol {
+    pub(crate) fn of(ctx: &Context) -> Self {
+        if ctx.get_flags().contains(ContextFlags::FLAGS_RESP3) {
+            Self::Resp3
+        } else {
+            Self::Resp2
+        }
+    }
+
+    /// Start a map of `len` key value pairs, `len` may be
+    /// [raw::REDISMODULE_POSTPONED_LEN].
+    pub(crate) fn reply_with_map(self, ctx: *mut raw::RedisModuleCtx, len: c_long) -> raw::Status {
+        match self {
+            Self::Resp3 => raw::reply_with_map(ctx, len),
+            Self::Resp2 => raw::reply_with_array(ctx, raw::flat_map_len(len)),
+        }
+    }
+
+    pub(crate) fn reply_set_map_length(self, ctx: *mut raw::RedisModuleCtx, len: c_long) {
+        match self {
+            Self::Resp3 => raw::reply_set_map_length(ctx, len),
+            Self::Resp2 => raw::reply_set_array_length(ctx, len * 2),
+        }
+    }
+
+    /// Start a set of `len` elements, `len` may be
+    /// [raw::REDISMODULE_POSTPONED_LEN].
+    pub(crate) fn reply_with_set(self, ctx: *mut raw::RedisModuleCtx, len: c_long) -> raw::Status {
+        match self {
+            Self::Resp3 => raw::reply_with_set(ctx, len),
+            Self::Resp2 => raw::reply_with_array(ctx, len),
+        }
+    }
+
+    pub(crate) fn reply_set_set_length(self, ctx: *mut raw::RedisModuleCtx, len: c_long) {
+        match self {
+            Self::Resp3 => raw::reply_set_set_length(ctx, len),
+            Self::Resp2 => raw::reply_set_array_length(ctx, len),
+        }
+    }
+
+    pub(crate) fn reply_with_bool(self, ctx: *mut raw::RedisModuleCtx, b: bool) -> raw::Status {
+        match self {
+            Self::Resp3 => raw::reply_with_bool(ctx, b.into()),
+            Self::Resp2 => raw::reply_with_long_long(ctx, b.into()),
+        }
+    }
+
+    /// Reply with a double used as a map key or a set element, RESP2 clients
+    /// get it formatted as a bulk string.
+    pub(crate) fn reply_with_double_key(
+        self,
+        ctx: *mut raw::RedisModuleCtx,
+        d: &DoubleKey,
+    ) -> raw::Status {
+        match self {
+            Self::Resp3 => raw::reply_with_double(ctx, d.value()),
+            Self::Resp2 => {
+                let s = d.to_resp2_string();
+                raw::reply_with_string_buffer(ctx, s.as_ptr().cast::<c_char>(), s.len())
+            }
+        }
+    }
+}
+
+/// Replies to the client element by element, calling the `RedisModule_ReplyWith*`
+/// functions directly, so large replies do not have to be built as a [RedisValue]
+/// first. Returned by [Context::reply_builder].
+///
+/// Aggregates are started with their number of elements (for maps, the number of
+/// key value pairs), followed by the elements themselves. When the number is not
+/// known up front, start the aggregate with one of the `begin_*_postponed` methods
+/// and set it once all the elements were added. Postponed lengths are set in the
+/// reverse order of the aggregates, the last postponed aggregate started is the
+/// one whose length is set.
+///
+/// The command should return [RedisValue::NoReply] once the reply is complete:
+///
+/// ```ignore
+/// fn evens(ctx: &Context, _args: Vec<RedisString>) -> RedisResult {
+///     let reply = ctx.reply_builder();
+///     reply.begin_array_postponed();
+///     let mut len = 0;
+///     for i in (0..1_000_000).filter(|i| i % 2 == 0) {
+///         reply.push_long(i);
+///         len += 1;
+///     }
+///     reply.set_array_length(len);
+///     Ok(RedisValue::NoReply)
+/// }
+/// ```
+pub struct ReplyBuilder<'ctx> {
+    ctx: &'ctx Context,
+    protocol: ReplyProtocol<'ctx>,
+}
+
+impl<'ctx> ReplyBuilder<'ctx> {
+    fn new(ctx: &'ctx Context) -> Self {
+        Self {
+            ctx,
+            protocol: ReplyProtocol::new(ctx),
+        }
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn begin_array(&self, len: usize) -> raw::Status {
+        raw::reply_with_array(self.ctx.ctx, len as c_long)
+    }
+
+    /// Start a map of `len` key value pairs. RESP2 clients get a flat array
+    /// of keys and values.
+    #[allow(clippy::must_use_candidate)]
+    pub fn begin_map(&self, len: usize) -> raw::Status {
+        self.protocol
+            .get()
+            .reply_with_map(self.ctx.ctx, len as c_long)
+    }
+
+    /// Start a set of `len` elements. RESP2 clients get an array.
+    #[allow(clippy::must_use_candidate)]
+    pub fn begin_set(&self, len: usize) -> raw::Status {
+        self.protocol
+            .get()
+            .reply_with_set(self.ctx.ctx, len as c_long)
+    }
+
+    /// Start an array whose length is set later with
+    /// [ReplyBuilder::set_array_length].
+    #[allow(clippy::must_use_candidate)]
+    pub fn begin_array_postponed(&self) -> raw::Status {
+        raw::reply_with_array(self.ctx.ctx, raw::REDISMODULE_POSTPONED_LEN as c_long)
+    }
+
+    pub fn set_array_length(&self, len: usize) {
+        raw::reply_set_array_length(self.ctx.ctx, len as c_long);
+    }
+
+    /// Start a map whose number of key value pairs is set later with
+    /// [ReplyBuilder::set_map_length].
+    #[allow(clippy::must_use_candidate)]
+    pub fn begin_map_postponed(&self) -> raw::Status {
+        self.protocol
+            .get()
+            .reply_with_map(self.ctx.ctx, raw::REDISMODULE_POSTPONED_LEN as c_long)
+    }
+
+    pub fn set_map_length(&self, len: usize) {
+        self.protocol
+            .get()
+            .reply_set_map_length(self.ctx.ctx, len as c_long);
+    }
+
+    /// Start a set whose length is set later with [ReplyBuilder::set_set_length].
+    #[allow(clippy::must_use_candidate)]
+    pub fn begin_set_postponed(&self) -> raw::Status {
+        self.protocol
+            .get()
+            .reply_with_set(self.ctx.ctx, raw::REDISMODULE_POSTPONED_LEN as c_long)
+    }
+
+    pub fn set_set_length(&self, len: usize) {
+        self.protocol
+            .get()
+            .reply_set_set_length(self.ctx.ctx, len as c_long);
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_long(&self, v: i64) -> raw::Status {
+        raw::reply_with_long_long(self.ctx.ctx, v)
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_double(&self, v: f64) -> raw::Status {
+        raw::reply_with_double(self.ctx.ctx, v)
+    }
+
+    /// Push a boolean, RESP2 clients get an integer of 1 or 0 instead.
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_bool(&self, v: bool) -> raw::Status {
+        self.protocol.get().reply_with_bool(self.ctx.ctx, v)
+    }
+
+    /// Push a bulk string.
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_string(&self, s: &str) -> raw::Status {
+        self.push_bytes(s.as_bytes())
+    }
+
+    /// Push a bulk string, which does not have to be valid UTF-8.
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_bytes(&self, s: &[u8]) -> raw::Status {
+        raw::reply_with_string_buffer(self.ctx.ctx, s.as_ptr().cast::<c_char>(), s.len())
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_redis_string(&self, s: &RedisString) -> raw::Status {
+        raw::reply_with_string(self.ctx.ctx, s.inner)
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_simple_string(&self, s: &str) -> raw::Status {
+        self.ctx.reply_simple_string(s)
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_null(&self) -> raw::Status {
+        raw::reply_with_null(self.ctx.ctx)
+    }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_error(&self, s: &str) -> raw::Status {
+        self.ctx.reply_error_string(s)
+    }
+
+    /// Push a whole value, for example a small aggregate within a large one.
+    /// Same as [Context::reply], values nested deeper than
+    /// [DEFAULT_MAX_REPLY_DEPTH] aggregates are replaced with an error.
+    #[allow(clippy::must_use_candidate)]
+    pub fn push_value(&self, v: RedisValue) -> raw::Status {
+        self.ctx
+            .reply_value(Ok(v), DEFAULT_MAX_REPLY_DEPTH, &self.protocol)
+    }
+}
+
+impl Context {
+    /// Return a [ReplyBuilder] replying to the client of the current command
+    /// element by element.
+    pub fn reply_builder(&self) -> ReplyBuilder<'_> {
+        ReplyBuilder::new(self)
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::BTreeSet;
 use std::ffi::CStr;
+use std::sync::Mutex;
 
 use crate::{context::Context, RedisError};
 use crate::{raw, InfoContext, RedisResult};
@@ -31,6 +33,42 @@ pub enum ModuleChangeSubevent {
     Unloaded,
 }
 
+/// The server events handled by the event handler macros (for example
+/// `flush_event_handler`), see [Context::unsubscribe_server_event].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum ServerEvent {
+    RoleChanged,
+    Loading,
+    Flush,
+    ModuleChange,
+    ConfigChange,
+    Cron,
+}
+
+impl ServerEvent {
+    fn id(self) -> u64 {
+        match self {
+            ServerEvent::RoleChanged => raw::REDISMODULE_EVENT_REPLICATION_ROLE_CHANGED,
+            ServerEvent::Loading => raw::REDISMODULE_EVENT_LOADING,
+            ServerEvent::Flush => raw::REDISMODULE_EVENT_FLUSHDB,
+            ServerEvent::ModuleChange => raw::REDISMODULE_EVENT_MODULE_CHANGE,
+            ServerEvent::ConfigChange => raw::REDISMODULE_EVENT_CONFIG,
+            ServerEvent::Cron => raw::REDISMODULE_EVENT_CRON_LOOP,
+        }
+    }
+
+    fn callback(self) -> raw::RedisModuleEventCallback {
+        match self {
+            ServerEvent::RoleChanged => Some(role_changed_callback),
+            ServerEvent::Loading => Some(loading_event_callback),
+            ServerEvent::Flush => Some(flush_event_callback),
+            ServerEvent::ModuleChange => Some(module_change_event_callback),
+            ServerEvent::ConfigChange => Some(config_change_event_callback),
+            ServerEvent::Cron => Some(cron_callback),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum ServerEventHandler {
     RuleChanged(fn(&Context, ServerRole)),
@@ -182,24 +220,42 @@ fn register_single_server_event_type<T>(
     inner_callback: raw::RedisModuleEventCallback,
 ) -> Result<(), RedisError> {
     if !callbacks.is_empty() {
-        let res = unsafe {
-            raw::RedisModule_SubscribeToServerEvent.unwrap()(
-                ctx.ctx,
-                raw::RedisModuleEvent {
-                    id: server_event,
-                    dataver: 1,
-                },
-                inner_callback,
-            )
-        };
-        if res != raw::REDISMODULE_OK as i32 {
-            return Err(RedisError::Str("Failed subscribing to server event"));
-        }
+        subscribe_to_server_event(ctx, server_event, inner_callback)?;
     }
 
     Ok(())
 }
 
+/// The ids of the events currently subscribed to, so an event which is not
+/// subscribed is never unsubscribed again.
+static SUBSCRIBED_SERVER_EVENTS: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+fn subscribe_to_server_event(
+    ctx: &Context,
+    server_event: u64,
+    inner_callback: raw::RedisModuleEventCallback,
+) -> Result<(), RedisError> {
+    let event = raw::RedisModuleEvent {
+        id: server_event,
+        dataver: 1,
+    };
+    let mut subscribed = SUBSCRIBED_SERVER_EVENTS.lock().unwrap();
+    if inner_callback.is_none() && !subscribed.contains(&server_event) {
+        return Ok(());
+    }
+    match raw::subscribe_to_server_event(ctx.ctx, event, inner_callback) {
+        raw::Status::Ok => {
+            if inner_callback.is_some() {
+                subscribed.insert(server_event);
+            } else {
+                subscribed.remove(&server_event);
+            }
+            Ok(())
+        }
+        raw::Status::Err => Err(RedisError::Str("Failed subscribing to server event")),
+    }
+}
+
 pub fn register_server_events(ctx: &Context) -> Result<(), RedisError> {
     register_single_server_event_type(
         ctx,
@@ -239,3 +295,19 @@ pub fn register_server_events(ctx: &Context) -> Result<(), RedisError> {
     )?;
     Ok(())
 }
+
+impl Context {
+    /// Stop calling the handlers of `event`, for example when the feature
+    /// handling it is disabled. Use [Context::subscribe_server_event] to
+    /// start calling them again. Does nothing if `event` is not subscribed.
+    pub fn unsubscribe_server_event(&self, event: ServerEvent) -> Result<(), RedisError> {
+        subscribe_to_server_event(self, event.id(), None)
+    }
+
+    /// Subscribe again to `event` after [Context::unsubscribe_server_event].
+    /// The handlers of all the events are subscribed when the module is
+    /// loaded, so this is only needed after unsubscribing.
+    pub fn subscribe_server_event(&self, event: ServerEvent) -> Result<(), RedisError> {
+        subscribe_to_server_event(self, event.id(), event.callback())
+    }
+}
@@ -85,6 +85,56 @@ impl<T: Default> Default for RedisGILGuard<T> {
 unsafe impl<T> Sync for RedisGILGuard<T> {}
 unsafe impl<T> Send for RedisGILGuard<T> {}
 
+/// A lazily initialized value protected by the Redis GIL.
+/// The value is created on the first call to [GilCell::get_or_init]
+/// and never changes afterwards, so it can be declared as a plain
+/// `static` without `static mut` or `lazy_static`.
+/// For example, look at examples/threads.rs
+pub struct GilCell<T> {
+    obj: UnsafeCell<Option<T>>,
+}
+
+impl<T> GilCell<T> {
+    pub const fn new() -> GilCell<T> {
+        GilCell {
+            obj: UnsafeCell::new(None),
+        }
+    }
+
+    /// Return the value if it was already initialized.
+    pub fn get<'a, G: RedisLockIndicator>(&'a self, _context: &'a G) -> Option<&'a T> {
+        unsafe { (*self.obj.get()).as_ref() }
+    }
+
+    /// Return the value, initializing it with `init` on the first call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `init` recursively initializes the same cell.
+    pub fn get_or_init<'a, G: RedisLockIndicator, F: FnOnce() -> T>(
+        &'a self,
+        context: &'a G,
+        init: F,
+    ) -> &'a T {
+        if let Some(val) = self.get(context) {
+            return val;
+        }
+        let val = init();
+        let slot = unsafe { &mut *self.obj.get() };
+        assert!(slot.is_none(), "GilCell was initialized reentrantly");
+        slot.insert(val)
+    }
+}
+
+impl<T> Default for GilCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<T: Send> Sync for GilCell<T> {}
+unsafe impl<T: Send> Send for GilCell<T> {}
+
 pub struct ContextGuard {
     ctx: Context,
 }
@@ -0,0 +1,106 @@
+use std::cell::UnsafeCell;
+use std::os::raw::c_void;
+
+use redis_module_macros_internals::api;
+
+use crate::{raw, Context, RedisLockIndicator, Status};
+
+/// Collects the writes requested from key space notification callbacks and
+/// performs all of them in a single post notification job (see
+/// [Context::add_post_notification_job]).
+///
+/// Scheduling a job per notification means one job, and usually one more
+/// round of notifications, for every key touched by a command. With a
+/// [WriteBehindBuffer] a command touching many keys (`MSET`, `DEL k1 k2 ...`,
+/// a `MULTI`/`EXEC` block) ends up with a single call to `flush`, which gets
+/// all the pending writes at once. This is useful, for example, for keeping
+/// a secondary index up to date.
+///
+/// The buffer is meant to be declared as a `static` and is only accessed
+/// while the Redis GIL is held:
+///
+/// ```ignore
+/// static INDEX_UPDATES: WriteBehindBuffer<Vec<u8>> = WriteBehindBuffer::new(update_index);
+///
+/// fn update_index(ctx: &Context, keys: Vec<Vec<u8>>) {
+///     // write all the keys to the index at once
+/// }
+///
+/// fn on_event(ctx: &Context, _event_type: NotifyEvent, _event: &str, key: &[u8]) {
+///     INDEX_UPDATES.push(ctx, key.to_vec());
+/// }
+/// ```
+pub struct WriteBehindBuffer<T> {
+    pending: UnsafeCell<Vec<T>>,
+    flush: fn(&Context, Vec<T>),
+}
+
+impl<T> WriteBehindBuffer<T> {
+    /// Create an empty buffer, `flush` is called with all the pending writes
+    /// from a post notification job.
+    pub const fn new(flush: fn(&Context, Vec<T>)) -> WriteBehindBuffer<T> {
+        WriteBehindBuffer {
+            pending: UnsafeCell::new(Vec::new()),
+            flush,
+        }
+    }
+
+    /// Return the number of writes waiting for the next flush.
+    pub fn len<G: RedisLockIndicator>(&self, _context: &G) -> usize {
+        unsafe { (*self.pending.get()).len() }
+    }
+
+    pub fn is_empty<G: RedisLockIndicator>(&self, context: &G) -> bool {
+        self.len(context) == 0
+    }
+
+    api!(
+        [RedisModule_AddPostNotificationJob],
+        /// Add a write to the buffer. The first write added after a flush
+        /// schedules the post notification job that will flush the buffer,
+        /// the following ones are only accumulated.
+        ///
+        /// Returns [Status::Err] if the job could not be scheduled (for example
+        /// while loading or on a read only replica), in which case the write
+        /// is dropped.
+        pub fn push(&'static self, ctx: &Context, write: T) -> Status {
+            let pending = unsafe { &mut *self.pending.get() };
+            if !pending.is_empty() {
+                pending.push(write);
+                return Status::Ok;
+            }
+
+            let res: Status = unsafe {
+                RedisModule_AddPostNotificationJob(
+                    ctx.ctx,
+                    Some(write_behind_flush_job::<T>),
+                    self as *const WriteBehindBuffer<T> as *mut c_void,
+                    None,
+                )
+            }
+            .into();
+            if res == Status::Ok {
+                pending.push(write);
+            }
+            res
+        }
+    );
+
+    fn flush(&self, ctx: &Context) {
+        // Take the writes before calling `flush`, so writes pushed by the
+        // notifications it triggers go to a new batch with its own job.
+        let writes = std::mem::take(unsafe { &mut *self.pending.get() });
+        if !writes.is_empty() {
+            (self.flush)(ctx, writes);
+        }
+    }
+}
+
+unsafe impl<T: Send> Sync for WriteBehindBuffer<T> {}
+unsafe impl<T: Send> Send for WriteBehindBuffer<T> {}
+
+extern "C" fn write_behind_flush_job<T>(ctx: *mut raw::RedisModuleCtx, pd: *mut c_void) {
+    let buffer = unsafe { &*(pd as *const WriteBehindBuffer<T>) };
+    let ctx = Context::new(ctx);
+    buffer.flush(&ctx);
+}
@@ -0,0 +1,141 @@
+//! Helpers for commands that expose paginated iteration the same way the
+//! native `SCAN` command does: the client passes a cursor (`0` to start),
+//! and the command replies with `[next_cursor, [items...]]`, where a
+//! `next_cursor` of `0` means the iteration is complete.
+//!
+//! ```ignore
+//! fn my_scan(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+//!     let mut args = args.into_iter().skip(1);
+//!     let cursor = Cursor::try_from(&args.next_arg()?)?;
+//!     let keys = KeysCursor::from_token(cursor.position());
+//!
+//!     let mut items = Vec::new();
+//!     keys.try_scan(ctx, &|_ctx, key_name, _key| items.push(key_name))?;
+//!
+//!     let next = keys.token().unwrap_or_default();
+//!     Ok(CursorReply::new(Cursor::new(next)).items(items).into())
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::{RedisError, RedisString, RedisValue};
+
+/// An opaque position in an iteration, sent to the client as an unsigned
+/// decimal number, same as the cursors of the native `SCAN` command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Cursor(u64);
+
+impl Cursor {
+    /// The cursor starting a new iteration, also returned once the
+    /// iteration is complete.
+    pub const START: Cursor = Cursor(0);
+
+    pub const fn new(position: u64) -> Self {
+        Self(position)
+    }
+
+    pub const fn position(&self) -> u64 {
+        self.0
+    }
+
+    /// Return `true` for [Cursor::START], when returned to the client it
+    /// means there are no more items.
+    pub const fn is_done(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Encode the cursor as it is sent to the client.
+    pub fn encode(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Decode a cursor sent by the client.
+    pub fn decode(encoded: &[u8]) -> Result<Self, RedisError> {
+        std::str::from_utf8(encoded)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Self)
+            .ok_or(RedisError::Str("ERR invalid cursor"))
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for Cursor {
+    fn from(position: u64) -> Self {
+        Self(position)
+    }
+}
+
+impl TryFrom<&RedisString> for Cursor {
+    type Error = RedisError;
+
+    fn try_from(s: &RedisString) -> Result<Self, Self::Error> {
+        Self::decode(s.as_slice())
+    }
+}
+
+/// Builds the standard two elements reply of a `SCAN` like command,
+/// `[next_cursor, [items...]]`.
+#[derive(Debug, Clone)]
+pub struct CursorReply {
+    next: Cursor,
+    items: Vec<RedisValue>,
+}
+
+impl CursorReply {
+    /// Create a reply with no items, `next` is the cursor the client should
+    /// send to continue the iteration ([Cursor::START] if it is complete).
+    pub fn new(next: Cursor) -> Self {
+        Self {
+            next,
+            items: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn item<T: Into<RedisValue>>(mut self, item: T) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    #[must_use]
+    pub fn items<T: Into<RedisValue>, I: IntoIterator<Item = T>>(mut self, items: I) -> Self {
+        self.items.extend(items.into_iter().map(Into::into));
+        self
+    }
+}
+
+impl From<CursorReply> for RedisValue {
+    fn from(reply: CursorReply) -> Self {
+        RedisValue::Array(vec![
+            RedisValue::BulkString(reply.next.encode()),
+            RedisValue::Array(reply.items),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+
+    #[test]
+    fn encode_decode() {
+        let cursor = Cursor::new(1234);
+        assert_eq!(cursor.encode(), "1234");
+        assert_eq!(Cursor::decode(cursor.encode().as_bytes()).unwrap(), cursor);
+        assert!(Cursor::decode(b"0").unwrap().is_done());
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert!(Cursor::decode(b"").is_err());
+        assert!(Cursor::decode(b"-1").is_err());
+        assert!(Cursor::decode(b"abc").is_err());
+    }
+}
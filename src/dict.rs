@@ -0,0 +1,245 @@
+use crate::raw;
+use crate::RedisString;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+/// The direction in which a [RedisDictIter] walks the keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DictDirection {
+    /// Ascending key order, starting from the first key `>=` the seek key.
+    Forward,
+    /// Descending key order, starting from the last key `<=` the seek key.
+    Backward,
+}
+
+/// An ordered dictionary with [RedisString] keys, backed by the Redis
+/// `RedisModuleDict` (a radix tree allocated with the Redis allocator).
+///
+/// Keys are ordered lexicographically by their bytes. Values are boxed and
+/// owned by the dictionary, they are dropped together with it.
+///
+/// The dictionary is not thread safe, it should only be accessed while
+/// the Redis GIL is held (for example by wrapping it in a [crate::RedisGILGuard]).
+pub struct RedisDict<V> {
+    inner: *mut raw::RedisModuleDict,
+    _values: PhantomData<Box<V>>,
+}
+
+impl<V> RedisDict<V> {
+    /// Create an empty dictionary. The dictionary is not attached to any
+    /// context, so it can outlive the command that created it.
+    #[must_use]
+    pub fn new() -> Self {
+        let inner = unsafe { raw::RedisModule_CreateDict.unwrap()(ptr::null_mut()) };
+        Self {
+            inner,
+            _values: PhantomData,
+        }
+    }
+
+    /// Return the number of entries in the dictionary.
+    pub fn len(&self) -> usize {
+        unsafe { raw::RedisModule_DictSize.unwrap()(self.inner) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Set `key` to `value`, returning the previous value if the key
+    /// already existed.
+    pub fn set(&mut self, key: &RedisString, value: V) -> Option<V> {
+        let old = self.take_value(key);
+        let value = Box::into_raw(Box::new(value)).cast::<c_void>();
+        unsafe { raw::RedisModule_DictReplace.unwrap()(self.inner, key.inner, value) };
+        old
+    }
+
+    pub fn get(&self, key: &RedisString) -> Option<&V> {
+        self.get_ptr(key).map(|v| unsafe { &*v })
+    }
+
+    pub fn get_mut(&mut self, key: &RedisString) -> Option<&mut V> {
+        self.get_ptr(key).map(|v| unsafe { &mut *v })
+    }
+
+    pub fn contains_key(&self, key: &RedisString) -> bool {
+        self.get_ptr(key).is_some()
+    }
+
+    /// Remove `key` from the dictionary, returning its value if it existed.
+    pub fn del(&mut self, key: &RedisString) -> Option<V> {
+        let mut old: *mut c_void = ptr::null_mut();
+        let res: raw::Status = unsafe {
+            raw::RedisModule_DictDel.unwrap()(
+                self.inner,
+                key.inner,
+                (&mut old as *mut *mut c_void).cast::<c_void>(),
+            )
+        }
+        .into();
+        match res {
+            raw::Status::Ok => Some(*unsafe { Box::from_raw(old.cast::<V>()) }),
+            raw::Status::Err => None,
+        }
+    }
+
+    /// Iterate over all the entries, in ascending key order.
+    pub fn iter(&self) -> RedisDictIter<'_, V> {
+        let di = unsafe {
+            raw::RedisModule_DictIteratorStartC.unwrap()(
+                self.inner,
+                "^\0".as_ptr().cast::<c_char>(),
+                ptr::null_mut(),
+                0,
+            )
+        };
+        RedisDictIter::new(di, DictDirection::Forward)
+    }
+
+    /// Iterate over the entries starting at `key` (which does not need to
+    /// exist in the dictionary), walking in the given `direction`.
+    pub fn iter_from(&self, key: &RedisString, direction: DictDirection) -> RedisDictIter<'_, V> {
+        let op = match direction {
+            DictDirection::Forward => ">=\0",
+            DictDirection::Backward => "<=\0",
+        };
+        let di = unsafe {
+            raw::RedisModule_DictIteratorStart.unwrap()(
+                self.inner,
+                op.as_ptr().cast::<c_char>(),
+                key.inner,
+            )
+        };
+        RedisDictIter::new(di, direction)
+    }
+
+    /// Iterate, in ascending order, over the entries whose key starts with `prefix`.
+    pub fn iter_prefix<'dict>(
+        &'dict self,
+        prefix: &'dict RedisString,
+    ) -> impl Iterator<Item = (RedisString, &'dict V)> + 'dict {
+        self.iter_from(prefix, DictDirection::Forward)
+            .take_while(move |(key, _)| key.as_slice().starts_with(prefix.as_slice()))
+    }
+
+    /// Return an estimate of the memory used by the dictionary: the overhead
+    /// of its internal structure, as estimated by `RedisModule_MallocSizeDict`
+    /// (which does not count the keys), plus the boxed values. Memory owned
+    /// by the values themselves is not counted, use
+    /// [RedisDict::memory_usage_with] to include it.
+    ///
+    /// This is meant for the `mem_usage` callback of data types embedding a
+    /// dictionary (used by `MEMORY USAGE` and eviction). On servers without
+    /// `RedisModule_MallocSizeDict` only the values are counted.
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage_with(|_| 0)
+    }
+
+    /// Same as [RedisDict::memory_usage], adding `value_usage` for each value,
+    /// which should return the memory owned by the value outside of its box.
+    pub fn memory_usage_with<F: Fn(&V) -> usize>(&self, value_usage: F) -> usize {
+        let dict_size =
+            unsafe { raw::RedisModule_MallocSizeDict }.map_or(0, |f| unsafe { f(self.inner) });
+        let values_size: usize = self
+            .iter()
+            .map(|(_, v)| std::mem::size_of::<V>() + value_usage(v))
+            .sum();
+        dict_size + values_size
+    }
+
+    fn get_ptr(&self, key: &RedisString) -> Option<*mut V> {
+        let mut nokey: c_int = 0;
+        let value = unsafe { raw::RedisModule_DictGet.unwrap()(self.inner, key.inner, &mut nokey) };
+        if nokey != 0 {
+            None
+        } else {
+            Some(value.cast::<V>())
+        }
+    }
+
+    fn take_value(&mut self, key: &RedisString) -> Option<V> {
+        self.get_ptr(key).map(|v| *unsafe { Box::from_raw(v) })
+    }
+}
+
+impl<V> Default for RedisDict<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Drop for RedisDict<V> {
+    fn drop(&mut self) {
+        unsafe {
+            let di = raw::RedisModule_DictIteratorStartC.unwrap()(
+                self.inner,
+                "^\0".as_ptr().cast::<c_char>(),
+                ptr::null_mut(),
+                0,
+            );
+            let mut value: *mut c_void = ptr::null_mut();
+            while !raw::RedisModule_DictNextC.unwrap()(di, ptr::null_mut(), &mut value).is_null() {
+                drop(Box::from_raw(value.cast::<V>()));
+            }
+            raw::RedisModule_DictIteratorStop.unwrap()(di);
+            raw::RedisModule_FreeDict.unwrap()(ptr::null_mut(), self.inner);
+        }
+    }
+}
+
+impl<'dict, V> IntoIterator for &'dict RedisDict<V> {
+    type Item = (RedisString, &'dict V);
+    type IntoIter = RedisDictIter<'dict, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [RedisDict], see [RedisDict::iter]
+/// and [RedisDict::iter_from].
+pub struct RedisDictIter<'dict, V> {
+    inner: *mut raw::RedisModuleDictIter,
+    direction: DictDirection,
+    _dict: PhantomData<&'dict RedisDict<V>>,
+}
+
+impl<'dict, V> RedisDictIter<'dict, V> {
+    fn new(inner: *mut raw::RedisModuleDictIter, direction: DictDirection) -> Self {
+        Self {
+            inner,
+            direction,
+            _dict: PhantomData,
+        }
+    }
+}
+
+impl<'dict, V> Iterator for RedisDictIter<'dict, V> {
+    type Item = (RedisString, &'dict V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut value: *mut c_void = ptr::null_mut();
+        let key = unsafe {
+            let step = match self.direction {
+                DictDirection::Forward => raw::RedisModule_DictNext,
+                DictDirection::Backward => raw::RedisModule_DictPrev,
+            };
+            step.unwrap()(ptr::null_mut(), self.inner, &mut value)
+        };
+        if key.is_null() {
+            return None;
+        }
+        Some((
+            RedisString::from_redis_module_string(ptr::null_mut(), key),
+            unsafe { &*value.cast::<V>() },
+        ))
+    }
+}
+
+impl<'dict, V> Drop for RedisDictIter<'dict, V> {
+    fn drop(&mut self) {
+        unsafe { raw::RedisModule_DictIteratorStop.unwrap()(self.inner) };
+    }
+}
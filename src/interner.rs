@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::ptr;
+
+use crate::{raw, RedisString};
+
+/// Deduplicates [RedisString] values by their content, so identical values
+/// stored by a module (for example the values of a column store like data
+/// type) share a single allocation.
+///
+/// The interner holds one copy of every distinct value, and every call to
+/// [RedisStringInterner::intern] returns a new reference to it. The copy is
+/// freed once it was removed from the interner and all of its references were
+/// dropped.
+///
+/// The interner is not thread safe, it should only be accessed while the Redis
+/// GIL is held (for example by wrapping it in a [crate::RedisGILGuard]).
+#[derive(Debug, Default)]
+pub struct RedisStringInterner {
+    strings: HashSet<RedisString>,
+}
+
+impl RedisStringInterner {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a reference to the interned value with the content of `s`,
+    /// copying `s` into the interner if there is none yet.
+    pub fn intern(&mut self, s: &RedisString) -> SharedRedisString {
+        if let Some(interned) = self.strings.get(s) {
+            return SharedRedisString::retain(interned);
+        }
+        let interned = s.clone();
+        let shared = SharedRedisString::retain(&interned);
+        self.strings.insert(interned);
+        shared
+    }
+
+    /// Return `true` if a value with the content of `s` is interned.
+    pub fn contains(&self, s: &RedisString) -> bool {
+        self.strings.contains(s)
+    }
+
+    /// Remove the value with the content of `s`, the references already
+    /// returned for it stay valid. Returns `false` if it was not interned.
+    pub fn remove(&mut self, s: &RedisString) -> bool {
+        self.strings.remove(s)
+    }
+
+    /// Return the number of distinct values.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Remove all the values, the references already returned stay valid.
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+}
+
+/// A reference to a value held by a [RedisStringInterner]. All the
+/// references to the same value point to the same `RedisModuleString`,
+/// which is reference counted by Redis, so they must only be created and
+/// dropped while the Redis GIL is held.
+///
+/// The value is only accessible for reading, as Redis does not allow
+/// modifying a string with more than one reference.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct SharedRedisString {
+    inner: RedisString,
+}
+
+impl SharedRedisString {
+    fn retain(s: &RedisString) -> Self {
+        raw::string_retain_string(ptr::null_mut(), s.inner);
+        Self {
+            inner: RedisString::from_redis_module_string(ptr::null_mut(), s.inner),
+        }
+    }
+
+    /// Return `true` if both references point to the same value.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::eq(self.inner.inner, other.inner.inner)
+    }
+}
+
+impl Deref for SharedRedisString {
+    type Target = RedisString;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
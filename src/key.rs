@@ -7,18 +7,22 @@ use std::ptr::NonNull;
 use std::time::Duration;
 
 use libc::size_t;
-use std::os::raw::c_int;
+use std::os::raw::{c_int, c_long};
 
 use raw::KeyType;
 
+use crate::context::call_reply::{CallReply, CallResult};
+use crate::context::StrCallArgs;
 use crate::native_types::RedisType;
 use crate::raw;
 use crate::redismodule::REDIS_OK;
 pub use crate::redisraw::bindings::*;
-use crate::stream::StreamIterator;
+use crate::stream::{StreamAddOption, StreamId, StreamIterator};
+use crate::zset::{ZaddFlags, ZsetRangeIterator};
 use crate::RedisError;
 use crate::RedisResult;
 use crate::RedisString;
+use crate::{CallOptionsBuilder, Context};
 use bitflags::bitflags;
 
 /// `RedisKey` is an abstraction over a Redis key that allows readonly
@@ -50,6 +54,19 @@ bitflags! {
     }
 }
 
+/// A copy of a key that can be used to recreate it, for example on another shard.
+/// See [RedisKey::dump_with_metadata] and [Context::restore_key_snapshot].
+#[derive(Debug)]
+pub struct KeySnapshot {
+    /// The type of the key at the time the snapshot was taken.
+    pub key_type: KeyType,
+    /// The key value, serialized using `DUMP`.
+    pub payload: Vec<u8>,
+    /// The absolute unix time in milliseconds at which the key expires,
+    /// `None` if the key has no expire.
+    pub expire_at: Option<i64>,
+}
+
 #[derive(Debug)]
 pub struct RedisKey {
     pub(crate) ctx: *mut raw::RedisModuleCtx,
@@ -111,6 +128,137 @@ impl RedisKey {
         unsafe { raw::RedisModule_KeyType.unwrap()(self.key_inner) }.into()
     }
 
+    /// Invoke a command on this key, the key name is passed as the first
+    /// argument, followed by `args`, so `key.call("HINCRBY", &["field", "1"])`
+    /// runs `HINCRBY <key name> field 1`.
+    ///
+    /// Redis does not allow calling a command on an opened key, so it still
+    /// looks up the key by its name, but the name is reused from the key
+    /// instead of creating a new string on every call. See [Context::call].
+    /// Returns an error if the key does not exist, as it has no name then.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromModuleKey` is missing in redismodule.h
+    pub fn call<'a, T: Into<StrCallArgs<'a>>>(&self, command: &str, args: T) -> RedisResult {
+        Context::new(self.ctx).call_on_key(command, self.key_inner, args)
+    }
+
+    /// Return the element at `index` of the list stored at this key, negative
+    /// indexes count from the tail (`-1` is the last element). Returns `None`
+    /// if the index is out of range or the key is not a list.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ListGet` is missing in redismodule.h
+    #[must_use]
+    pub fn list_get(&self, index: c_long) -> Option<RedisString> {
+        list_get(self.ctx, self.key_inner, index)
+    }
+
+    /// Return an iterator over the elements of the list stored at this key,
+    /// from the head to the tail, without removing them.
+    #[must_use]
+    pub fn list_iter(&self) -> ListIterator<'_> {
+        ListIterator::new(self.ctx, self.key_inner)
+    }
+
+    /// Return the score of `member` in the sorted set stored at this key, or
+    /// `None` if it is not a member or the key is not a sorted set.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetScore` is missing in redismodule.h
+    #[must_use]
+    pub fn zset_score(&self, member: &RedisString) -> Option<f64> {
+        zset_score(self.key_inner, member)
+    }
+
+    /// Return an iterator over the members of the sorted set stored at this
+    /// key whose score is between `min` and `max` (inclusive), from the
+    /// lowest score to the highest, along with their scores.
+    ///
+    /// Redis keeps a single range per key, so the key is borrowed mutably
+    /// and a second range can not be started while the first one is alive:
+    ///
+    /// ```compile_fail
+    /// # use redis_module::{Context, RedisError, RedisString};
+    /// # fn f(ctx: &Context, key_name: &RedisString) -> Result<(), RedisError> {
+    /// let mut key = ctx.open_key(key_name);
+    /// let low = key.zset_range_by_score(0.0, 1.0)?;
+    /// let high = key.zset_range_by_score(2.0, 3.0)?;
+    /// drop(low);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetFirstInScoreRange` is missing in redismodule.h
+    pub fn zset_range_by_score(
+        &mut self,
+        min: f64,
+        max: f64,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        self.zset_score_range_iter(min, max, false, false)
+    }
+
+    /// Return an iterator over the members of the sorted set stored at this
+    /// key whose score is between `min` and `max`, along with their scores.
+    /// Both bounds are excluded if `exclusive` is set, and the members are
+    /// returned from the highest score to the lowest if `reverse` is set.
+    /// The key is borrowed mutably, see [RedisKey::zset_range_by_score].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the `RedisModule_Zset*Range*` functions are missing in redismodule.h
+    pub fn zset_score_range_iter(
+        &mut self,
+        min: f64,
+        max: f64,
+        exclusive: bool,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        zset_range(self.key_inner, || {
+            ZsetRangeIterator::by_score(self.ctx, self.key_inner, min, max, exclusive, reverse)
+        })
+    }
+
+    /// Return an iterator over the members of the sorted set stored at this
+    /// key between `min` and `max`, given in the `ZRANGEBYLEX` format (`[a`,
+    /// `(a`, `-` or `+`), along with their scores. The members are returned
+    /// in reverse lexicographical order if `reverse` is set. The key is
+    /// borrowed mutably, see [RedisKey::zset_range_by_score].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the `RedisModule_Zset*Range*` functions are missing in redismodule.h
+    pub fn zset_lex_range_iter(
+        &mut self,
+        min: &RedisString,
+        max: &RedisString,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        zset_range(self.key_inner, || {
+            ZsetRangeIterator::by_lex(self.ctx, self.key_inner, min, max, reverse)
+        })
+    }
+
+    /// Return the name of the key, or `None` if the key does not exist.
+    /// The name holds its own reference to the string owned by the key, so
+    /// it stays valid after the key is closed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromModuleKey` is missing in redismodule.h
+    #[must_use]
+    pub fn get_name(&self) -> Option<RedisString> {
+        if self.is_null() {
+            return None;
+        }
+        Some(get_key_name(self.ctx, self.key_inner))
+    }
+
     /// Detects whether the key pointer given to us by Redis is null.
     #[must_use]
     pub fn is_null(&self) -> bool {
@@ -145,6 +293,12 @@ impl RedisKey {
         Ok(val)
     }
 
+    /// Same as [RedisKey::hash_get], but returns the raw bytes of the value,
+    /// which does not have to be valid UTF-8.
+    pub fn hash_get_bytes(&self, field: &str) -> Result<Option<Vec<u8>>, RedisError> {
+        Ok(self.hash_get(field)?.map(|v| v.as_slice().to_vec()))
+    }
+
     /// Returns the values associated with the specified fields in the hash stored at this key.
     /// The result will be `None` if the key does not exist.
     pub fn hash_get_multi<'a, A, B>(
@@ -167,6 +321,50 @@ impl RedisKey {
         Ok(val)
     }
 
+    /// Returns the raw bytes of the values associated with the specified
+    /// fields, in the same order as `fields`, `None` for missing fields.
+    /// The result will be `None` if the key does not exist.
+    pub fn hash_get_multi_bytes<A>(
+        &self,
+        fields: &[A],
+    ) -> Result<Option<Vec<Option<Vec<u8>>>>, RedisError>
+    where
+        A: Into<Vec<u8>> + Clone,
+    {
+        if self.is_null() {
+            return Ok(None);
+        }
+        hash_mget_key_bytes(self.ctx, self.key_inner, fields).map(Some)
+    }
+
+    /// Take a [KeySnapshot] holding the serialized value, the type and the
+    /// absolute expire of the key. The result will be `None` if the key does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromModuleKey` or `RedisModule_GetAbsExpire`
+    /// are missing in redismodule.h
+    pub fn dump_with_metadata(&self) -> Result<Option<KeySnapshot>, RedisError> {
+        let key_name = match self.get_name() {
+            Some(key_name) => key_name,
+            None => return Ok(None),
+        };
+        let ctx = Context::new(self.ctx);
+        let options = CallOptionsBuilder::new().errors_as_replies().build();
+        let res: CallResult = ctx.call_ext("DUMP", &options, &[&key_name]);
+        let payload = match res? {
+            CallReply::String(s) => s.as_bytes().to_vec(),
+            CallReply::Null(_) => return Ok(None),
+            _ => return Err(RedisError::Str("Unexpected DUMP reply type")),
+        };
+        let expire_at = raw::get_abs_expire(self.key_inner);
+        Ok(Some(KeySnapshot {
+            key_type: self.key_type(),
+            payload,
+            expire_at: (expire_at != REDISMODULE_NO_EXPIRE.into()).then_some(expire_at),
+        }))
+    }
+
     pub fn get_stream_iterator(&self, reverse: bool) -> Result<StreamIterator, RedisError> {
         StreamIterator::new(self, None, None, false, reverse)
     }
@@ -180,6 +378,19 @@ impl RedisKey {
     ) -> Result<StreamIterator, RedisError> {
         StreamIterator::new(self, from, to, exclusive, reverse)
     }
+
+    /// Same as [RedisKey::get_stream_range_iterator], with the bounds given
+    /// as [StreamId]. A missing bound starts (or ends) the range at the
+    /// first (or last) entry.
+    pub fn get_stream_id_range_iterator(
+        &self,
+        from: Option<StreamId>,
+        to: Option<StreamId>,
+        exclusive: bool,
+        reverse: bool,
+    ) -> Result<StreamIterator, RedisError> {
+        self.get_stream_range_iterator(from.map(Into::into), to.map(Into::into), exclusive, reverse)
+    }
 }
 
 impl Drop for RedisKey {
@@ -252,17 +463,78 @@ impl RedisKeyWritable {
         raw::hash_set(self.key_inner, field, value.inner)
     }
 
+    /// Set several fields of the hash stored at this key, creating the hash
+    /// if the key is empty.
+    ///
+    /// `RedisModule_HashSet` is a varargs function, so the fields are set 12
+    /// at a time (see [RedisKeyWritable::hash_get_multi]), a single call is
+    /// made when there are no more than 12 pairs. Returns [raw::Status::Err]
+    /// if the key holds a value which is not a hash or a field name contains
+    /// a NUL byte.
+    #[allow(clippy::must_use_candidate)]
+    pub fn hash_set_multi(&self, pairs: &[(&str, RedisString)]) -> raw::Status {
+        const BATCH_SIZE: usize = 12;
+
+        match self.key_type() {
+            KeyType::Empty | KeyType::Hash => {}
+            _ => return raw::Status::Err,
+        }
+
+        for chunk in pairs.chunks(BATCH_SIZE) {
+            let fields: Vec<&str> = chunk.iter().map(|(field, _)| *field).collect();
+            let values: Vec<*mut raw::RedisModuleString> =
+                chunk.iter().map(|(_, value)| value.inner).collect();
+            if raw::hash_set_multi(self.key_inner, &fields, &values) == raw::Status::Err {
+                return raw::Status::Err;
+            }
+        }
+        raw::Status::Ok
+    }
+
     #[allow(clippy::must_use_candidate)]
     pub fn hash_del(&self, field: &str) -> raw::Status {
         raw::hash_del(self.key_inner, field)
     }
 
+    /// Delete several fields from the hash stored at this key, returning the
+    /// number of fields that existed and were deleted. A missing key is treated
+    /// as an empty hash.
+    pub fn hash_del_multi(&self, fields: &[&str]) -> Result<usize, RedisError> {
+        const BATCH_SIZE: usize = 12;
+
+        match self.key_type() {
+            KeyType::Empty => return Ok(0),
+            KeyType::Hash => {}
+            _ => return Err(RedisError::WrongType),
+        }
+
+        fields
+            .chunks(BATCH_SIZE)
+            .map(|chunk_fields| raw::hash_del_multi(self.key_inner, chunk_fields))
+            .sum()
+    }
+
     pub fn hash_get(&self, field: &str) -> Result<Option<RedisString>, RedisError> {
         Ok(hash_mget_key(self.ctx, self.key_inner, &[field])?
             .pop()
             .expect("hash_mget_key should return vector of same length as input"))
     }
 
+    /// Same as [RedisKeyWritable::hash_get], but returns the raw bytes of the
+    /// value, which does not have to be valid UTF-8.
+    pub fn hash_get_bytes(&self, field: &str) -> Result<Option<Vec<u8>>, RedisError> {
+        Ok(self.hash_get(field)?.map(|v| v.as_slice().to_vec()))
+    }
+
+    /// Returns the raw bytes of the values associated with the specified
+    /// fields, in the same order as `fields`, `None` for missing fields.
+    pub fn hash_get_multi_bytes<A>(&self, fields: &[A]) -> Result<Vec<Option<Vec<u8>>>, RedisError>
+    where
+        A: Into<Vec<u8>> + Clone,
+    {
+        hash_mget_key_bytes(self.ctx, self.key_inner, fields)
+    }
+
     /// Returns the values associated with the specified fields in the hash stored at this key.
     pub fn hash_get_multi<'a, A, B>(
         &self,
@@ -291,6 +563,145 @@ impl RedisKeyWritable {
         raw::list_push(self.key_inner, raw::Where::ListTail, element.inner)
     }
 
+    /// Return the element at `index` of the list, see [RedisKey::list_get].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ListGet` is missing in redismodule.h
+    #[must_use]
+    pub fn list_get(&self, index: c_long) -> Option<RedisString> {
+        list_get(self.ctx, self.key_inner, index)
+    }
+
+    /// Return an iterator over the elements of the list, see [RedisKey::list_iter].
+    /// The key is borrowed mutably, so the list can not be modified while
+    /// iterating.
+    #[must_use]
+    pub fn list_iter(&mut self) -> ListIterator<'_> {
+        ListIterator::new(self.ctx, self.key_inner)
+    }
+
+    /// Add `member` to the sorted set stored at this key with the given
+    /// `score`, or update its score, according to `flags`. The sorted set is
+    /// created if the key is empty.
+    ///
+    /// Returns the score of the member after the call, which is not `score`
+    /// if the update was prevented by `flags`, or `None` if the member was
+    /// not added ([ZaddFlags::XX] on a new member).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetAdd` or `RedisModule_ZsetScore` are missing in redismodule.h
+    pub fn zset_add(
+        &self,
+        score: f64,
+        member: &RedisString,
+        flags: ZaddFlags,
+    ) -> Result<Option<f64>, RedisError> {
+        if !matches!(self.key_type(), KeyType::ZSet | KeyType::Empty) {
+            return Err(RedisError::WrongType);
+        }
+        let mut flags = flags.bits();
+        match raw::zset_add(self.key_inner, score, member.inner, &mut flags) {
+            raw::Status::Ok => Ok(zset_score(self.key_inner, member)),
+            raw::Status::Err => Err(RedisError::Str("ERR invalid score or flags")),
+        }
+    }
+
+    /// Return the score of `member` in the sorted set, see [RedisKey::zset_score].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetScore` is missing in redismodule.h
+    #[must_use]
+    pub fn zset_score(&self, member: &RedisString) -> Option<f64> {
+        zset_score(self.key_inner, member)
+    }
+
+    /// Return an iterator over a score range of the sorted set, see
+    /// [RedisKey::zset_range_by_score]. The key is borrowed mutably, so the
+    /// sorted set can not be modified while iterating.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ZsetFirstInScoreRange` is missing in redismodule.h
+    pub fn zset_range_by_score(
+        &mut self,
+        min: f64,
+        max: f64,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        self.zset_score_range_iter(min, max, false, false)
+    }
+
+    /// Return an iterator over a score range of the sorted set, see
+    /// [RedisKey::zset_score_range_iter].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the `RedisModule_Zset*Range*` functions are missing in redismodule.h
+    pub fn zset_score_range_iter(
+        &mut self,
+        min: f64,
+        max: f64,
+        exclusive: bool,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        zset_range(self.key_inner, || {
+            ZsetRangeIterator::by_score(self.ctx, self.key_inner, min, max, exclusive, reverse)
+        })
+    }
+
+    /// Return an iterator over a lexicographical range of the sorted set, see
+    /// [RedisKey::zset_lex_range_iter].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the `RedisModule_Zset*Range*` functions are missing in redismodule.h
+    pub fn zset_lex_range_iter(
+        &mut self,
+        min: &RedisString,
+        max: &RedisString,
+        reverse: bool,
+    ) -> Result<ZsetRangeIterator<'_>, RedisError> {
+        zset_range(self.key_inner, || {
+            ZsetRangeIterator::by_lex(self.ctx, self.key_inner, min, max, reverse)
+        })
+    }
+
+    /// Replace the element at `index` of the list, negative indexes count
+    /// from the tail. Fails if the key is not a list or the index is out of range.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ListSet` is missing in redismodule.h
+    pub fn list_set(&self, index: c_long, element: RedisString) -> Result<(), RedisError> {
+        if self.key_type() != KeyType::List {
+            return Err(RedisError::WrongType);
+        }
+        match raw::list_set(self.key_inner, index, element.inner) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str("ERR index out of range")),
+        }
+    }
+
+    /// Insert an element so it ends up at `index` of the list, shifting the
+    /// following elements. Inserting at the length of the list (or at `-1`)
+    /// appends the element, and inserting into an empty key creates the list.
+    /// Fails if the key is not a list or the index is out of range.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_ListInsert` is missing in redismodule.h
+    pub fn list_insert(&self, index: c_long, element: RedisString) -> Result<(), RedisError> {
+        if !matches!(self.key_type(), KeyType::List | KeyType::Empty) {
+            return Err(RedisError::WrongType);
+        }
+        match raw::list_insert(self.key_inner, index, element.inner) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str("ERR index out of range")),
+        }
+    }
+
     //  `list_pop_head` pops and returns the first element of the list.
     //  Returns None when:
     //     1. The list is empty.
@@ -321,6 +732,11 @@ impl RedisKeyWritable {
         Some(RedisString::new(NonNull::new(self.ctx), ptr))
     }
 
+    /// Set the key time to live.
+    ///
+    /// The duration is truncated to whole milliseconds, so a duration shorter
+    /// than one millisecond expires the key immediately. Use
+    /// [RedisKeyWritable::set_expire_checked] to reject such durations.
     pub fn set_expire(&self, expire: Duration) -> RedisResult {
         let exp_millis = expire.as_millis();
 
@@ -330,10 +746,53 @@ impl RedisKeyWritable {
 
         match raw::set_expire(self.key_inner, exp_time) {
             raw::Status::Ok => REDIS_OK,
+            raw::Status::Err => Err(self.expire_error("Error while setting key expire")),
+        }
+    }
+
+    /// Same as [RedisKeyWritable::set_expire] but returns an error instead of
+    /// expiring the key immediately when the duration is shorter than one millisecond.
+    pub fn set_expire_checked(&self, expire: Duration) -> RedisResult {
+        if expire < Duration::from_millis(1) {
+            return Err(RedisError::String(format!(
+                "Error expire duration {expire:?} is shorter than 1ms"
+            )));
+        }
+        self.set_expire(expire)
+    }
 
-            // Error may occur if the key wasn't open for writing or is an
-            // empty key.
-            raw::Status::Err => Err(RedisError::Str("Error while setting key expire")),
+    /// Return the remaining time to live of the key, or `None` if the key
+    /// has no expire (or does not exist).
+    pub fn get_expire(&self) -> Option<Duration> {
+        let ttl = raw::get_expire(self.key_inner);
+        if ttl == REDISMODULE_NO_EXPIRE.into() {
+            return None;
+        }
+        Some(Duration::from_millis(ttl.max(0) as u64))
+    }
+
+    /// Return the absolute unix time in milliseconds at which the key
+    /// expires, or `None` if the key has no expire (or does not exist).
+    ///
+    /// Unlike the remaining time to live returned by
+    /// [RedisKeyWritable::get_expire], the absolute expire can be copied to
+    /// another key with [RedisKeyWritable::set_absolute_expire] without drift.
+    pub fn get_absolute_expire(&self) -> Option<i64> {
+        let expire_at = raw::get_abs_expire(self.key_inner);
+        (expire_at != REDISMODULE_NO_EXPIRE.into()).then_some(expire_at)
+    }
+
+    /// Set the absolute unix time in milliseconds at which the key expires.
+    /// A time in the past expires the key immediately.
+    pub fn set_absolute_expire(&self, unix_ms: i64) -> RedisResult {
+        if unix_ms < 0 {
+            return Err(RedisError::String(format!(
+                "Error absolute expire {unix_ms} is not allowed"
+            )));
+        }
+        match raw::set_abs_expire(self.key_inner, unix_ms) {
+            raw::Status::Ok => REDIS_OK,
+            raw::Status::Err => Err(self.expire_error("Error while setting key expire")),
         }
     }
 
@@ -341,10 +800,20 @@ impl RedisKeyWritable {
     pub fn remove_expire(&self) -> RedisResult {
         match raw::set_expire(self.key_inner, REDISMODULE_NO_EXPIRE.into()) {
             raw::Status::Ok => REDIS_OK,
+            raw::Status::Err => Err(self.expire_error("Error while removing key expire")),
+        }
+    }
 
-            // Error may occur if the key wasn't open for writing or is an
-            // empty key.
-            raw::Status::Err => Err(RedisError::Str("Error while removing key expire")),
+    /// Explain why `RedisModule_SetExpire` failed, when the cause can be
+    /// told apart: it fails if the key is empty (does not exist), otherwise
+    /// the cause, like an invalid expire, is not reported by Redis.
+    fn expire_error(&self, msg: &str) -> RedisError {
+        if self.key_inner.is_null() {
+            RedisError::String(format!("{msg}: key is not open"))
+        } else if self.is_empty() {
+            RedisError::String(format!("{msg}: key does not exist"))
+        } else {
+            RedisError::String(msg.to_owned())
         }
     }
 
@@ -380,6 +849,31 @@ impl RedisKeyWritable {
         unsafe { raw::RedisModule_KeyType.unwrap()(self.key_inner) }.into()
     }
 
+    /// Invoke a command on this key, the key name is passed as the first
+    /// argument, followed by `args`, so `key.call("HINCRBY", &["field", "1"])`
+    /// runs `HINCRBY <key name> field 1`.
+    ///
+    /// Redis does not allow calling a command on an opened key, so it still
+    /// looks up the key by its name, but the name is reused from the key
+    /// instead of creating a new string on every call. See [Context::call].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromModuleKey` is missing in redismodule.h
+    pub fn call<'a, T: Into<StrCallArgs<'a>>>(&self, command: &str, args: T) -> RedisResult {
+        Context::new(self.ctx).call_on_key(command, self.key_inner, args)
+    }
+
+    /// Return the name of the key, see [RedisKey::get_name].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_GetKeyNameFromModuleKey` is missing in redismodule.h
+    #[must_use]
+    pub fn get_name(&self) -> RedisString {
+        get_key_name(self.ctx, self.key_inner)
+    }
+
     pub fn open_with_redis_string(
         ctx: *mut raw::RedisModuleCtx,
         key: *mut raw::RedisModuleString,
@@ -428,11 +922,81 @@ impl RedisKeyWritable {
         status.into()
     }
 
+    /// Add an entry with the given fields to the stream, creating the stream
+    /// if the key is empty, and return the id of the new entry.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StreamAdd` is missing in redismodule.h
+    pub fn stream_add(
+        &self,
+        id: StreamAddOption,
+        fields: &[(&str, &RedisString)],
+    ) -> Result<StreamId, RedisError> {
+        if !matches!(self.key_type(), KeyType::Stream | KeyType::Empty) {
+            return Err(RedisError::WrongType);
+        }
+        let (flags, mut id) = match id {
+            StreamAddOption::AutoId => (
+                raw::REDISMODULE_STREAM_ADD_AUTOID,
+                raw::RedisModuleStreamID { ms: 0, seq: 0 },
+            ),
+            StreamAddOption::Id(id) => (0, id.into()),
+        };
+        let names: Vec<RedisString> = fields
+            .iter()
+            .map(|(name, _)| RedisString::create_from_slice(self.ctx, name.as_bytes()))
+            .collect();
+        let mut argv: Vec<*mut raw::RedisModuleString> = names
+            .iter()
+            .zip(fields)
+            .flat_map(|(name, (_, value))| [name.inner, value.inner])
+            .collect();
+        let res: raw::Status = unsafe {
+            raw::RedisModule_StreamAdd.unwrap()(
+                self.key_inner,
+                flags as c_int,
+                &mut id,
+                argv.as_mut_ptr(),
+                fields.len() as i64,
+            )
+        }
+        .into();
+        match res {
+            raw::Status::Ok => Ok(id.into()),
+            raw::Status::Err if flags == 0 => Err(RedisError::Str(
+                "ERR The ID specified in XADD is equal or smaller than the target stream top item",
+            )),
+            raw::Status::Err => Err(RedisError::Str("ERR failed adding the stream entry")),
+        }
+    }
+
+    /// Delete the entry with the given id from the stream.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `RedisModule_StreamDelete` is missing in redismodule.h
+    pub fn stream_delete(&self, id: StreamId) -> Result<(), RedisError> {
+        let mut id = id.into();
+        match self.key_type() {
+            KeyType::Stream => {}
+            KeyType::Empty => return Err(RedisError::Str("ERR no such stream entry")),
+            _ => return Err(RedisError::WrongType),
+        }
+        let res: raw::Status =
+            unsafe { raw::RedisModule_StreamDelete.unwrap()(self.key_inner, &mut id) }.into();
+        match res {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(RedisError::Str("ERR no such stream entry")),
+        }
+    }
+
     pub fn trim_stream_by_id(
         &self,
-        mut id: raw::RedisModuleStreamID,
+        id: impl Into<raw::RedisModuleStreamID>,
         approx: bool,
     ) -> Result<usize, RedisError> {
+        let mut id = id.into();
         let flags = if approx {
             raw::REDISMODULE_STREAM_TRIM_APPROX
         } else {
@@ -461,6 +1025,23 @@ where
     phantom: std::marker::PhantomData<B>,
 }
 
+impl<'a, A, B> HMGetResult<'a, A, B>
+where
+    A: Into<Vec<u8>> + Clone,
+    RedisString: Into<B>,
+{
+    /// Return a (field-name, field-value) pair for every requested field, in the
+    /// order they were requested. Unlike [HMGetResult::into_iter], fields that do
+    /// not exist in the hash are kept with a `None` value.
+    pub fn into_vec_with_misses(self) -> Vec<(A, Option<B>)> {
+        self.fields
+            .iter()
+            .cloned()
+            .zip(self.values.into_iter().map(|v| v.map(Into::into)))
+            .collect()
+    }
+}
+
 pub struct HMGetIter<'a, A, B>
 where
     A: Into<Vec<u8>>,
@@ -656,6 +1237,117 @@ where
     Ok(values)
 }
 
+fn hash_mget_key_bytes<T>(
+    ctx: *mut raw::RedisModuleCtx,
+    key: *mut raw::RedisModuleKey,
+    fields: &[T],
+) -> Result<Vec<Option<Vec<u8>>>, RedisError>
+where
+    T: Into<Vec<u8>> + Clone,
+{
+    Ok(hash_mget_key(ctx, key, fields)?
+        .into_iter()
+        .map(|v| v.map(|v| v.as_slice().to_vec()))
+        .collect())
+}
+
+/// An iterator over the elements of a list, returned by [RedisKey::list_iter]
+/// and [RedisKeyWritable::list_iter]. Redis keeps its position in the list
+/// between calls, so reading the elements one after the other is not
+/// quadratic. The list must not be modified while iterating.
+pub struct ListIterator<'key> {
+    ctx: *mut raw::RedisModuleCtx,
+    key_inner: *mut raw::RedisModuleKey,
+    index: usize,
+    len: usize,
+    _key: std::marker::PhantomData<&'key ()>,
+}
+
+impl<'key> ListIterator<'key> {
+    fn new(ctx: *mut raw::RedisModuleCtx, key_inner: *mut raw::RedisModuleKey) -> Self {
+        let len = if raw::KeyType::from(unsafe { raw::RedisModule_KeyType.unwrap()(key_inner) })
+            == KeyType::List
+        {
+            raw::value_length(key_inner)
+        } else {
+            0
+        };
+        Self {
+            ctx,
+            key_inner,
+            index: 0,
+            len,
+            _key: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'key> Iterator for ListIterator<'key> {
+    type Item = RedisString;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+        let res = list_get(self.ctx, self.key_inner, self.index as c_long);
+        self.index += 1;
+        res
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'key> ExactSizeIterator for ListIterator<'key> {}
+
+fn zset_score(key_inner: *mut raw::RedisModuleKey, member: &RedisString) -> Option<f64> {
+    // A key which does not exist has no members.
+    if key_inner.is_null() {
+        return None;
+    }
+    let mut score = 0.0;
+    match raw::zset_score(key_inner, member.inner, &mut score) {
+        raw::Status::Ok => Some(score),
+        raw::Status::Err => None,
+    }
+}
+
+// Start the range with `start` if the key is a sorted set, a key which does
+// not exist has an empty range.
+fn zset_range<'key>(
+    key_inner: *mut raw::RedisModuleKey,
+    start: impl FnOnce() -> Result<ZsetRangeIterator<'key>, RedisError>,
+) -> Result<ZsetRangeIterator<'key>, RedisError> {
+    let key_type: KeyType = unsafe { raw::RedisModule_KeyType.unwrap()(key_inner) }.into();
+    match key_type {
+        KeyType::Empty => Ok(ZsetRangeIterator::empty()),
+        KeyType::ZSet => start(),
+        _ => Err(RedisError::WrongType),
+    }
+}
+
+// The element is a new string, owned by the returned [RedisString].
+fn list_get(
+    ctx: *mut raw::RedisModuleCtx,
+    key_inner: *mut raw::RedisModuleKey,
+    index: c_long,
+) -> Option<RedisString> {
+    let ptr = raw::list_get(key_inner, index);
+    if ptr.is_null() {
+        return None;
+    }
+    Some(RedisString::from_redis_module_string(ctx, ptr))
+}
+
+// The name is retained, the key keeps owning its own reference.
+fn get_key_name(ctx: *mut raw::RedisModuleCtx, key_inner: *mut raw::RedisModuleKey) -> RedisString {
+    RedisString::new(NonNull::new(ctx), unsafe {
+        raw::RedisModule_GetKeyNameFromModuleKey.unwrap()(key_inner) as *mut raw::RedisModuleString
+    })
+}
+
 fn to_raw_mode(mode: KeyMode) -> raw::KeyMode {
     match mode {
         KeyMode::Read => raw::KeyMode::READ,
@@ -3,7 +3,10 @@ extern crate num_traits;
 
 pub mod alloc;
 pub mod apierror;
+pub mod cursor;
+pub mod dict;
 pub mod error;
+pub mod interner;
 pub mod native_types;
 pub mod raw;
 pub mod rediserror;
@@ -11,28 +14,40 @@ mod redismodule;
 pub mod redisraw;
 pub mod redisvalue;
 pub mod stream;
+pub mod zset;
 
 pub mod configuration;
 mod context;
 pub mod key;
 pub mod logging;
 mod macros;
+pub mod metrics;
 mod utils;
 
 pub use crate::context::blocked::BlockedClient;
 pub use crate::context::thread_safe::{
-    ContextGuard, DetachedFromClient, RedisGILGuard, RedisLockIndicator, ThreadSafeContext,
+    ContextGuard, DetachedFromClient, GilCell, RedisGILGuard, RedisLockIndicator, ThreadSafeContext,
 };
+pub use crate::cursor::{Cursor, CursorReply};
+pub use crate::dict::{DictDirection, RedisDict};
+pub use crate::interner::{RedisStringInterner, SharedRedisString};
 pub use crate::raw::NotifyEvent;
 
 pub use crate::configuration::ConfigurationValue;
 pub use crate::configuration::EnumConfigurationValue;
 pub use crate::context::call_reply::FutureCallReply;
-pub use crate::context::call_reply::{CallReply, CallResult, ErrorReply, PromiseCallReply};
+pub use crate::context::call_reply::{
+    CallReply, CallResult, ErrorReply, FromCallReply, PromiseCallReply, VerbatimStringFormat,
+};
+pub use crate::context::client::{ClientInfo, ClientInfoFlags};
+pub use crate::context::commandfilter;
 pub use crate::context::commands;
 pub use crate::context::defrag;
-pub use crate::context::keys_cursor::KeysCursor;
+pub use crate::context::info::KeyspaceStats;
+pub use crate::context::keys_cursor::{KeysCursor, ScanIter};
+pub use crate::context::reply::ReplyBuilder;
 pub use crate::context::server_events;
+pub use crate::context::write_behind::WriteBehindBuffer;
 pub use crate::context::AclCategory;
 pub use crate::context::AclPermissions;
 #[cfg(any(
@@ -40,6 +55,7 @@ pub use crate::context::AclPermissions;
     feature = "min-redis-compatibility-version-7-2"
 ))]
 pub use crate::context::BlockingCallOptions;
+pub use crate::context::CallArgs;
 pub use crate::context::CallOptionResp;
 pub use crate::context::CallOptions;
 pub use crate::context::CallOptionsBuilder;
@@ -47,6 +63,8 @@ pub use crate::context::Context;
 pub use crate::context::ContextFlags;
 pub use crate::context::DetachedContext;
 pub use crate::context::DetachedContextGuard;
+pub use crate::context::OwnedDetachedContext;
+pub use crate::context::OwnedDetachedContextGuard;
 pub use crate::context::{
     InfoContextBuilderFieldBottomLevelValue, InfoContextBuilderFieldTopLevelValue,
     InfoContextFieldBottomLevelData, InfoContextFieldTopLevelData, OneInfoSectionData,
@@ -55,6 +73,7 @@ pub use crate::raw::*;
 pub use crate::redismodule::*;
 use backtrace::Backtrace;
 use context::server_events::INFO_COMMAND_HANDLER_LIST;
+use std::sync::Once;
 
 /// The detached Redis module context (the context of this module). It
 /// is only set to a proper value after the module is initialised via the
@@ -72,22 +91,21 @@ fn add_trace_info(ctx: &InfoContext) -> RedisResult<()> {
     const SECTION_NAME: &str = "trace";
     const FIELD_NAME: &str = "backtrace";
 
-    let current_backtrace = Backtrace::new();
-    let trace = format!("{current_backtrace:?}");
-
-    ctx.builder()
-        .add_section(SECTION_NAME)
-        .field(FIELD_NAME, trace)?
-        .build_section()?
-        .build_info()?;
-
-    Ok(())
+    // Capturing the backtrace is expensive, skip it if the section is not requested.
+    ctx.build_section_if_requested(SECTION_NAME, |section| {
+        let current_backtrace = Backtrace::new();
+        section.field(FIELD_NAME, format!("{current_backtrace:?}"))
+    })
 }
 
 /// A type alias for the custom info command handler.
 /// The function may optionally return an object of one section to add.
 /// If nothing is returned, it is assumed that the function has already
 /// filled all the information required via [`InfoContext::builder`].
+///
+/// Redis does not tell the info handlers which sections were requested, use
+/// [`InfoContext::build_section_if_requested`] to skip building the sections
+/// which would be discarded.
 pub type InfoHandlerFunctionType = fn(&InfoContext, bool) -> RedisResult<()>;
 
 /// Default "INFO" command handler for the module.
@@ -106,6 +124,10 @@ pub fn basic_info_command_handler(ctx: &InfoContext, for_crash_report: bool) {
         .iter()
         .filter_map(|callback| callback(ctx, for_crash_report).err())
         .for_each(|e| log::error!("Couldn't build info for the module's custom handler: {e}"));
+
+    if let Err(e) = metrics::add_metrics_info(ctx) {
+        log::error!("Couldn't build the metrics info for the module: {e}");
+    }
 }
 
 /// Initialize RedisModuleAPI without register as a module.
@@ -113,6 +135,26 @@ pub fn init_api(ctx: &Context) {
     unsafe { crate::raw::Export_RedisModule_InitAPI(ctx.ctx) };
 }
 
+static MODULE_GLOBALS_INIT: Once = Once::new();
+
+/// Run `init` exactly once for the lifetime of the process, setting
+/// [MODULE_CONTEXT] first if it was not set yet.
+///
+/// This is useful for modules that do not use the [redis_module] macro
+/// (see [init_api]) and have several entrypoints, each of which might be
+/// the first one to run. The API must already be initialized when this
+/// function is called. Returns `true` if `init` was run by this call.
+pub fn initialize_module_globals<F: FnOnce(&Context)>(ctx: &Context, init: F) -> bool {
+    let mut initialized = false;
+    MODULE_GLOBALS_INIT.call_once(|| {
+        // The context might have already been set by the module itself.
+        let _ = MODULE_CONTEXT.set_context(ctx);
+        init(ctx);
+        initialized = true;
+    });
+    initialized
+}
+
 pub(crate) unsafe fn deallocate_pointer<P>(p: *mut P) {
     std::ptr::drop_in_place(p);
     std::alloc::dealloc(p as *mut u8, std::alloc::Layout::new::<P>());
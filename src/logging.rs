@@ -37,11 +37,24 @@ pub(crate) fn log_internal<L: Into<RedisLogLevel>>(
         return;
     }
 
-    let level = CString::new(level.into().as_ref()).unwrap();
-    let fmt = CString::new(message).unwrap();
-    unsafe {
-        raw::RedisModule_Log.expect(NOT_INITIALISED_MESSAGE)(ctx, level.as_ptr(), fmt.as_ptr())
-    }
+    let level = level.into();
+    let log = match unsafe { raw::RedisModule_Log } {
+        Some(log) => log,
+        // Nothing can be logged to Redis before the module is loaded.
+        None => {
+            eprintln!("{}: {message}", level.as_ref());
+            return;
+        }
+    };
+    let level = CString::new(level.as_ref()).unwrap();
+    let fmt = message_as_cstring(message);
+    unsafe { log(ctx, level.as_ptr(), fmt.as_ptr()) }
+}
+
+/// Convert a log message to a C string, replacing the null bytes, which a C
+/// string can't hold, with spaces.
+pub(crate) fn message_as_cstring(message: &str) -> CString {
+    CString::new(message.replace('\0', " ")).unwrap_or_default()
 }
 
 /// This function should be used when a callback is returning a critical error
@@ -52,7 +65,7 @@ pub fn log_io_error(io: *mut raw::RedisModuleIO, level: RedisLogLevel, message:
         return;
     }
     let level = CString::new(level.as_ref()).unwrap();
-    let fmt = CString::new(message).unwrap();
+    let fmt = message_as_cstring(message);
     unsafe {
         raw::RedisModule_LogIOError.expect(NOT_INITIALISED_MESSAGE)(
             io,
@@ -66,7 +79,15 @@ pub fn log_io_error(io: *mut raw::RedisModuleIO, level: RedisLogLevel, message:
 /// requiring a context. This prevents Redis from including the module
 /// name in the logged message.
 pub fn log<T: AsRef<str>>(level: RedisLogLevel, message: T) {
-    log_internal(ptr::null_mut(), level, message.as_ref());
+    log_to_current(level, message.as_ref());
+}
+
+/// Log a message without any context, so it can be called from any thread,
+/// without holding the Redis GIL and before a [crate::DetachedContext] is
+/// set. Before the module is loaded, the message is written to the standard
+/// error instead. Null bytes in the message are replaced with spaces.
+pub fn log_to_current(level: RedisLogLevel, message: &str) {
+    log_internal(ptr::null_mut(), level, message);
 }
 
 /// Log a message to Redis at the [RedisLogLevel::Debug] level.
@@ -194,3 +215,23 @@ pub mod standard_log_implementation {
     }
 }
 pub use standard_log_implementation::*;
+
+#[cfg(test)]
+mod tests {
+    use super::{message_as_cstring, RedisLogLevel};
+
+    #[test]
+    fn level_names() {
+        // The level names Redis expects in `RedisModule_Log`.
+        assert_eq!(RedisLogLevel::Debug.as_ref(), "debug");
+        assert_eq!(RedisLogLevel::Notice.as_ref(), "notice");
+        assert_eq!(RedisLogLevel::Verbose.as_ref(), "verbose");
+        assert_eq!(RedisLogLevel::Warning.as_ref(), "warning");
+    }
+
+    #[test]
+    fn message_with_nulls() {
+        assert_eq!(message_as_cstring("a\0b\0").to_bytes(), b"a b ");
+        assert_eq!(message_as_cstring("plain").to_bytes(), b"plain");
+    }
+}
@@ -15,6 +15,10 @@ macro_rules! redis_command {
         let name = CString::new($command_name).unwrap();
         let flags = CString::new($command_flags).unwrap();
 
+        // Holds the handler set by `Context::replace_command_handler`, if any.
+        static __HANDLER_SLOT: $crate::commands::CommandHandlerSlot =
+            $crate::commands::CommandHandlerSlot::new();
+
         /////////////////////
         extern "C" fn __do_command(
             ctx: *mut $crate::raw::RedisModuleCtx,
@@ -24,8 +28,11 @@ macro_rules! redis_command {
             let context = $crate::Context::new(ctx);
 
             let args = $crate::decode_args(ctx, argv, argc);
-            let response = $command_handler(&context, args);
-            context.reply(response.map(|v| v.into())) as c_int
+            let response = match __HANDLER_SLOT.get() {
+                Some(handler) => handler(&context, args),
+                None => $command_handler(&context, args).map(|v| v.into()),
+            };
+            context.reply(response) as c_int
         }
         /////////////////////
 
@@ -43,10 +50,12 @@ macro_rules! redis_command {
         {
             $crate::raw::redis_log(
                 $ctx,
+                $crate::logging::RedisLogLevel::Warning,
                 &format!("Error: failed to create command {}", $command_name),
             );
             return $crate::raw::Status::Err as c_int;
         }
+        $crate::commands::register_command_handler_slot(name.to_str().unwrap(), &__HANDLER_SLOT);
 
         let mandatory = AclCategory::from($mandatory_acl_categories);
         if let Some(RM_SetCommandACLCategories) = $crate::raw::RedisModule_SetCommandACLCategories {
@@ -55,6 +64,7 @@ macro_rules! redis_command {
             if command.is_null() {
                 $crate::raw::redis_log(
                     $ctx,
+                    $crate::logging::RedisLogLevel::Warning,
                     &format!("Error: failed to get command {}", $command_name),
                 );
                 return $crate::raw::Status::Err as c_int;
@@ -75,6 +85,7 @@ macro_rules! redis_command {
                     optional_failed = true;
                     $crate::raw::redis_log(
                         $ctx,
+                        $crate::logging::RedisLogLevel::Warning,
                         &format!(
                             "Warning: failed to set command `{}` ACL categories `{}`",
                             $command_name, acl_categories.to_str().unwrap()
@@ -91,6 +102,7 @@ macro_rules! redis_command {
                 {
                     $crate::raw::redis_log(
                         $ctx,
+                        $crate::logging::RedisLogLevel::Warning,
                         &format!(
                             "Error: failed to set command `{}` mandatory ACL categories `{mandatory}`",
                             $command_name
@@ -102,6 +114,7 @@ macro_rules! redis_command {
         } else if mandatory != AclCategory::None {
             $crate::raw::redis_log(
                 $ctx,
+                $crate::logging::RedisLogLevel::Warning,
                 "Error: Redis version does not support ACL categories",
             );
             return $crate::raw::Status::Err as c_int;
@@ -158,6 +171,98 @@ macro_rules! redis_event_handler {
     }};
 }
 
+/// Exports a function to other modules using the Redis shared API, see
+/// [crate::Context::export_typed_shared_api].
+///
+/// The function is checked against the given type at compile time, and the
+/// type, as written, is exported alongside it so that [import_api] can
+/// compare it on the other side. The macro must be called in an `unsafe`
+/// block, as the importing modules trust the exported type.
+///
+/// ```ignore
+/// pub extern "C" fn add(a: i64, b: i64) -> i64 { a + b }
+///
+/// unsafe { export_api!(ctx, "mymodule.add", add: extern "C" fn(i64, i64) -> i64) }?;
+/// ```
+#[macro_export]
+macro_rules! export_api {
+    ($ctx:expr, $name:expr, $func:path : $ty:ty) => {{
+        let func: $ty = $func;
+        $crate::Context::export_typed_shared_api(
+            $ctx,
+            $name,
+            func as *const ::std::os::raw::c_void,
+            ::std::ffi::CStr::from_bytes_with_nul(concat!(stringify!($ty), "\0").as_bytes())
+                .unwrap(),
+        )
+    }};
+}
+
+/// Imports a function exported by another module with [export_api], see
+/// [crate::Context::import_typed_shared_api].
+///
+/// Returns an error if the function was not exported, or if it was exported
+/// with a type written differently. The macro must be called in an `unsafe`
+/// block, as the function is cast to `$ty`, which is only sound if it really
+/// has this type.
+///
+/// ```ignore
+/// let add = unsafe { import_api!(ctx, "mymodule.add", extern "C" fn(i64, i64) -> i64) }?;
+/// let sum = add(1, 2);
+/// ```
+#[macro_export]
+macro_rules! import_api {
+    ($ctx:expr, $name:expr, $ty:ty) => {
+        $crate::Context::import_typed_shared_api(
+            $ctx,
+            $name,
+            ::std::ffi::CStr::from_bytes_with_nul(concat!(stringify!($ty), "\0").as_bytes())
+                .unwrap(),
+        )
+        .map(|func| ::std::mem::transmute::<*mut ::std::os::raw::c_void, $ty>(func))
+    };
+}
+
+/// Asserts that a condition holds, like [std::assert!], but on failure calls
+/// `RedisModule_Assert` so Redis writes a proper crash report (with the
+/// module's INFO sections and backtrace) instead of unwinding a Rust panic
+/// across the FFI boundary. An optional message can be given using the
+/// [format!] syntax; it replaces the stringified condition in the report.
+///
+/// It is not named `assert` so that a glob import of the crate does not make
+/// the standard [std::assert!] ambiguous.
+///
+/// ```ignore
+/// redis_assert!(len <= capacity, "len {} exceeds capacity {}", len, capacity);
+/// ```
+#[macro_export]
+macro_rules! redis_assert {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            $crate::raw::assert_failed(::std::stringify!($cond), ::std::file!(), ::std::line!());
+        }
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::raw::assert_failed(&::std::format!($($arg)+), ::std::file!(), ::std::line!());
+        }
+    };
+}
+
+/// Creates a [crate::RedisString] using the [format!] syntax, writing directly
+/// into the `RedisModuleString` buffer. The first argument is the optional
+/// context passed to [crate::RedisString::from_format].
+///
+/// ```ignore
+/// let key = rformat!(NonNull::new(ctx.ctx), "{}:{}", prefix, id);
+/// ```
+#[macro_export]
+macro_rules! rformat {
+    ($ctx:expr, $($arg:tt)*) => {
+        $crate::RedisString::from_format($ctx, format_args!($($arg)*))
+    };
+}
+
 /// Defines a Redis module.
 ///
 /// It registers the defined module, sets it up and initialises properly,
@@ -322,12 +427,12 @@ macro_rules! redis_module {
                         if module_acl_category != AclCategory::None {
                             let category = CString::new(format!("{module_acl_category}")).unwrap();
                             if RM_AddACLCategory(ctx, category.as_ptr()) == raw::Status::Err as c_int {
-                                raw::redis_log(ctx, &format!("Error: failed to add ACL category `{module_acl_category}`"));
+                                raw::redis_log(ctx, $crate::logging::RedisLogLevel::Warning, &format!("Error: failed to add ACL category `{module_acl_category}`"));
                                 return raw::Status::Err as c_int;
                             }
                         }
                     } else {
-                        raw::redis_log(ctx, "Warning: Redis version does not support adding new ACL categories");
+                        raw::redis_log(ctx, $crate::logging::RedisLogLevel::Warning, "Warning: Redis version does not support adding new ACL categories");
                     }
                 )*
             )?
@@ -0,0 +1,188 @@
+//! Counters and gauges that are exported automatically in the `metrics`
+//! section of the module's INFO output (`INFO <module>_metrics`).
+//!
+//! Metrics are usually declared as statics and registered once, for
+//! example when the module is loaded:
+//!
+//! ```ignore
+//! static NUM_REQUESTS: Counter = Counter::new("num_requests");
+//!
+//! fn init(_ctx: &Context, _args: &[RedisString]) -> Status {
+//!     NUM_REQUESTS.register();
+//!     Status::Ok
+//! }
+//! ```
+//!
+//! Updating a metric does not require the Redis GIL, so metrics can be
+//! updated from any thread.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::{InfoContext, RedisResult};
+
+const SECTION_NAME: &str = "metrics";
+
+/// A monotonically increasing counter.
+#[derive(Debug)]
+pub struct Counter {
+    name: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.value.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Export the counter in the module's INFO output. Registering the
+    /// same counter more than once has no effect.
+    pub fn register(&'static self) {
+        register(Metric::Counter(self));
+    }
+}
+
+/// A value that can go up and down.
+#[derive(Debug)]
+pub struct Gauge {
+    name: &'static str,
+    value: AtomicI64,
+}
+
+impl Gauge {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: AtomicI64::new(0),
+        }
+    }
+
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn dec(&self) {
+        self.add(-1);
+    }
+
+    pub fn add(&self, n: i64) {
+        self.value.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Export the gauge in the module's INFO output. Registering the
+    /// same gauge more than once has no effect.
+    pub fn register(&'static self) {
+        register(Metric::Gauge(self));
+    }
+}
+
+/// The number of calls, the number of errors and the accumulated latency (in
+/// microseconds) of a command. This is what the `metrics: true` option of the
+/// `command` macro uses, the counters are named `cmd_<command>_calls`,
+/// `cmd_<command>_errors` and `cmd_<command>_usec`.
+#[derive(Debug)]
+pub struct CommandMetrics {
+    calls: Counter,
+    errors: Counter,
+    usec: Counter,
+}
+
+impl CommandMetrics {
+    pub const fn new(
+        calls_name: &'static str,
+        errors_name: &'static str,
+        usec_name: &'static str,
+    ) -> Self {
+        Self {
+            calls: Counter::new(calls_name),
+            errors: Counter::new(errors_name),
+            usec: Counter::new(usec_name),
+        }
+    }
+
+    /// Run the command handler `f`, counting the call, its latency and
+    /// whether it returned an error.
+    pub fn measure<T, E>(&self, f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        let start = Instant::now();
+        let res = f();
+        self.usec.add(start.elapsed().as_micros() as u64);
+        self.calls.inc();
+        if res.is_err() {
+            self.errors.inc();
+        }
+        res
+    }
+
+    /// Export the counters in the module's INFO output.
+    pub fn register(&'static self) {
+        self.calls.register();
+        self.errors.register();
+        self.usec.register();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Metric {
+    Counter(&'static Counter),
+    Gauge(&'static Gauge),
+}
+
+impl Metric {
+    fn is_same(&self, other: &Metric) -> bool {
+        match (self, other) {
+            (Metric::Counter(a), Metric::Counter(b)) => std::ptr::eq(*a, *b),
+            (Metric::Gauge(a), Metric::Gauge(b)) => std::ptr::eq(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+static METRICS: Mutex<Vec<Metric>> = Mutex::new(Vec::new());
+
+fn register(metric: Metric) {
+    let mut metrics = METRICS.lock().unwrap();
+    if !metrics.iter().any(|m| m.is_same(&metric)) {
+        metrics.push(metric);
+    }
+}
+
+/// Add the `metrics` section with all the registered metrics, if any.
+pub(crate) fn add_metrics_info(ctx: &InfoContext) -> RedisResult<()> {
+    let metrics = METRICS.lock().unwrap().clone();
+    if metrics.is_empty() {
+        return Ok(());
+    }
+
+    ctx.build_section_if_requested(SECTION_NAME, |section| {
+        metrics
+            .into_iter()
+            .try_fold(section, |section, metric| match metric {
+                Metric::Counter(c) => section.field(c.name, c.get()),
+                Metric::Gauge(g) => section.field(g.name, g.get()),
+            })
+    })
+}
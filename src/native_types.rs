@@ -1,9 +1,29 @@
 use std::cell::RefCell;
 use std::ffi::CString;
+use std::os::raw::{c_int, c_void};
 use std::ptr;
 
+use crate::error::Error;
+use crate::logging::{log_io_error, RedisLogLevel};
 use crate::raw;
 
+/// The type methods version that introduced `free_effort`.
+const FREE_EFFORT_METHOD_VERSION: u64 = 3;
+/// The type methods version that introduced `free_effort2`.
+const FREE_EFFORT2_METHOD_VERSION: u64 = 4;
+
+/// A module data type, registered on module load.
+///
+/// The `free_effort` (or `free_effort2`) callback lets Redis decide whether
+/// a value is freed on the main thread or in a background thread. It returns
+/// the number of allocations needed to free the value: values whose effort
+/// is above the lazyfree threshold (64) are freed in the background when the
+/// deletion is lazy, that is on `UNLINK`, `FLUSHALL ASYNC`, or when the
+/// matching `lazyfree-lazy-user-del`, `lazyfree-lazy-expire`,
+/// `lazyfree-lazy-eviction` or `lazyfree-lazy-server-del` config is enabled.
+/// Returning 0 means the effort is unknown and the value is always freed
+/// lazily in those cases. Without the callback, values are always freed on
+/// the main thread.
 pub struct RedisType {
     name: &'static str,
     version: i32,
@@ -34,10 +54,16 @@ impl RedisType {
     pub fn create_data_type(&self, ctx: *mut raw::RedisModuleCtx) -> Result<(), &str> {
         if self.name.len() != 9 {
             let msg = "Redis requires the length of native type names to be exactly 9 characters";
-            raw::redis_log(ctx, format!("{msg}, name is: '{}'", self.name).as_str());
+            raw::redis_log(
+                ctx,
+                RedisLogLevel::Warning,
+                format!("{msg}, name is: '{}'", self.name).as_str(),
+            );
             return Err(msg);
         }
 
+        self.check_type_methods_version(ctx);
+
         let type_name = CString::new(self.name).unwrap();
 
         let redis_type = unsafe {
@@ -50,7 +76,11 @@ impl RedisType {
         };
 
         if redis_type.is_null() {
-            raw::redis_log(ctx, "Error: created data type is null");
+            raw::redis_log(
+                ctx,
+                RedisLogLevel::Warning,
+                "Error: created data type is null",
+            );
             return Err("Error: created data type is null");
         }
 
@@ -58,9 +88,95 @@ impl RedisType {
 
         raw::redis_log(
             ctx,
+            RedisLogLevel::Notice,
             format!("Created new data type '{}'", self.name).as_str(),
         );
 
         Ok(())
     }
+
+    /// Return the encoding version new values are saved with. Values loaded
+    /// from an RDB get the version they were saved with as `encver`.
+    pub const fn encoding_version(&self) -> i32 {
+        self.version
+    }
+
+    /// Return an error if `encver` is newer than the current encoding version,
+    /// that is if the value was saved by a newer version of the module.
+    pub fn check_encoding_version(&self, encver: i32) -> Result<(), Error> {
+        if encver > self.version {
+            return Err(Error::generic(&format!(
+                "Can not load data type '{}' with encoding version {encver}, the latest supported version is {}",
+                self.name, self.version
+            )));
+        }
+        Ok(())
+    }
+
+    /// Return the `REDISMODULE_TYPE_METHOD_VERSION` of the running server, or
+    /// `None` if the server does not expose `RedisModule_GetTypeMethodVersion`.
+    pub fn server_type_method_version() -> Option<u64> {
+        unsafe { raw::RedisModule_GetTypeMethodVersion }.map(|f| unsafe { f() } as u64)
+    }
+
+    /// Warn about callbacks that the running server is too old to call.
+    fn check_type_methods_version(&self, ctx: *mut raw::RedisModuleCtx) {
+        let server_version = match Self::server_type_method_version() {
+            Some(v) => v,
+            None => return,
+        };
+        let methods = &self.type_methods;
+        let callbacks = [
+            (
+                "free_effort",
+                methods.free_effort.is_some(),
+                FREE_EFFORT_METHOD_VERSION,
+            ),
+            (
+                "free_effort2",
+                methods.free_effort2.is_some(),
+                FREE_EFFORT2_METHOD_VERSION,
+            ),
+        ];
+        for (name, is_set, required) in callbacks {
+            if is_set && server_version < required {
+                raw::redis_log(
+                    ctx,
+                    RedisLogLevel::Warning,
+                    format!(
+                        "Warning: '{name}' of data type '{}' requires type methods version {required}, \
+                         the server supports {server_version}, it will be ignored",
+                        self.name
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+}
+
+/// A module data type value that can be loaded from an RDB, see [rdb_load].
+pub trait RdbLoad: Sized {
+    /// Load a value saved with the encoding version `encver`, which can be
+    /// older than the current version of the type. This is where values
+    /// saved by an older version of the module are migrated to the current
+    /// format.
+    fn rdb_load(rdb: *mut raw::RedisModuleIO, encver: i32) -> Result<Self, Error>;
+}
+
+/// An `rdb_load` callback for [raw::RedisModuleTypeMethods] loading the
+/// value with [RdbLoad::rdb_load]. The value is returned boxed, so the
+/// `free` callback of the type should drop a `Box<T>`. On error, the error
+/// is logged and the load fails.
+pub unsafe extern "C" fn rdb_load<T: RdbLoad>(
+    rdb: *mut raw::RedisModuleIO,
+    encver: c_int,
+) -> *mut c_void {
+    match T::rdb_load(rdb, encver) {
+        Ok(value) => Box::into_raw(Box::new(value)).cast::<c_void>(),
+        Err(e) => {
+            log_io_error(rdb, RedisLogLevel::Warning, &e.to_string());
+            ptr::null_mut()
+        }
+    }
 }
@@ -19,6 +19,7 @@ use libc::size_t;
 use num_traits::FromPrimitive;
 
 use crate::error::Error;
+use crate::logging::RedisLogLevel;
 pub use crate::redisraw::bindings::*;
 use crate::{context::StrCallArgs, Context, RedisString};
 use crate::{RedisBuffer, RedisError};
@@ -122,6 +123,17 @@ impl From<Status> for Result<(), &str> {
     }
 }
 
+bitflags! {
+    /// Where a command replicated with [replicate_with_flags] is propagated to.
+    #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+    pub struct ReplicationTarget: u8 {
+        /// Propagate the command to the AOF.
+        const AOF = 1 << 0;
+        /// Propagate the command to the replicas.
+        const REPLICAS = 1 << 1;
+    }
+}
+
 bitflags! {
     #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
     pub struct NotifyEvent : c_int {
@@ -317,6 +329,27 @@ pub fn call_reply_map_element(
     (key, val)
 }
 
+/// Return the attribute attached to the reply, or null if there is none.
+/// Servers without RESP3 support have no attributes.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn call_reply_attribute(reply: *mut RedisModuleCallReply) -> *mut RedisModuleCallReply {
+    unsafe { RedisModule_CallReplyAttribute.map_or(ptr::null_mut(), |f| f(reply)) }
+}
+
+/// # Panics
+///
+/// Panics if the Redis server doesn't support attributes (since RESP3).
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn call_reply_attribute_element(
+    reply: *mut RedisModuleCallReply,
+    idx: usize,
+) -> (*mut RedisModuleCallReply, *mut RedisModuleCallReply) {
+    let mut key: *mut RedisModuleCallReply = ptr::null_mut();
+    let mut val: *mut RedisModuleCallReply = ptr::null_mut();
+    unsafe { RedisModule_CallReplyAttributeElement.unwrap()(reply, idx, &mut key, &mut val) };
+    (key, val)
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn call_reply_length(reply: *mut RedisModuleCallReply) -> usize {
     unsafe { RedisModule_CallReplyLength.unwrap()(reply) }
@@ -381,30 +414,71 @@ pub fn reply_with_map(ctx: *mut RedisModuleCtx, len: c_long) -> Status {
     unsafe {
         RedisModule_ReplyWithMap
             .map_or_else(
-                || RedisModule_ReplyWithArray.unwrap()(ctx, len * 2),
+                || RedisModule_ReplyWithArray.unwrap()(ctx, flat_map_len(len)),
                 |f| f(ctx, len),
             )
             .into()
     }
 }
 
+// The length of the flat array replacing a map on servers without RESP3
+// support or for RESP2 clients, keeping the postponed length as is.
+pub(crate) fn flat_map_len(len: c_long) -> c_long {
+    if len == REDISMODULE_POSTPONED_LEN as c_long {
+        len
+    } else {
+        len * 2
+    }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn reply_set_array_length(ctx: *mut RedisModuleCtx, len: c_long) {
+    unsafe { RedisModule_ReplySetArrayLength.unwrap()(ctx, len) }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn reply_set_map_length(ctx: *mut RedisModuleCtx, len: c_long) {
+    unsafe {
+        RedisModule_ReplySetMapLength.map_or_else(
+            || RedisModule_ReplySetArrayLength.unwrap()(ctx, len * 2),
+            |f| f(ctx, len),
+        )
+    }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn reply_set_set_length(ctx: *mut RedisModuleCtx, len: c_long) {
+    unsafe {
+        RedisModule_ReplySetSetLength.map_or_else(
+            || RedisModule_ReplySetArrayLength.unwrap()(ctx, len),
+            |f| f(ctx, len),
+        )
+    }
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_set(ctx: *mut RedisModuleCtx, len: c_long) -> Status {
     unsafe {
         RedisModule_ReplyWithSet
             .map_or_else(
-                || RedisModule_ReplyWithArray.unwrap()(ctx, len * 2),
+                || RedisModule_ReplyWithArray.unwrap()(ctx, len),
                 |f| f(ctx, len),
             )
             .into()
     }
 }
 
+// Attributes have no RESP2 equivalent, so like Redis does for RESP2 clients,
+// servers without RedisModule_ReplyWithAttribute get an error and no reply
+// is emitted.
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_attribute(ctx: *mut RedisModuleCtx, len: c_long) -> Status {
-    unsafe { RedisModule_ReplyWithAttribute.unwrap()(ctx, len).into() }
+    unsafe { RedisModule_ReplyWithAttribute.map_or(Status::Err, |f| f(ctx, len).into()) }
 }
 
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -421,10 +495,18 @@ pub fn reply_with_null(ctx: *mut RedisModuleCtx) -> Status {
     unsafe { RedisModule_ReplyWithNull.unwrap()(ctx).into() }
 }
 
+// Servers without RedisModule_ReplyWithBool get the RESP2 integer reply.
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_bool(ctx: *mut RedisModuleCtx, b: c_int) -> Status {
-    unsafe { RedisModule_ReplyWithBool.unwrap()(ctx, b).into() }
+    unsafe {
+        RedisModule_ReplyWithBool
+            .map_or_else(
+                || RedisModule_ReplyWithLongLong.unwrap()(ctx, b.into()),
+                |f| f(ctx, b),
+            )
+            .into()
+    }
 }
 
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -433,10 +515,17 @@ pub fn reply_with_long_long(ctx: *mut RedisModuleCtx, ll: c_longlong) -> Status
     unsafe { RedisModule_ReplyWithLongLong.unwrap()(ctx, ll).into() }
 }
 
+// Servers without RedisModule_ReplyWithDouble get the RESP2 bulk string reply.
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_double(ctx: *mut RedisModuleCtx, f: c_double) -> Status {
-    unsafe { RedisModule_ReplyWithDouble.unwrap()(ctx, f).into() }
+    match unsafe { RedisModule_ReplyWithDouble } {
+        Some(reply) => unsafe { reply(ctx, f).into() },
+        None => {
+            let s = f.to_string();
+            reply_with_string_buffer(ctx, s.as_ptr().cast::<c_char>(), s.len())
+        }
+    }
 }
 
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -457,12 +546,18 @@ pub fn reply_with_string_buffer(ctx: *mut RedisModuleCtx, s: *const c_char, len:
     unsafe { RedisModule_ReplyWithStringBuffer.unwrap()(ctx, s, len).into() }
 }
 
+// Servers without RedisModule_ReplyWithBigNumber get the RESP2 bulk string reply.
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_big_number(ctx: *mut RedisModuleCtx, s: *const c_char, len: size_t) -> Status {
-    unsafe { RedisModule_ReplyWithBigNumber.unwrap()(ctx, s, len).into() }
+    match unsafe { RedisModule_ReplyWithBigNumber } {
+        Some(reply) => unsafe { reply(ctx, s, len).into() },
+        None => reply_with_string_buffer(ctx, s, len),
+    }
 }
 
+// Servers without RedisModule_ReplyWithVerbatimStringType get the RESP2 bulk
+// string reply, without the format.
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn reply_with_verbatim_string(
@@ -471,7 +566,10 @@ pub fn reply_with_verbatim_string(
     len: size_t,
     format: *const c_char,
 ) -> Status {
-    unsafe { RedisModule_ReplyWithVerbatimStringType.unwrap()(ctx, s, len, format).into() }
+    match unsafe { RedisModule_ReplyWithVerbatimStringType } {
+        Some(reply) => unsafe { reply(ctx, s, len, format).into() },
+        None => reply_with_string_buffer(ctx, s, len),
+    }
 }
 
 // Sets the expiry on a key.
@@ -483,6 +581,45 @@ pub fn set_expire(key: *mut RedisModuleKey, expire: c_longlong) -> Status {
     unsafe { RedisModule_SetExpire.unwrap()(key, expire).into() }
 }
 
+// Returns the remaining time to live of the key in milliseconds,
+// or REDISMODULE_NO_EXPIRE if the key has no expire.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn get_expire(key: *mut RedisModuleKey) -> c_longlong {
+    unsafe { RedisModule_GetExpire.unwrap()(key) }
+}
+
+// Sets the absolute unix time in milliseconds at which the key expires.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn set_abs_expire(key: *mut RedisModuleKey, expire: c_longlong) -> Status {
+    unsafe { RedisModule_SetAbsExpire.unwrap()(key, expire).into() }
+}
+
+// Returns the absolute unix time in milliseconds at which the key expires,
+// or REDISMODULE_NO_EXPIRE if the key has no expire.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn get_abs_expire(key: *mut RedisModuleKey) -> c_longlong {
+    unsafe { RedisModule_GetAbsExpire.unwrap()(key) }
+}
+
+// Reports a failed assertion through RedisModule__Assert, which writes a Redis
+// crash report (including the module INFO sections) and terminates the server.
+// Falls back to a Rust panic when the module API was not initialized.
+#[cold]
+pub fn assert_failed(expr: &str, file: &str, line: u32) -> ! {
+    match unsafe { RedisModule__Assert } {
+        Some(assert) => {
+            let expr = CString::new(expr.replace('\0', "")).unwrap();
+            let file = CString::new(file.replace('\0', "")).unwrap();
+            unsafe { assert(expr.as_ptr(), file.as_ptr(), line as c_int) };
+            std::process::abort()
+        }
+        None => panic!("assertion failed: {expr}, {file}:{line}"),
+    }
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn string_dma(key: *mut RedisModuleKey, len: *mut size_t, mode: KeyMode) -> *mut c_char {
@@ -600,15 +737,94 @@ pub fn hash_set(key: *mut RedisModuleKey, field: &str, value: *mut RedisModuleSt
     }
 }
 
+/// Set up to 12 fields of a hash in a single call. See [hash_get_multi] for
+/// the reason of the fixed number of fields.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn hash_set_multi(
+    key: *mut RedisModuleKey,
+    fields: &[&str],
+    values: &[*mut RedisModuleString],
+) -> Status {
+    assert_eq!(fields.len(), values.len());
+
+    let fields = match fields
+        .iter()
+        .map(|e| CString::new(*e))
+        .collect::<Result<Vec<CString>, _>>()
+    {
+        Ok(fields) => fields,
+        Err(_) => return Status::Err,
+    };
+
+    let mut fi = fields.iter();
+    let mut vi = values.iter();
+
+    macro_rules! rm {
+        () => { 0 };
+        ($($args:expr)*) => { unsafe {
+            RedisModule_HashSet.unwrap()(
+                key, REDISMODULE_HASH_CFIELDS as i32,
+                $($args),*,
+                ptr::null::<c_char>()
+            )
+        }};
+    }
+    macro_rules! f {
+        () => {
+            fi.next().unwrap().as_ptr()
+        };
+    }
+    macro_rules! v {
+        () => {
+            *vi.next().unwrap()
+        };
+    }
+
+    // The result is the number of updated fields, which is 0 both on errors
+    // and when all the fields are new, so it can not be used for the status.
+    match fields.len() {
+        0 => rm! {},
+        1 => rm! {f!() v!()},
+        2 => rm! {f!() v!() f!() v!()},
+        3 => rm! {f!() v!() f!() v!() f!() v!()},
+        4 => rm! {f!() v!() f!() v!() f!() v!() f!() v!()},
+        5 => rm! {f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()},
+        6 => rm! {f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()},
+        7 => rm! {
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+            f!() v!()
+        },
+        8 => rm! {
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+            f!() v!() f!() v!()
+        },
+        9 => rm! {
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+            f!() v!() f!() v!() f!() v!()
+        },
+        10 => rm! {
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+            f!() v!() f!() v!() f!() v!() f!() v!()
+        },
+        11 => rm! {
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+        },
+        12 => rm! {
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+            f!() v!() f!() v!() f!() v!() f!() v!() f!() v!() f!() v!()
+        },
+        _ => panic!("Unsupported length"),
+    };
+
+    Status::Ok
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn hash_del(key: *mut RedisModuleKey, field: &str) -> Status {
     let field = CString::new(field).unwrap();
 
-    // TODO: Add hash_del_multi()
-    // Support to pass multiple fields is desired but is complicated.
-    // See hash_get_multi() and https://github.com/redis/redis/issues/7860
-
     unsafe {
         RedisModule_HashSet.unwrap()(
             key,
@@ -621,6 +837,57 @@ pub fn hash_del(key: *mut RedisModuleKey, field: &str) -> Status {
     }
 }
 
+/// Delete up to 12 fields of a hash in a single call, returning the number
+/// of fields that existed and were deleted. See [hash_get_multi] for the
+/// reason of the fixed number of fields.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn hash_del_multi<T>(key: *mut RedisModuleKey, fields: &[T]) -> Result<usize, RedisError>
+where
+    T: Into<Vec<u8>> + Clone,
+{
+    let fields = fields
+        .iter()
+        .map(|e| CString::new(e.clone()))
+        .collect::<Result<Vec<CString>, _>>()?;
+
+    let mut fi = fields.iter();
+
+    macro_rules! rm {
+        () => { 0 };
+        ($($args:expr)*) => { unsafe {
+            RedisModule_HashSet.unwrap()(
+                key, REDISMODULE_HASH_CFIELDS as i32,
+                $($args, REDISMODULE_HASH_DELETE),*,
+                ptr::null::<c_char>()
+            )
+        }};
+    }
+    macro_rules! f {
+        () => {
+            fi.next().unwrap().as_ptr()
+        };
+    }
+
+    let deleted = match fields.len() {
+        0 => rm! {},
+        1 => rm! {f!()},
+        2 => rm! {f!() f!()},
+        3 => rm! {f!() f!() f!()},
+        4 => rm! {f!() f!() f!() f!()},
+        5 => rm! {f!() f!() f!() f!() f!()},
+        6 => rm! {f!() f!() f!() f!() f!() f!()},
+        7 => rm! {f!() f!() f!() f!() f!() f!() f!()},
+        8 => rm! {f!() f!() f!() f!() f!() f!() f!() f!()},
+        9 => rm! {f!() f!() f!() f!() f!() f!() f!() f!() f!()},
+        10 => rm! {f!() f!() f!() f!() f!() f!() f!() f!() f!() f!()},
+        11 => rm! {f!() f!() f!() f!() f!() f!() f!() f!() f!() f!() f!()},
+        12 => rm! {f!() f!() f!() f!() f!() f!() f!() f!() f!() f!() f!() f!()},
+        _ => panic!("Unsupported length"),
+    };
+
+    Ok(deleted as usize)
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
 pub fn list_push(
@@ -637,6 +904,63 @@ pub fn list_pop(key: *mut RedisModuleKey, list_where: Where) -> *mut RedisModule
     unsafe { RedisModule_ListPop.unwrap()(key, list_where as i32) }
 }
 
+/// Returns null if the index is out of range or the key is not a list.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn list_get(key: *mut RedisModuleKey, index: c_long) -> *mut RedisModuleString {
+    unsafe { RedisModule_ListGet.unwrap()(key, index) }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn list_set(
+    key: *mut RedisModuleKey,
+    index: c_long,
+    element: *mut RedisModuleString,
+) -> Status {
+    unsafe { RedisModule_ListSet.unwrap()(key, index, element).into() }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn list_insert(
+    key: *mut RedisModuleKey,
+    index: c_long,
+    element: *mut RedisModuleString,
+) -> Status {
+    unsafe { RedisModule_ListInsert.unwrap()(key, index, element).into() }
+}
+
+/// `flags` are the [crate::zset::ZaddFlags] of the call, and are set to the
+/// `REDISMODULE_ZADD_ADDED`, `REDISMODULE_ZADD_UPDATED` or `REDISMODULE_ZADD_NOP`
+/// result on return.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn zset_add(
+    key: *mut RedisModuleKey,
+    score: c_double,
+    element: *mut RedisModuleString,
+    flags: &mut c_int,
+) -> Status {
+    unsafe { RedisModule_ZsetAdd.unwrap()(key, score, element, flags).into() }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn zset_score(
+    key: *mut RedisModuleKey,
+    element: *mut RedisModuleString,
+    score: &mut c_double,
+) -> Status {
+    unsafe { RedisModule_ZsetScore.unwrap()(key, element, score).into() }
+}
+
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[inline]
+pub fn value_length(key: *mut RedisModuleKey) -> usize {
+    unsafe { RedisModule_ValueLength.unwrap()(key) }
+}
+
 // Returns pointer to the C string, and sets len to its length
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[inline]
@@ -736,6 +1060,46 @@ pub fn replicate<'a, T: Into<StrCallArgs<'a>>>(
     }
 }
 
+/// Same as [replicate], but only propagates the command to the given targets,
+/// using the `A` (no AOF) and `R` (no replicas) modifiers of `RedisModule_Replicate`.
+/// Fails without propagating anything if `target` is empty, as there is
+/// nowhere to propagate the command to.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn replicate_with_flags<'a, T: Into<StrCallArgs<'a>>>(
+    ctx: *mut RedisModuleCtx,
+    command: &str,
+    args: T,
+    target: ReplicationTarget,
+) -> Status {
+    if target.is_empty() {
+        return Status::Err;
+    }
+
+    let mut call_args: StrCallArgs = args.into();
+    let final_args = call_args.args_mut();
+
+    let cmd = CString::new(command).unwrap();
+    let mut fmt = String::from("v");
+    if !target.contains(ReplicationTarget::AOF) {
+        fmt.push('A');
+    }
+    if !target.contains(ReplicationTarget::REPLICAS) {
+        fmt.push('R');
+    }
+    let fmt = CString::new(fmt).unwrap();
+
+    unsafe {
+        RedisModule_Replicate.unwrap()(
+            ctx,
+            cmd.as_ptr(),
+            fmt.as_ptr(),
+            final_args.as_ptr(),
+            final_args.len(),
+        )
+        .into()
+    }
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn load_double(rdb: *mut RedisModuleIO) -> Result<f64, Error> {
     unsafe { load(rdb, |rdb| RedisModule_LoadDouble.unwrap()(rdb)) }
@@ -789,11 +1153,12 @@ pub fn string_compare(a: *mut RedisModuleString, b: *mut RedisModuleString) -> O
 }
 
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn string_append_buffer(
+pub fn string_append_buffer<T: AsRef<[u8]> + ?Sized>(
     ctx: *mut RedisModuleCtx,
     s: *mut RedisModuleString,
-    buff: &str,
+    buff: &T,
 ) -> Status {
+    let buff = buff.as_ref();
     unsafe {
         RedisModule_StringAppendBuffer.unwrap()(ctx, s, buff.as_ptr().cast::<c_char>(), buff.len())
             .into()
@@ -906,10 +1271,18 @@ pub unsafe fn notify_keyspace_event(
 /// Panics when the [RedisModule_GetNotifyKeyspaceEvents] is unavailable.
 #[must_use]
 pub fn get_keyspace_events() -> NotifyEvent {
-    unsafe {
-        let events = RedisModule_GetNotifyKeyspaceEvents.unwrap()();
-        NotifyEvent::from_bits_truncate(events)
-    }
+    NotifyEvent::from_bits_truncate(get_keyspace_events_raw())
+}
+
+/// Returns the raw `notify-keyspace-events` flags, including bits that are
+/// not known to [NotifyEvent].
+///
+/// # Panics
+///
+/// Panics when the [RedisModule_GetNotifyKeyspaceEvents] is unavailable.
+#[must_use]
+pub fn get_keyspace_events_raw() -> c_int {
+    unsafe { RedisModule_GetNotifyKeyspaceEvents.unwrap()() }
 }
 
 /// Returns all the available notification flags for key-space
@@ -930,7 +1303,7 @@ pub fn get_keyspace_notification_flags_all() -> NotifyEvent {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Version {
     pub major: i32,
     pub minor: i32,
@@ -954,10 +1327,25 @@ pub fn is_io_error(rdb: *mut RedisModuleIO) -> bool {
 }
 
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn redis_log(ctx: *mut RedisModuleCtx, msg: &str) {
-    let level = CString::new("notice").unwrap(); // FIXME reuse this
-    let msg = CString::new(msg).unwrap();
+pub fn redis_log(ctx: *mut RedisModuleCtx, level: RedisLogLevel, msg: &str) {
+    let level = CString::new(level.as_ref()).unwrap();
+    let msg = crate::logging::message_as_cstring(msg);
     unsafe {
         RedisModule_Log.unwrap()(ctx, level.as_ptr(), msg.as_ptr());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[should_panic(expected = "assertion failed: 1 + 1 == 3")]
+    fn assert_failed_panics_without_module_api() {
+        crate::redis_assert!(1 + 1 == 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: size is 2")]
+    fn assert_failed_panics_with_message_without_module_api() {
+        crate::redis_assert!(1 + 1 == 3, "size is {}", 2);
+    }
+}
@@ -9,6 +9,13 @@ pub enum RedisError {
     Str(&'static str),
     String(String),
     WrongType,
+    /// An error replied as `CODE message`, see [Context::reply_error_with_code].
+    ///
+    /// [Context::reply_error_with_code]: crate::Context::reply_error_with_code
+    WithCode {
+        code: String,
+        message: String,
+    },
 }
 
 impl<'root> From<ErrorCallReply<'root>> for RedisError {
@@ -62,8 +69,50 @@ impl fmt::Display for RedisError {
             .unwrap(),
             Self::Str(s) => s,
             Self::String(s) => s.as_str(),
+            Self::WithCode { code, message } => {
+                return write!(f, "{}", error_with_code(code, message))
+            }
         };
 
         write!(f, "{d}")
     }
 }
+
+/// The generic error code, used when the code given to [error_with_code] is
+/// not valid.
+const GENERIC_ERROR_CODE: &str = "ERR";
+
+/// Format an error reply as `CODE message`, clients take the first word of
+/// the error as its code. The code must be made of uppercase ASCII letters,
+/// digits and underscores, otherwise `ERR` is used instead.
+pub(crate) fn error_with_code(code: &str, message: &str) -> String {
+    let valid = !code.is_empty()
+        && code
+            .bytes()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == b'_');
+    let code = if valid { code } else { GENERIC_ERROR_CODE };
+    format!("{code} {message}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error_with_code, RedisError};
+
+    #[test]
+    fn format_error_with_code() {
+        assert_eq!(
+            error_with_code("NOTFOUND", "no such item"),
+            "NOTFOUND no such item"
+        );
+        assert_eq!(error_with_code("ERR_2", "x"), "ERR_2 x");
+        assert_eq!(error_with_code("", "x"), "ERR x");
+        assert_eq!(error_with_code("notfound", "x"), "ERR x");
+        assert_eq!(error_with_code("NOT FOUND", "x"), "ERR x");
+
+        let err = RedisError::WithCode {
+            code: "BUSY".to_owned(),
+            message: "try again".to_owned(),
+        };
+        assert_eq!(err.to_string(), "BUSY try again");
+    }
+}
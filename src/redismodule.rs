@@ -9,6 +9,7 @@ use std::slice;
 use std::str;
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
+use std::time::Duration;
 use std::{fmt, ptr};
 
 use serde::de::{Error, SeqAccess};
@@ -95,6 +96,87 @@ where
     }
 }
 
+/// Sorting helpers for slices of [RedisString] that agree with the
+/// ordering used by Redis native commands.
+pub trait RedisStringSliceExt {
+    /// Sort the strings by their bytes, using `RedisModule_StringCompare`.
+    fn sort_redis(&mut self);
+
+    /// Sort the strings by their numeric value, strings with equal values are
+    /// ordered by their bytes. Return an error, leaving the slice untouched,
+    /// if any of the strings is not a valid number.
+    fn sort_redis_by_numeric(&mut self) -> Result<(), RedisError>;
+}
+
+impl RedisStringSliceExt for [RedisString] {
+    fn sort_redis(&mut self) {
+        self.sort_by(|a, b| raw::string_compare(a.inner, b.inner));
+    }
+
+    fn sort_redis_by_numeric(&mut self) -> Result<(), RedisError> {
+        let scores = self
+            .iter()
+            .map(RedisString::parse_float)
+            .collect::<Result<Vec<f64>, RedisError>>()?;
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by(|a, b| {
+            scores[*a]
+                .total_cmp(&scores[*b])
+                .then_with(|| raw::string_compare(self[*a].inner, self[*b].inner))
+        });
+
+        // Move the element at `order[i]` to position `i`, following the permutation cycles.
+        for start in 0..order.len() {
+            let mut current = start;
+            while order[current] != usize::MAX {
+                let next = order[current];
+                order[current] = usize::MAX;
+                if next == start {
+                    break;
+                }
+                self.swap(current, next);
+                current = next;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// See [RedisString::parse_duration].
+fn parse_duration(s: &[u8]) -> Result<Duration, RedisError> {
+    let invalid = |reason: &str| {
+        RedisError::String(format!(
+            "Invalid duration '{}': {reason}",
+            String::from_utf8_lossy(s)
+        ))
+    };
+
+    let digits = s.iter().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return Err(invalid("expected a non negative integer"));
+    }
+    let (number, unit) = s.split_at(digits);
+    let number: u64 = str::from_utf8(number)
+        .unwrap()
+        .parse()
+        .map_err(|_| invalid("number is too large"))?;
+
+    let duration = match unit {
+        b"us" => Some(Duration::from_micros(number)),
+        b"" | b"ms" => Some(Duration::from_millis(number)),
+        b"s" => Some(Duration::from_secs(number)),
+        b"m" => number.checked_mul(60).map(Duration::from_secs),
+        b"h" => number.checked_mul(60 * 60).map(Duration::from_secs),
+        b"d" => number.checked_mul(24 * 60 * 60).map(Duration::from_secs),
+        _ => {
+            return Err(invalid(
+                "unknown unit, expected one of us, ms, s, m, h or d",
+            ))
+        }
+    };
+    duration.ok_or_else(|| invalid("number is too large"))
+}
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn decode_args(
     ctx: *mut raw::RedisModuleCtx,
@@ -183,10 +265,33 @@ impl RedisString {
         str::from_utf8(Self::string_as_slice(ptr))
     }
 
+    /// Create a [RedisString] from format arguments, writing them directly
+    /// into the underlying `RedisModuleString` buffer without allocating an
+    /// intermediate [String]. Usually used through the [crate::rformat!] macro.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if appending to the underlying string fails
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn from_format(ctx: Option<NonNull<raw::RedisModuleCtx>>, args: fmt::Arguments) -> Self {
+        let ctx = ctx.map_or(std::ptr::null_mut(), |v| v.as_ptr());
+        let mut s = Self::create_from_slice(ctx, args.as_str().unwrap_or("").as_bytes());
+        if args.as_str().is_none() {
+            fmt::write(&mut s, args).expect("Failed formatting into a RedisString");
+        }
+        s
+    }
+
     pub fn append(&mut self, s: &str) -> raw::Status {
         raw::string_append_buffer(self.ctx, self.inner, s)
     }
 
+    /// Append bytes which do not have to be valid UTF-8, the counterpart of
+    /// [RedisString::create_from_slice].
+    pub fn append_bytes(&mut self, data: &[u8]) -> raw::Status {
+        raw::string_append_buffer(self.ctx, self.inner, data)
+    }
+
     #[must_use]
     pub fn len(&self) -> usize {
         let mut len: usize = 0;
@@ -201,6 +306,13 @@ impl RedisString {
         len == 0
     }
 
+    /// Return the memory allocated for the string, as reported by the Redis
+    /// allocator, or `0` if the server does not expose `RedisModule_MallocSizeString`.
+    #[must_use]
+    pub fn malloc_size(&self) -> usize {
+        unsafe { raw::RedisModule_MallocSizeString }.map_or(0, |f| unsafe { f(self.inner) })
+    }
+
     pub fn try_as_str<'a>(&self) -> Result<&'a str, RedisError> {
         Self::from_ptr(self.inner).map_err(|_| RedisError::Str("Couldn't parse as UTF-8 string"))
     }
@@ -230,20 +342,53 @@ impl RedisString {
         String::from_utf8_lossy(self.as_slice()).into_owned()
     }
 
+    /// Parse the string as a `u64`, see [RedisString::parse_integer].
     pub fn parse_unsigned_integer(&self) -> Result<u64, RedisError> {
-        let val = self.parse_integer()?;
-        u64::try_from(val)
-            .map_err(|_| RedisError::Str("Couldn't parse negative number as unsigned integer"))
+        let val = self.parse_signed_integer()?;
+        u64::try_from(val).map_err(|_| {
+            RedisError::String(format!(
+                "Couldn't parse negative number '{val}' as unsigned integer"
+            ))
+        })
     }
 
-    pub fn parse_integer(&self) -> Result<i64, RedisError> {
+    /// Parse the string as an `i64` with `RedisModule_StringToLongLong`, which
+    /// does not allocate and does not accept leading or trailing spaces.
+    pub fn parse_signed_integer(&self) -> Result<i64, RedisError> {
         let mut val: i64 = 0;
         match raw::string_to_longlong(self.inner, &mut val) {
             raw::Status::Ok => Ok(val),
-            raw::Status::Err => Err(RedisError::Str("Couldn't parse as integer")),
+            raw::Status::Err => Err(RedisError::String(format!(
+                "Couldn't parse '{}' as integer",
+                self.to_string_lossy()
+            ))),
         }
     }
 
+    /// Parse the string as any integer type which can be converted from an
+    /// `i64`, for example `i32`, `u16` or `usize`. Values out of the range of
+    /// `T` are an error.
+    ///
+    /// ```ignore
+    /// let count: usize = args.next_arg()?.parse_integer()?;
+    /// ```
+    pub fn parse_integer<T: TryFrom<i64>>(&self) -> Result<T, RedisError> {
+        let val = self.parse_signed_integer()?;
+        T::try_from(val).map_err(|_| {
+            RedisError::String(format!(
+                "Integer '{val}' is out of range for {}",
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+
+    /// Parse the string as a duration, a non negative integer followed by
+    /// one of the units `us`, `ms`, `s`, `m`, `h` or `d`, for example `100ms`
+    /// or `10s`. A number without a unit is in milliseconds.
+    pub fn parse_duration(&self) -> Result<Duration, RedisError> {
+        parse_duration(self.as_slice())
+    }
+
     pub fn parse_float(&self) -> Result<f64, RedisError> {
         let mut val: f64 = 0.0;
         match raw::string_to_double(self.inner, &mut val) {
@@ -301,6 +446,15 @@ impl Display for RedisString {
     }
 }
 
+impl fmt::Write for RedisString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.append(s) {
+            raw::Status::Ok => Ok(()),
+            raw::Status::Err => Err(fmt::Error),
+        }
+    }
+}
+
 impl Borrow<str> for RedisString {
     fn borrow(&self) -> &str {
         // RedisString might not be UTF-8 safe
@@ -422,3 +576,46 @@ impl Drop for RedisBuffer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+    use std::time::Duration;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration(b"100").unwrap(), Duration::from_millis(100));
+        assert_eq!(
+            parse_duration(b"100ms").unwrap(),
+            Duration::from_millis(100)
+        );
+        assert_eq!(parse_duration(b"5us").unwrap(), Duration::from_micros(5));
+        assert_eq!(parse_duration(b"10s").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration(b"2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration(b"1h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_duration(b"1d").unwrap(), Duration::from_secs(86400));
+        assert_eq!(parse_duration(b"0s").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_duration_invalid() {
+        for s in [
+            &b""[..],
+            b"ms",
+            b"-1s",
+            b" 1s",
+            b"1.5s",
+            b"10w",
+            b"10 s",
+            b"10S",
+        ] {
+            assert!(parse_duration(s).is_err(), "{s:?}");
+        }
+        assert!(parse_duration(b"99999999999999999999").is_err());
+        assert!(parse_duration(b"999999999999999999d").is_err());
+        assert_eq!(
+            parse_duration(b"10w").unwrap_err().to_string(),
+            "Invalid duration '10w': unknown unit, expected one of us, ms, s, m, h or d"
+        );
+    }
+}
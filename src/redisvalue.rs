@@ -3,8 +3,10 @@ use crate::{
     CallReply, RedisError, RedisString,
 };
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-    hash::Hash,
+    hash::{Hash, Hasher},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
@@ -14,6 +16,88 @@ pub enum RedisValueKey {
     BulkRedisString(RedisString),
     BulkString(Vec<u8>),
     Bool(bool),
+    Double(DoubleKey),
+}
+
+/// How a [DoubleKey] is rendered for RESP2 clients, where map keys
+/// are sent as bulk strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DoubleKeyFormat {
+    /// The shortest representation that round-trips to the same value.
+    #[default]
+    Shortest,
+    /// A fixed number of digits after the decimal point.
+    Fixed(usize),
+}
+
+/// A double usable as a map key or a set element. RESP3 clients receive
+/// a native double, RESP2 clients a bulk string formatted according to
+/// [DoubleKeyFormat].
+///
+/// Keys are compared, ordered and hashed by their value only (using
+/// [f64::total_cmp]), the format does not take part in it.
+#[derive(Debug, Clone, Copy)]
+pub struct DoubleKey {
+    value: f64,
+    format: DoubleKeyFormat,
+}
+
+impl DoubleKey {
+    pub fn new(value: f64) -> Self {
+        Self::with_format(value, DoubleKeyFormat::default())
+    }
+
+    pub fn with_format(value: f64, format: DoubleKeyFormat) -> Self {
+        Self { value, format }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn format(&self) -> DoubleKeyFormat {
+        self.format
+    }
+
+    /// The bulk string sent to RESP2 clients.
+    pub fn to_resp2_string(&self) -> String {
+        if self.value.is_nan() {
+            return "nan".to_owned();
+        }
+        if self.value.is_infinite() {
+            return if self.value > 0.0 { "inf" } else { "-inf" }.to_owned();
+        }
+        match self.format {
+            DoubleKeyFormat::Shortest => self.value.to_string(),
+            DoubleKeyFormat::Fixed(precision) => format!("{:.*}", precision, self.value),
+        }
+    }
+}
+
+impl PartialEq for DoubleKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DoubleKey {}
+
+impl PartialOrd for DoubleKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DoubleKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.total_cmp(&other.value)
+    }
+}
+
+impl Hash for DoubleKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.to_bits().hash(state);
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -30,14 +114,31 @@ pub enum RedisValue {
     VerbatimString((VerbatimStringFormat, Vec<u8>)),
     Array(Vec<RedisValue>),
     StaticError(&'static str),
+    /// An error reply, also valid as an element of an aggregate reply
+    /// (for example, a failed command inside an `EXEC` result).
+    Error(String),
     Map(HashMap<RedisValueKey, RedisValue>),
     Set(HashSet<RedisValueKey>),
     OrderedMap(BTreeMap<RedisValueKey, RedisValue>),
+    /// A map replied in the order of its entries, which are not deduplicated.
+    InsertionOrderedMap(Vec<(RedisValueKey, RedisValue)>),
     OrderedSet(BTreeSet<RedisValueKey>),
     Null,
     NoReply, // No reply at all (as opposed to a Null reply)
 }
 
+impl RedisValue {
+    /// Create a [RedisValue::Array] from any iterator of items that can be
+    /// converted into a [RedisValue].
+    pub fn array_from<I>(iter: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<RedisValue>,
+    {
+        Self::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
 impl TryFrom<RedisValue> for String {
     type Error = RedisError;
     fn try_from(val: RedisValue) -> Result<Self, RedisError> {
@@ -94,6 +195,18 @@ impl From<bool> for RedisValueKey {
     }
 }
 
+impl From<DoubleKey> for RedisValueKey {
+    fn from(d: DoubleKey) -> Self {
+        Self::Double(d)
+    }
+}
+
+impl From<f64> for RedisValueKey {
+    fn from(f: f64) -> Self {
+        DoubleKey::new(f).into()
+    }
+}
+
 impl From<()> for RedisValue {
     fn from(_: ()) -> Self {
         Self::Null
@@ -124,6 +237,25 @@ impl From<f64> for RedisValue {
     }
 }
 
+/// Converts a [Duration] into an integer number of milliseconds,
+/// saturating at [i64::MAX].
+impl From<Duration> for RedisValue {
+    fn from(d: Duration) -> Self {
+        Self::Integer(i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+    }
+}
+
+/// Converts a [SystemTime] into an integer unix time in milliseconds.
+/// Times before the unix epoch are negative.
+impl From<SystemTime> for RedisValue {
+    fn from(t: SystemTime) -> Self {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.into(),
+            Err(e) => Self::Integer(-i64::try_from(e.duration().as_millis()).unwrap_or(i64::MAX)),
+        }
+    }
+}
+
 impl From<String> for RedisValue {
     fn from(s: String) -> Self {
         Self::BulkString(s)
@@ -160,6 +292,21 @@ impl From<&String> for RedisValue {
     }
 }
 
+/// `None` is replied as a null, so a command returning an optional value,
+/// like `GET`, does not have to match on it:
+///
+/// ```
+/// use redis_module::{Context, NextArg, RedisResult, RedisString, RedisValue};
+///
+/// fn get(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+///     let mut args = args.into_iter().skip(1);
+///     let key = ctx.open_key(&args.next_arg()?);
+///     Ok(key.read()?.map(<[u8]>::to_vec).into())
+/// }
+///
+/// assert_eq!(RedisValue::from(None::<i64>), RedisValue::Null);
+/// assert_eq!(RedisValue::from(Some(1_i64)), RedisValue::Integer(1));
+/// ```
 impl<T: Into<Self>> From<Option<T>> for RedisValue {
     fn from(s: Option<T>) -> Self {
         s.map_or(Self::Null, Into::into)
@@ -206,6 +353,27 @@ impl<K: Into<RedisValueKey>> From<BTreeSet<K>> for RedisValue {
     }
 }
 
+/// Implements [From] for tuples of values convertible into [RedisValue],
+/// producing a [RedisValue::Array] with one element per tuple field.
+macro_rules! tuple_into_redis_value {
+    ($($name:ident),+) => {
+        impl<$($name: Into<RedisValue>),+> From<($($name,)+)> for RedisValue {
+            #[allow(non_snake_case)]
+            fn from(($($name,)+): ($($name,)+)) -> Self {
+                Self::Array(vec![$($name.into()),+])
+            }
+        }
+    };
+}
+
+tuple_into_redis_value!(A, B);
+tuple_into_redis_value!(A, B, C);
+tuple_into_redis_value!(A, B, C, D);
+tuple_into_redis_value!(A, B, C, D, E);
+tuple_into_redis_value!(A, B, C, D, E, F);
+tuple_into_redis_value!(A, B, C, D, E, F, G);
+tuple_into_redis_value!(A, B, C, D, E, F, G, H);
+
 impl<'root> TryFrom<&CallReply<'root>> for RedisValueKey {
     type Error = RedisError;
     fn try_from(reply: &CallReply<'root>) -> Result<Self, Self::Error> {
@@ -217,6 +385,8 @@ impl<'root> TryFrom<&CallReply<'root>> for RedisValueKey {
                     RedisValueKey::String(v)
                 })),
             CallReply::Bool(b) => Ok(RedisValueKey::Bool(b.to_bool())),
+            CallReply::Double(d) => Ok(RedisValueKey::Double(DoubleKey::new(d.to_double()))),
+            CallReply::Attribute(reply) => reply.value().try_into(),
             _ => Err(RedisError::String(format!(
                 "Given CallReply can not be used as a map key or a set element, {:?}",
                 reply
@@ -225,60 +395,119 @@ impl<'root> TryFrom<&CallReply<'root>> for RedisValueKey {
     }
 }
 
-impl<'root> From<&CallReply<'root>> for RedisValue {
-    fn from(reply: &CallReply<'root>) -> Self {
+/// The default maximum nesting depth used when converting a [CallReply] into a
+/// [RedisValue] and when replying with a [RedisValue].
+pub const DEFAULT_MAX_REPLY_DEPTH: usize = 512;
+
+const MAX_REPLY_DEPTH_ERROR: &str = "Reply nesting exceeds the maximum allowed depth";
+
+impl RedisValue {
+    /// Convert a [CallReply] into a [RedisValue], failing with an error if
+    /// the reply is nested deeper than `max_depth` aggregates (arrays, maps
+    /// and sets). Use this when the reply comes from an untrusted source,
+    /// to avoid overflowing the stack.
+    pub fn try_from_call_reply(
+        reply: &CallReply<'_>,
+        max_depth: usize,
+    ) -> Result<RedisValue, RedisError> {
+        Self::from_call_reply_with_depth(reply, max_depth)
+            .ok_or(RedisError::Str(MAX_REPLY_DEPTH_ERROR))
+    }
+
+    /// Same as [RedisValue::try_from_call_reply] but for a [CallResult].
+    pub fn try_from_call_result(
+        reply: &CallResult<'_>,
+        max_depth: usize,
+    ) -> Result<RedisValue, RedisError> {
+        Self::from_call_result_with_depth(reply, max_depth)
+            .ok_or(RedisError::Str(MAX_REPLY_DEPTH_ERROR))
+    }
+
+    fn from_call_result_with_depth(reply: &CallResult<'_>, depth: usize) -> Option<RedisValue> {
         match reply {
+            // Error replies are converted into the bytes of the error message
+            // rather than [RedisValue::Error], for backward compatibility.
+            Err(e) => Some(RedisValue::StringBuffer(e.as_bytes().to_vec())),
+            Ok(v) => Self::from_call_reply_with_depth(v, depth),
+        }
+    }
+
+    fn from_call_reply_with_depth(reply: &CallReply<'_>, depth: usize) -> Option<RedisValue> {
+        let res = match reply {
             CallReply::Unknown => RedisValue::StaticError("Error on method call"),
             CallReply::Array(reply) => {
-                RedisValue::Array(reply.iter().map(|v| (&v).into()).collect())
+                let depth = depth.checked_sub(1)?;
+                RedisValue::Array(
+                    reply
+                        .iter()
+                        .map(|v| Self::from_call_result_with_depth(&v, depth))
+                        .collect::<Option<_>>()?,
+                )
             }
             CallReply::I64(reply) => RedisValue::Integer(reply.to_i64()),
             CallReply::String(reply) => RedisValue::SimpleString(reply.to_string().unwrap()),
             CallReply::Null(_) => RedisValue::Null,
-            CallReply::Map(reply) => RedisValue::Map(
-                reply
-                    .iter()
-                    .map(|(key, val)| {
-                        (
-                            (&key).try_into().unwrap_or_else(|e| {
-                                panic!("Got unhashable map key from Redis, {key:?}, {e}")
-                            }),
-                            (&val).into(),
-                        )
-                    })
-                    .collect(),
-            ),
-            CallReply::Set(reply) => RedisValue::Set(
-                reply
-                    .iter()
-                    .map(|v| {
-                        (&v).try_into().unwrap_or_else(|e| {
-                            panic!("Got unhashable set element from Redis, {v:?}, {e}")
+            CallReply::Map(reply) => {
+                let depth = depth.checked_sub(1)?;
+                RedisValue::Map(
+                    reply
+                        .iter()
+                        .map(|(key, val)| {
+                            Some((
+                                (&key).try_into().unwrap_or_else(|e| {
+                                    panic!("Got unhashable map key from Redis, {key:?}, {e}")
+                                }),
+                                Self::from_call_result_with_depth(&val, depth)?,
+                            ))
+                        })
+                        .collect::<Option<_>>()?,
+                )
+            }
+            CallReply::Set(reply) => {
+                depth.checked_sub(1)?;
+                RedisValue::Set(
+                    reply
+                        .iter()
+                        .map(|v| {
+                            (&v).try_into().unwrap_or_else(|e| {
+                                panic!("Got unhashable set element from Redis, {v:?}, {e}")
+                            })
                         })
-                    })
-                    .collect(),
-            ),
+                        .collect(),
+                )
+            }
             CallReply::Bool(reply) => RedisValue::Bool(reply.to_bool()),
             CallReply::Double(reply) => RedisValue::Float(reply.to_double()),
             CallReply::BigNumber(reply) => RedisValue::BigNumber(reply.to_string().unwrap()),
             CallReply::VerbatimString(reply) => {
                 RedisValue::VerbatimString(reply.to_parts().unwrap())
             }
-        }
+            // [RedisValue] has no attributes, only the value is kept.
+            CallReply::Attribute(reply) => {
+                return Self::from_call_reply_with_depth(reply.value(), depth)
+            }
+        };
+        Some(res)
+    }
+}
+
+/// Replies nested deeper than [DEFAULT_MAX_REPLY_DEPTH] are converted into
+/// a [RedisValue::StaticError], use [RedisValue::try_from_call_reply] to
+/// control the depth.
+impl<'root> From<&CallReply<'root>> for RedisValue {
+    fn from(reply: &CallReply<'root>) -> Self {
+        Self::from_call_reply_with_depth(reply, DEFAULT_MAX_REPLY_DEPTH)
+            .unwrap_or(RedisValue::StaticError(MAX_REPLY_DEPTH_ERROR))
     }
 }
 
+/// Replies nested deeper than [DEFAULT_MAX_REPLY_DEPTH] are converted into
+/// a [RedisValue::StaticError], use [RedisValue::try_from_call_result] to
+/// control the depth.
 impl<'root> From<&CallResult<'root>> for RedisValue {
     fn from(reply: &CallResult<'root>) -> Self {
-        reply.as_ref().map_or_else(
-            |e| {
-                // [RedisValue] does not support error, we can change that but to avoid
-                // drastic changes and try to keep backword compatability, currently
-                // we will stansform the error into a String buffer.
-                RedisValue::StringBuffer(e.as_bytes().to_vec())
-            },
-            |v| (v).into(),
-        )
+        Self::from_call_result_with_depth(reply, DEFAULT_MAX_REPLY_DEPTH)
+            .unwrap_or(RedisValue::StaticError(MAX_REPLY_DEPTH_ERROR))
     }
 }
 
@@ -300,7 +529,8 @@ impl<'root> TryFrom<&CallResult<'root>> for RedisValueKey {
 
 #[cfg(test)]
 mod tests {
-    use super::RedisValue;
+    use super::{DoubleKey, DoubleKeyFormat, RedisValue, RedisValueKey};
+    use std::time::{Duration, UNIX_EPOCH};
 
     #[test]
     fn from_vec_string() {
@@ -343,8 +573,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn array_from_iter() {
+        assert_eq!(
+            RedisValue::array_from((1..=3).map(|i: i64| i * 2)),
+            RedisValue::Array(vec![
+                RedisValue::Integer(2),
+                RedisValue::Integer(4),
+                RedisValue::Integer(6)
+            ])
+        );
+    }
+
+    #[test]
+    fn array_from_empty() {
+        assert_eq!(
+            RedisValue::array_from(Vec::<String>::new()),
+            RedisValue::Array(vec![])
+        );
+    }
+
     #[test]
     fn from_option_none() {
         assert_eq!(RedisValue::from(None::<()>), RedisValue::Null,);
     }
+
+    #[test]
+    fn from_duration() {
+        assert_eq!(
+            RedisValue::from(Duration::from_micros(1_500_700)),
+            RedisValue::Integer(1500)
+        );
+        assert_eq!(
+            RedisValue::from(Duration::MAX),
+            RedisValue::Integer(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn from_system_time() {
+        assert_eq!(
+            RedisValue::from(UNIX_EPOCH + Duration::from_secs(2)),
+            RedisValue::Integer(2000)
+        );
+        assert_eq!(
+            RedisValue::from(UNIX_EPOCH - Duration::from_millis(5)),
+            RedisValue::Integer(-5)
+        );
+    }
+
+    #[test]
+    fn double_key_resp2_format() {
+        assert_eq!(DoubleKey::new(1.5).to_resp2_string(), "1.5");
+        assert_eq!(
+            DoubleKey::new(0.1 + 0.2).to_resp2_string(),
+            "0.30000000000000004"
+        );
+        assert_eq!(
+            DoubleKey::with_format(1.5, DoubleKeyFormat::Fixed(2)).to_resp2_string(),
+            "1.50"
+        );
+        assert_eq!(
+            DoubleKey::with_format(f64::NEG_INFINITY, DoubleKeyFormat::Fixed(2)).to_resp2_string(),
+            "-inf"
+        );
+    }
+
+    #[test]
+    fn double_key_ordering() {
+        let mut keys = vec![
+            RedisValueKey::from(2.5),
+            RedisValueKey::from(-1.0),
+            RedisValueKey::from(f64::INFINITY),
+        ];
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                RedisValueKey::from(-1.0),
+                RedisValueKey::from(2.5),
+                RedisValueKey::from(f64::INFINITY)
+            ]
+        );
+        assert_eq!(
+            DoubleKey::new(1.0),
+            DoubleKey::with_format(1.0, DoubleKeyFormat::Fixed(3))
+        );
+    }
+
+    #[test]
+    fn from_tuple() {
+        assert_eq!(
+            RedisValue::from((1i64, "a".to_string(), 1.5)),
+            RedisValue::Array(vec![
+                RedisValue::Integer(1),
+                RedisValue::BulkString("a".to_owned()),
+                RedisValue::Float(1.5)
+            ])
+        );
+        assert_eq!(
+            RedisValue::from(((), vec![1i64, 2])),
+            RedisValue::Array(vec![
+                RedisValue::Null,
+                RedisValue::Array(vec![RedisValue::Integer(1), RedisValue::Integer(2)])
+            ])
+        );
+    }
 }
@@ -3,8 +3,102 @@ use crate::raw;
 use crate::RedisError;
 use crate::RedisString;
 use crate::Status;
+use std::fmt;
 use std::os::raw::c_long;
 use std::ptr;
+use std::str::FromStr;
+
+/// The id of a stream entry, made of a milliseconds time and a sequence
+/// number, written `ms-seq` like `1526919030474-55`. Ids are ordered the same
+/// way their entries are ordered in the stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    ms: u64,
+    seq: u64,
+}
+
+impl StreamId {
+    /// The smallest id, like the special `-` id of `XRANGE`.
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    /// The greatest id, like the special `+` id of `XRANGE`.
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub const fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+
+    pub const fn ms(&self) -> u64 {
+        self.ms
+    }
+
+    pub const fn seq(&self) -> u64 {
+        self.seq
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = RedisError;
+
+    /// Parse an id in the `ms-seq` form, or in the `ms` form where the
+    /// sequence number is `0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ms, seq) = s.split_once('-').unwrap_or((s, "0"));
+        let parse = |part: &str| {
+            // `u64::from_str` also accepts a leading `+`.
+            part.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| part.parse().ok())
+                .flatten()
+        };
+        match (parse(ms), parse(seq)) {
+            (Some(ms), Some(seq)) => Ok(Self { ms, seq }),
+            _ => Err(RedisError::String(format!("Invalid stream id '{s}'"))),
+        }
+    }
+}
+
+impl From<raw::RedisModuleStreamID> for StreamId {
+    fn from(id: raw::RedisModuleStreamID) -> Self {
+        Self {
+            ms: id.ms,
+            seq: id.seq,
+        }
+    }
+}
+
+impl From<StreamId> for raw::RedisModuleStreamID {
+    fn from(id: StreamId) -> Self {
+        Self {
+            ms: id.ms,
+            seq: id.seq,
+        }
+    }
+}
+
+/// The id of an entry added with [crate::key::RedisKeyWritable::stream_add].
+#[derive(Debug, Clone, Copy)]
+pub enum StreamAddOption {
+    /// Let Redis assign the next id, like `XADD key * ...`.
+    AutoId,
+    /// Add the entry with the given id, which must be greater than the id of
+    /// the last entry of the stream.
+    Id(StreamId),
+}
+
+impl From<StreamId> for StreamAddOption {
+    fn from(id: StreamId) -> Self {
+        Self::Id(id)
+    }
+}
 
 #[derive(Debug)]
 pub struct StreamRecord {
@@ -98,3 +192,37 @@ impl<'key> Drop for StreamIterator<'key> {
         unsafe { raw::RedisModule_StreamIteratorDelete.unwrap()(self.key.key_inner) };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::StreamId;
+
+    #[test]
+    fn parse_display() {
+        let id: StreamId = "1526919030474-55".parse().unwrap();
+        assert_eq!((id.ms(), id.seq()), (1526919030474, 55));
+        assert_eq!(id.to_string(), "1526919030474-55");
+        assert_eq!(
+            "1526919030474".parse::<StreamId>().unwrap(),
+            StreamId::new(1526919030474, 0)
+        );
+        assert!(StreamId::new(1, 2) < StreamId::new(2, 0));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for id in [
+            "",
+            "-",
+            "1-",
+            "-1",
+            "1-2-3",
+            "+1",
+            "1-+2",
+            "a-1",
+            "18446744073709551616",
+        ] {
+            assert!(id.parse::<StreamId>().is_err(), "{id}");
+        }
+    }
+}
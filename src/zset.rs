@@ -0,0 +1,152 @@
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use std::ptr;
+
+use bitflags::bitflags;
+
+use crate::raw;
+use crate::RedisError;
+use crate::RedisString;
+use crate::Status;
+
+bitflags! {
+    /// The conditions of [crate::key::RedisKeyWritable::zset_add], like the
+    /// options of `ZADD`.
+    pub struct ZaddFlags: c_int {
+        /// Only update the score of existing members.
+        const XX = raw::REDISMODULE_ZADD_XX as c_int;
+        /// Only add new members.
+        const NX = raw::REDISMODULE_ZADD_NX as c_int;
+        /// Only update the score of existing members if the new score is greater.
+        const GT = raw::REDISMODULE_ZADD_GT as c_int;
+        /// Only update the score of existing members if the new score is less.
+        const LT = raw::REDISMODULE_ZADD_LT as c_int;
+    }
+}
+
+/// An iterator over the members of a sorted set within a score or a
+/// lexicographical range, yielding each member along with its score. Redis
+/// keeps the range on the key, so only one range may be iterated on a key at
+/// a time, and the sorted set must not be modified while iterating. The range
+/// is stopped once the iterator is dropped.
+pub struct ZsetRangeIterator<'key> {
+    ctx: *mut raw::RedisModuleCtx,
+    // Null for the empty range of a key which does not exist.
+    key_inner: *mut raw::RedisModuleKey,
+    reverse: bool,
+    _key: PhantomData<&'key ()>,
+}
+
+impl<'key> ZsetRangeIterator<'key> {
+    pub(crate) fn empty() -> Self {
+        Self {
+            ctx: ptr::null_mut(),
+            key_inner: ptr::null_mut(),
+            reverse: false,
+            _key: PhantomData,
+        }
+    }
+
+    /// Start iterating the members whose score is between `min` and `max`,
+    /// excluding both bounds if `exclusive` is set. Reverse iteration starts
+    /// from the highest score.
+    pub(crate) fn by_score(
+        ctx: *mut raw::RedisModuleCtx,
+        key_inner: *mut raw::RedisModuleKey,
+        min: f64,
+        max: f64,
+        exclusive: bool,
+        reverse: bool,
+    ) -> Result<Self, RedisError> {
+        let exclusive = c_int::from(exclusive);
+        let res = unsafe {
+            if reverse {
+                raw::RedisModule_ZsetLastInScoreRange.unwrap()(
+                    key_inner, min, max, exclusive, exclusive,
+                )
+            } else {
+                raw::RedisModule_ZsetFirstInScoreRange.unwrap()(
+                    key_inner, min, max, exclusive, exclusive,
+                )
+            }
+        };
+        Self::start(ctx, key_inner, res.into(), reverse)
+    }
+
+    /// Start iterating the members between `min` and `max`, given in the
+    /// `ZRANGEBYLEX` format (`[a`, `(a`, `-` or `+`). The members of the
+    /// sorted set should all have the same score. Reverse iteration starts
+    /// from the last member.
+    pub(crate) fn by_lex(
+        ctx: *mut raw::RedisModuleCtx,
+        key_inner: *mut raw::RedisModuleKey,
+        min: &RedisString,
+        max: &RedisString,
+        reverse: bool,
+    ) -> Result<Self, RedisError> {
+        let res = unsafe {
+            if reverse {
+                raw::RedisModule_ZsetLastInLexRange.unwrap()(key_inner, min.inner, max.inner)
+            } else {
+                raw::RedisModule_ZsetFirstInLexRange.unwrap()(key_inner, min.inner, max.inner)
+            }
+        };
+        Self::start(ctx, key_inner, res.into(), reverse)
+    }
+
+    fn start(
+        ctx: *mut raw::RedisModuleCtx,
+        key_inner: *mut raw::RedisModuleKey,
+        res: Status,
+        reverse: bool,
+    ) -> Result<Self, RedisError> {
+        if res == Status::Err {
+            return Err(RedisError::Str("Failed creating sorted set range iterator"));
+        }
+        Ok(Self {
+            ctx,
+            key_inner,
+            reverse,
+            _key: PhantomData,
+        })
+    }
+}
+
+impl<'key> Iterator for ZsetRangeIterator<'key> {
+    type Item = (RedisString, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.key_inner.is_null()
+            || unsafe { raw::RedisModule_ZsetRangeEndReached.unwrap()(self.key_inner) } != 0
+        {
+            return None;
+        }
+        let mut score = 0.0;
+        let member = unsafe {
+            raw::RedisModule_ZsetRangeCurrentElement.unwrap()(self.key_inner, &mut score)
+        };
+        if member.is_null() {
+            return None;
+        }
+        unsafe {
+            if self.reverse {
+                raw::RedisModule_ZsetRangePrev.unwrap()(self.key_inner);
+            } else {
+                raw::RedisModule_ZsetRangeNext.unwrap()(self.key_inner);
+            }
+        }
+        // The current element is a new string, owned by the returned [RedisString].
+        Some((
+            RedisString::from_redis_module_string(self.ctx, member),
+            score,
+        ))
+    }
+}
+
+impl<'key> Drop for ZsetRangeIterator<'key> {
+    fn drop(&mut self) {
+        if !self.key_inner.is_null() {
+            unsafe { raw::RedisModule_ZsetRangeStop.unwrap()(self.key_inner) };
+        }
+    }
+}
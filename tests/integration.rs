@@ -3,7 +3,9 @@ use std::thread;
 use std::time::Duration;
 use std::time::SystemTime;
 
-use crate::utils::{get_redis_connection, start_redis_server_with_module, TestConnection};
+use crate::utils::{
+    get_redis_connection, start_redis_server_with_module, Resp3Value, TestConnection,
+};
 use anyhow::Context;
 use anyhow::Result;
 use redis::{RedisError, RedisResult, Value};
@@ -26,6 +28,50 @@ fn test_hello() -> Result<()> {
     if res.is_ok() {
         return Err(anyhow::Error::msg("Should return an error"));
     }
+    // The error names the argument which is not an integer.
+    assert!(res.unwrap_err().to_string().contains("'xx'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_replace_command_handler() -> Result<()> {
+    let mut con = TestConnection::new("hello");
+
+    let _: String = redis::cmd("hello.swap")
+        .arg(&["sum"])
+        .query(&mut con)
+        .with_context(|| "failed to run hello.swap")?;
+
+    let res: Vec<i32> = redis::cmd("hello.mul")
+        .arg(&[3, 4])
+        .query(&mut con)
+        .with_context(|| "failed to run hello.mul")?;
+    assert_eq!(res, vec![3, 4, 7]);
+
+    let _: String = redis::cmd("hello.swap")
+        .arg(&["mul"])
+        .query(&mut con)
+        .with_context(|| "failed to run hello.swap")?;
+
+    let res: Vec<i32> = redis::cmd("hello.mul")
+        .arg(&[3, 4])
+        .query(&mut con)
+        .with_context(|| "failed to run hello.mul")?;
+    assert_eq!(res, vec![3, 4, 12]);
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_api() -> Result<()> {
+    let mut con = TestConnection::new("hello");
+
+    let res: i64 = redis::cmd("hello.shared_add")
+        .arg(&[3, 4])
+        .query(&mut con)
+        .with_context(|| "failed to run hello.shared_add")?;
+    assert_eq!(res, 7);
 
     Ok(())
 }
@@ -64,6 +110,27 @@ fn test_helper_version() -> Result<()> {
         .with_context(|| "failed to run test_helper._version_rm_call")?;
     assert_eq!(res, res2);
 
+    // The cached version stays the same across calls.
+    for _ in 0..2 {
+        let res3: Vec<i64> = redis::cmd("test_helper.version_cached")
+            .query(&mut con)
+            .with_context(|| "failed to run test_helper.version_cached")?;
+        assert_eq!(res, res3);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_log_message_with_nulls() -> Result<()> {
+    let mut con = TestConnection::new("test_helper");
+
+    let res: String = redis::cmd("test_helper.log")
+        .arg(b"null\0in\0message".as_slice())
+        .query(&mut con)
+        .with_context(|| "failed to run test_helper.log")?;
+    assert_eq!(res, "OK");
+
     Ok(())
 }
 
@@ -126,6 +193,22 @@ fn test_helper_info() -> Result<()> {
         })
 }
 
+#[test]
+fn test_info_handler_builder_values() -> Result<()> {
+    let mut con = TestConnection::new("info_handler_builder");
+
+    let res: String = redis::cmd("INFO")
+        .arg("info_handler_builder")
+        .query(&mut con)
+        .with_context(|| "failed to run INFO info_handler_builder")?;
+
+    // The fields of the sub-dictionary are flattened into the dictionary.
+    assert!(res.contains("dictionary:key=value,sub.a=1,sub.b=two"));
+    assert!(res.contains("info_handler_builder_latency:p50=1.000,p99.9=2.500"));
+
+    Ok(())
+}
+
 #[test]
 fn test_info_handler_multiple_sections() -> Result<()> {
     const MODULES: [&str; 1] = ["info_handler_multiple_sections"];
@@ -141,6 +224,14 @@ fn test_info_handler_multiple_sections() -> Result<()> {
         assert!(res.contains(&format!("{module}_field_2:value2")));
         assert!(!res.contains(&format!("{module}_field_1:value1")));
 
+        // The expensive section was not built for the previous INFO.
+        let res: String = redis::cmd("INFO")
+            .arg(format!("{module}_expensive"))
+            .query(&mut con)
+            .with_context(|| format!("failed to run INFO {module}"))?;
+        assert!(res.contains(&format!("{module}_computed:1")));
+        assert!(!res.contains(&format!("{module}_field_2:value2")));
+
         Ok(())
     })
 }
@@ -178,6 +269,271 @@ fn test_string() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_string_format() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    let res: String = redis::cmd("string.format")
+        .arg(&["user", "42"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.format")?;
+
+    assert_eq!(&res, "user:42");
+
+    Ok(())
+}
+
+#[test]
+fn test_string_sort() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    let res: Vec<String> = redis::cmd("string.sort")
+        .arg(&["alpha", "10", "9", "b", "a", "-1.5", "1e1"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.sort")?;
+    assert_eq!(res, vec!["-1.5", "10", "1e1", "9", "a", "b"]);
+
+    let res: Vec<String> = redis::cmd("string.sort")
+        .arg(&["numeric", "10", "9", "-1.5", "1e1", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.sort")?;
+    assert_eq!(res, vec!["-1.5", "0", "9", "10", "1e1"]);
+
+    let res: Result<Vec<String>, RedisError> = redis::cmd("string.sort")
+        .arg(&["numeric", "1", "a"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_string_binary_key() -> Result<()> {
+    let mut con = TestConnection::new("string");
+
+    let key: &[u8] = b"key:\xff\x00\xfe";
+    let res: Vec<u8> = redis::cmd("string.set_binary_key")
+        .arg(key)
+        .arg(&b"\x00\xff\x80"[..])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set_binary_key")?;
+    assert_eq!(res, b"\x00\xff\x80\xff\x00\xfe");
+
+    let res: Vec<u8> = redis::cmd("GET")
+        .arg(key)
+        .query(&mut con)
+        .with_context(|| "failed to run GET")?;
+    assert_eq!(res, b"\x00\xff\x80\xff\x00\xfe");
+
+    Ok(())
+}
+
+#[test]
+fn test_list_get() -> Result<()> {
+    let mut con = TestConnection::new("lists");
+
+    redis::cmd("rpush")
+        .arg(&["l", "a", "b", "c"])
+        .query(&mut con)
+        .with_context(|| "failed to run rpush")?;
+
+    for (index, expected) in [
+        (0, Some("a")),
+        (2, Some("c")),
+        (-1, Some("c")),
+        (-3, Some("a")),
+    ] {
+        let res: Option<String> = redis::cmd("LINDEX.NATIVE")
+            .arg(&["l", &index.to_string()])
+            .query(&mut con)
+            .with_context(|| "failed to run LINDEX.NATIVE")?;
+        assert_eq!(res.as_deref(), expected);
+    }
+
+    for index in ["3", "-4"] {
+        let res: Option<String> = redis::cmd("LINDEX.NATIVE")
+            .arg(&["l", index])
+            .query(&mut con)
+            .with_context(|| "failed to run LINDEX.NATIVE")?;
+        assert_eq!(res, None);
+    }
+
+    let res: Vec<String> = redis::cmd("LRANGE.NATIVE")
+        .arg(&["l"])
+        .query(&mut con)
+        .with_context(|| "failed to run LRANGE.NATIVE")?;
+    assert_eq!(&res, &["a", "b", "c"]);
+
+    // Reading does not remove the elements.
+    let res: i64 = redis::cmd("llen").arg(&["l"]).query(&mut con)?;
+    assert_eq!(res, 3);
+
+    let res: Vec<String> = redis::cmd("LRANGE.NATIVE")
+        .arg(&["missing"])
+        .query(&mut con)
+        .with_context(|| "failed to run LRANGE.NATIVE")?;
+    assert!(res.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_list_set_insert() -> Result<()> {
+    let mut con = TestConnection::new("lists");
+
+    redis::cmd("rpush")
+        .arg(&["l", "b", "d"])
+        .query(&mut con)
+        .with_context(|| "failed to run rpush")?;
+
+    // Head, middle and tail (at the length of the list).
+    for (index, element, len) in [("0", "a", 3), ("2", "c", 4), ("4", "e", 5)] {
+        let res: i64 = redis::cmd("LINSERT.NATIVE")
+            .arg(&["l", index, element])
+            .query(&mut con)
+            .with_context(|| "failed to run LINSERT.NATIVE")?;
+        assert_eq!(res, len);
+    }
+
+    let res: Vec<String> = redis::cmd("lrange")
+        .arg(&["l", "0", "-1"])
+        .query(&mut con)?;
+    assert_eq!(&res, &["a", "b", "c", "d", "e"]);
+
+    let err = redis::cmd("LINSERT.NATIVE")
+        .arg(&["l", "6", "x"])
+        .query::<i64>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.detail(), Some("index out of range"));
+
+    let res: String = redis::cmd("LSET.NATIVE")
+        .arg(&["l", "-1", "z"])
+        .query(&mut con)
+        .with_context(|| "failed to run LSET.NATIVE")?;
+    assert_eq!(&res, "OK");
+
+    let err = redis::cmd("LSET.NATIVE")
+        .arg(&["l", "5", "x"])
+        .query::<String>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.detail(), Some("index out of range"));
+
+    let res: Vec<String> = redis::cmd("lrange")
+        .arg(&["l", "0", "-1"])
+        .query(&mut con)?;
+    assert_eq!(&res, &["a", "b", "c", "d", "z"]);
+
+    redis::cmd("set").arg(&["s", "v"]).query(&mut con)?;
+    let err = redis::cmd("LINSERT.NATIVE")
+        .arg(&["s", "0", "x"])
+        .query::<i64>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.kind(), redis::ErrorKind::TypeError);
+
+    Ok(())
+}
+
+#[test]
+fn test_zset() -> Result<()> {
+    let mut con = TestConnection::new("zset");
+
+    let zadd = |con: &mut TestConnection, args: &[&str]| -> Result<Option<f64>> {
+        redis::cmd("ZADD.NATIVE")
+            .arg(args)
+            .query(con)
+            .with_context(|| "failed to run ZADD.NATIVE")
+    };
+
+    assert_eq!(zadd(&mut con, &["z", "1", "a"])?, Some(1.0));
+    assert_eq!(zadd(&mut con, &["z", "2", "b"])?, Some(2.0));
+    assert_eq!(zadd(&mut con, &["z", "3", "c"])?, Some(3.0));
+
+    // NX keeps the score of an existing member, XX does not add new members.
+    assert_eq!(zadd(&mut con, &["z", "5", "a", "NX"])?, Some(1.0));
+    assert_eq!(zadd(&mut con, &["z", "5", "d", "XX"])?, None);
+    // GT and LT only update in one direction.
+    assert_eq!(zadd(&mut con, &["z", "0", "b", "GT"])?, Some(2.0));
+    assert_eq!(zadd(&mut con, &["z", "4", "b", "GT"])?, Some(4.0));
+    assert_eq!(zadd(&mut con, &["z", "5", "c", "LT"])?, Some(3.0));
+
+    let res: Option<f64> = redis::cmd("ZSCORE.NATIVE")
+        .arg(&["z", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZSCORE.NATIVE")?;
+    assert_eq!(res, Some(4.0));
+
+    let res: Option<f64> = redis::cmd("ZSCORE.NATIVE")
+        .arg(&["z", "d"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZSCORE.NATIVE")?;
+    assert_eq!(res, None);
+
+    let res: Option<f64> = redis::cmd("ZSCORE.NATIVE")
+        .arg(&["missing", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZSCORE.NATIVE")?;
+    assert_eq!(res, None);
+
+    let res: Vec<(String, f64)> = redis::cmd("ZRANGEBYSCORE.NATIVE")
+        .arg(&["z", "1", "3"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZRANGEBYSCORE.NATIVE")?;
+    assert_eq!(res, vec![("a".to_owned(), 1.0), ("c".to_owned(), 3.0)]);
+
+    let res: Vec<(String, f64)> = redis::cmd("ZRANGEBYSCORE.NATIVE")
+        .arg(&["missing", "1", "3"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZRANGEBYSCORE.NATIVE")?;
+    assert!(res.is_empty());
+
+    let res: Vec<(String, f64)> = redis::cmd("ZRANGEBYSCORE.NATIVE")
+        .arg(&["z", "1", "4", "EXCLUSIVE"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZRANGEBYSCORE.NATIVE")?;
+    assert_eq!(res, vec![("c".to_owned(), 3.0)]);
+
+    let res: Vec<(String, f64)> = redis::cmd("ZRANGEBYSCORE.NATIVE")
+        .arg(&["z", "1", "4", "REV"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZRANGEBYSCORE.NATIVE")?;
+    assert_eq!(
+        res,
+        vec![
+            ("b".to_owned(), 4.0),
+            ("c".to_owned(), 3.0),
+            ("a".to_owned(), 1.0)
+        ]
+    );
+
+    let res: Vec<(String, f64)> = redis::cmd("ZRANGEBYSCORE.NATIVE")
+        .arg(&["z", "1", "4", "EXCLUSIVE", "REV"])
+        .query(&mut con)
+        .with_context(|| "failed to run ZRANGEBYSCORE.NATIVE")?;
+    assert_eq!(res, vec![("c".to_owned(), 3.0)]);
+
+    // Lexicographical ranges, on members which all have the same score.
+    for member in ["a", "b", "c", "d"] {
+        zadd(&mut con, &["lex", "0", member])?;
+    }
+    let lex_range = |con: &mut TestConnection, args: &[&str]| -> Result<Vec<String>> {
+        let res: Vec<(String, f64)> = redis::cmd("ZRANGEBYLEX.NATIVE")
+            .arg(args)
+            .query(con)
+            .with_context(|| "failed to run ZRANGEBYLEX.NATIVE")?;
+        Ok(res.into_iter().map(|(member, _)| member).collect())
+    };
+    assert_eq!(lex_range(&mut con, &["lex", "[b", "[d"])?, ["b", "c", "d"]);
+    assert_eq!(lex_range(&mut con, &["lex", "(b", "(d"])?, ["c"]);
+    assert_eq!(lex_range(&mut con, &["lex", "-", "(c", "REV"])?, ["b", "a"]);
+    assert!(lex_range(&mut con, &["lex", "b", "d"]).is_err());
+
+    redis::cmd("set").arg(&["s", "v"]).query(&mut con)?;
+    let err = zadd(&mut con, &["s", "1", "a"]).unwrap_err();
+    assert!(format!("{err:#}").contains("WRONGTYPE"));
+
+    Ok(())
+}
+
 #[test]
 fn test_scan() -> Result<()> {
     let mut con = TestConnection::new("scan_keys");
@@ -197,6 +553,74 @@ fn test_scan() -> Result<()> {
 
     assert_eq!(&res, &["x", "y"]);
 
+    let mut res: Vec<String> = redis::cmd("scan_key_names").query(&mut con)?;
+    res.sort();
+
+    assert_eq!(&res, &["x", "y"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_paged() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    for i in 0..100 {
+        redis::cmd("set")
+            .arg(&[format!("key{i}"), "1".to_owned()])
+            .query(&mut con)
+            .with_context(|| "failed to run set")?;
+    }
+
+    let mut keys: Vec<String> = Vec::new();
+    let mut token = "0".to_owned();
+    loop {
+        let (next, mut page): (String, Vec<String>) = redis::cmd("scan_keys_paged")
+            .arg(&[token])
+            .query(&mut con)
+            .with_context(|| "failed to run scan_keys_paged")?;
+        keys.append(&mut page);
+        if next == "0" {
+            break;
+        }
+        token = next;
+    }
+    keys.sort();
+    keys.dedup();
+
+    assert_eq!(keys.len(), 100);
+
+    let res: RedisResult<(String, Vec<String>)> =
+        redis::cmd("scan_keys_paged").arg(&["abc"]).query(&mut con);
+    assert!(res.err().unwrap().to_string().contains("invalid cursor"));
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_iter() -> Result<()> {
+    let mut con = TestConnection::new("scan_keys");
+
+    for i in 0..100 {
+        redis::cmd("set")
+            .arg(&[format!("key{i}"), "1".to_owned()])
+            .query(&mut con)
+            .with_context(|| "failed to run set")?;
+    }
+    redis::cmd("sadd")
+        .arg(&["not_a_string", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run sadd")?;
+
+    let mut keys: Vec<String> = redis::cmd("scan_keys_iter")
+        .query(&mut con)
+        .with_context(|| "failed to run scan_keys_iter")?;
+    keys.sort();
+    keys.dedup();
+
+    assert_eq!(keys.len(), 100);
+    assert!(keys.iter().all(|k| k.starts_with("key")));
+
     Ok(())
 }
 
@@ -236,6 +660,84 @@ fn test_stream_reader() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_stream_add() -> Result<()> {
+    let mut con = TestConnection::new("stream");
+
+    let res: String = redis::cmd("STREAM_ADD")
+        .arg(&["s", "5-1", "foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_ADD")?;
+    assert_eq!(res, "5-1");
+
+    let res: String = redis::cmd("STREAM_ADD")
+        .arg(&["s", "*", "foo", "bar", "baz", "qux"])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_ADD")?;
+    assert_ne!(res, "5-1");
+
+    let res: Vec<(String, Vec<String>)> = redis::cmd("XRANGE")
+        .arg(&["s", "-", "+"])
+        .query(&mut con)
+        .with_context(|| "failed to run XRANGE")?;
+    assert_eq!(res.len(), 2);
+    assert_eq!(
+        res[0],
+        ("5-1".to_string(), vec!["foo".into(), "bar".into()])
+    );
+    assert_eq!(res[1].1, vec!["foo", "bar", "baz", "qux"]);
+
+    let res: Result<String, RedisError> = redis::cmd("STREAM_ADD")
+        .arg(&["s", "4-0", "foo", "bar"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("equal or smaller than the target stream top item"));
+
+    let _: () = redis::cmd("SET").arg(&["x", "1"]).query(&mut con)?;
+    let res: Result<String, RedisError> = redis::cmd("STREAM_ADD")
+        .arg(&["x", "*", "foo", "bar"])
+        .query(&mut con);
+    assert!(res.unwrap_err().to_string().contains("WRONGTYPE"));
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_delete() -> Result<()> {
+    let mut con = TestConnection::new("stream");
+
+    for id in ["1-1", "1-2", "1-3"] {
+        let _: String = redis::cmd("STREAM_ADD")
+            .arg(&["s", id, "foo", "bar"])
+            .query(&mut con)
+            .with_context(|| "failed to run STREAM_ADD")?;
+    }
+
+    let _: String = redis::cmd("STREAM_DEL")
+        .arg(&["s", "1-2"])
+        .query(&mut con)
+        .with_context(|| "failed to run STREAM_DEL")?;
+
+    let res: Result<String, RedisError> =
+        redis::cmd("STREAM_DEL").arg(&["s", "1-2"]).query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("no such stream entry"));
+
+    for expected in ["1-1", "1-3"] {
+        let res: String = redis::cmd("STREAM_POP")
+            .arg(&["s"])
+            .query(&mut con)
+            .with_context(|| "failed to run STREAM_POP")?;
+        assert_eq!(res, expected);
+    }
+
+    Ok(())
+}
+
 #[test]
 #[cfg(any(
     feature = "min-redis-compatibility-version-7-4",
@@ -250,6 +752,49 @@ fn test_call() -> Result<()> {
 
     assert_eq!(&res, "pass");
 
+    redis::cmd("set")
+        .arg(&["forward_error", "value"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+
+    // The error of the inner call is forwarded as is, including its error code
+    let err = redis::cmd("call.forward_error")
+        .arg(&["lpush", "forward_error", "x"])
+        .query::<()>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.kind(), redis::ErrorKind::TypeError);
+
+    let res: String = redis::cmd("call.typed")
+        .query(&mut con)
+        .with_context(|| "failed to run call.typed")?;
+    assert_eq!(&res, "pass");
+
+    let res: String = redis::cmd("call.on_key")
+        .query(&mut con)
+        .with_context(|| "failed to run call.on_key")?;
+    assert_eq!(&res, "pass");
+
+    let res: String = redis::cmd("call.mixed_args")
+        .query(&mut con)
+        .with_context(|| "failed to run call.mixed_args")?;
+    assert_eq!(&res, "pass");
+
+    for expected in ["1.5", "3"] {
+        let res: String = redis::cmd("call.incr_by_float")
+            .arg(&["float_counter", "1.5"])
+            .query(&mut con)
+            .with_context(|| "failed to run call.incr_by_float")?;
+        assert_eq!(&res, expected);
+    }
+
+    let res: String = redis::cmd("call.set_echo")
+        .arg(&["echo_key", "echo_value"])
+        .query(&mut con)
+        .with_context(|| "failed to run call.set_echo")?;
+    assert_eq!(&res, "OK");
+    let res: String = redis::cmd("GET").arg("echo_key").query(&mut con)?;
+    assert_eq!(&res, "echo_value");
+
     Ok(())
 }
 
@@ -265,20 +810,135 @@ fn test_ctx_flags() -> Result<()> {
 }
 
 #[test]
-fn test_get_current_user() -> Result<()> {
-    let mut con = TestConnection::new("acl");
+fn test_get_client_addr() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
 
-    let res: String = redis::cmd("get_current_user").query(&mut con)?;
+    let id: u64 = redis::cmd("client").arg("id").query(&mut con)?;
+    let (ip, _port): (String, u16) = redis::cmd("client_addr").arg(id).query(&mut con)?;
+    assert_eq!(ip, "127.0.0.1");
 
-    assert_eq!(&res, "default");
+    let res: Result<(String, u16), RedisError> =
+        redis::cmd("client_addr").arg(u64::MAX).query(&mut con);
+    assert!(res.is_err());
 
     Ok(())
 }
 
 #[test]
-#[cfg(feature = "min-redis-compatibility-version-7-4")]
-fn test_set_acl_categories() -> Result<()> {
-    let mut con = TestConnection::new("acl");
+fn test_get_client_name_by_id() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
+
+    let id: u64 = redis::cmd("client").arg("id").query(&mut con)?;
+    let res: Option<String> = redis::cmd("client_name").arg(id).query(&mut con)?;
+    assert_eq!(res, None);
+
+    let _: String = redis::cmd("client")
+        .arg(&["setname", "gateway"])
+        .query(&mut con)?;
+    let res: Option<String> = redis::cmd("client_name").arg(id).query(&mut con)?;
+    assert_eq!(res.as_deref(), Some("gateway"));
+
+    let res: Option<String> = redis::cmd("client_name").arg(u64::MAX).query(&mut con)?;
+    assert_eq!(res, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_get_client_info() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
+
+    let id: i64 = redis::cmd("client").arg("id").query(&mut con)?;
+    let (info_id, addr, port, db, multi): (i64, String, i64, i64, bool) =
+        redis::cmd("client_info").query(&mut con)?;
+    assert_eq!(info_id, id);
+    assert_eq!(addr, "127.0.0.1");
+    assert!(port > 0);
+    assert_eq!(db, 0);
+    assert!(!multi);
+
+    let res: (i64, String, i64, i64, bool) = redis::cmd("client_info").arg(id).query(&mut con)?;
+    assert_eq!(res, (id, addr, port, db, multi));
+
+    let res: RedisResult<Value> = redis::cmd("client_info").arg(u64::MAX).query(&mut con);
+    assert!(res.is_err());
+
+    // The flags are the ones of the client at the time of the call.
+    let _: String = redis::cmd("MULTI").query(&mut con)?;
+    let _: String = redis::cmd("client_info").query(&mut con)?;
+    let res: Vec<(i64, String, i64, i64, bool)> = redis::cmd("EXEC").query(&mut con)?;
+    assert!(res[0].4);
+
+    Ok(())
+}
+
+#[test]
+fn test_client_list() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
+    let mut other = con.new_connection()?;
+
+    let _: String = redis::cmd("SELECT").arg(3).query(&mut other)?;
+    let other_id: i64 = redis::cmd("client").arg("id").query(&mut other)?;
+    let (id, addr, port, db, _): (i64, String, i64, i64, bool) =
+        redis::cmd("client_info").query(&mut con)?;
+
+    let res: Vec<(i64, String, i64, i64)> = redis::cmd("client_list").query(&mut con)?;
+    assert!(res.contains(&(id, addr, port, db)));
+    let other_info = res.iter().find(|info| info.0 == other_id).unwrap();
+    assert_eq!(other_info.3, 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_oom_for() -> Result<()> {
+    let mut con = TestConnection::new("ctx_flags");
+
+    // No maxmemory limit.
+    let res: String = redis::cmd("check_oom").arg(1u64 << 40).query(&mut con)?;
+    assert_eq!(&res, "OK");
+
+    let info: String = redis::cmd("INFO").arg("memory").query(&mut con)?;
+    let used_memory: u64 = info
+        .lines()
+        .find_map(|l| l.strip_prefix("used_memory:"))
+        .unwrap()
+        .trim()
+        .parse()?;
+    let _: String = redis::cmd("CONFIG")
+        .arg(&[
+            "SET",
+            "maxmemory",
+            &(used_memory + 10 * 1024 * 1024).to_string(),
+        ])
+        .query(&mut con)?;
+
+    let res: String = redis::cmd("check_oom").arg(1024).query(&mut con)?;
+    assert_eq!(&res, "OK");
+
+    let res: RedisResult<String> = redis::cmd("check_oom")
+        .arg(100 * 1024 * 1024)
+        .query(&mut con);
+    assert!(res.err().unwrap().to_string().contains("OOM"));
+
+    Ok(())
+}
+
+#[test]
+fn test_get_current_user() -> Result<()> {
+    let mut con = TestConnection::new("acl");
+
+    let res: String = redis::cmd("get_current_user").query(&mut con)?;
+
+    assert_eq!(&res, "default");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "min-redis-compatibility-version-7-4")]
+fn test_set_acl_categories() -> Result<()> {
+    let mut con = TestConnection::new("acl");
 
     let res: Vec<String> = redis::cmd("ACL").arg("CAT").query(&mut con)?;
     assert!(res.contains(&"acl".to_owned()));
@@ -354,6 +1014,70 @@ fn test_key_space_notifications() -> Result<()> {
     let res: String = redis::cmd("GET").arg(&["num_sets"]).query(&mut con)?;
     assert_eq!(res, "1");
 
+    // The counters are also exported in the module's INFO output.
+    let res: String = redis::cmd("INFO")
+        .arg(&["events_metrics"])
+        .query(&mut con)?;
+    assert!(res.contains("events_num_key_misses:1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_write_behind_buffer() -> Result<()> {
+    let mut con = TestConnection::new("events");
+
+    // All the keys set by a single command are indexed by a single flush.
+    let _: String = redis::cmd("MSET")
+        .arg(&["wb:1", "a", "wb:2", "b", "wb:3", "c"])
+        .query(&mut con)?;
+
+    let res: usize = redis::cmd("events.num_index_flushes").query(&mut con)?;
+    assert_eq!(res, 1);
+
+    let mut res: Vec<String> = redis::cmd("SMEMBERS").arg(&["wb_index"]).query(&mut con)?;
+    res.sort();
+    assert_eq!(res, vec!["wb:1", "wb:2", "wb:3"]);
+
+    let _: String = redis::cmd("SET").arg(&["wb:4", "d"]).query(&mut con)?;
+    let res: usize = redis::cmd("events.num_index_flushes").query(&mut con)?;
+    assert_eq!(res, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_keyspace_events_detailed() -> Result<()> {
+    let mut con = TestConnection::new("events");
+
+    let _: String = redis::cmd("CONFIG")
+        .arg(&["SET", "notify-keyspace-events", "KEA"])
+        .query(&mut con)?;
+
+    // The keyspace (K) and keyevent (E) classes are not part of `NotifyEvent`,
+    // so they only show up on the raw value.
+    let res: Vec<i64> = redis::cmd("events.keyspace_events").query(&mut con)?;
+    assert_eq!(res[1] & 0b11, 0b11);
+    assert_eq!(res[0], res[1] & !0b11);
+
+    Ok(())
+}
+
+#[test]
+fn test_keyspace_stats() -> Result<()> {
+    let mut con = TestConnection::new("events");
+
+    redis::cmd("GET").arg(&["x"]).query(&mut con)?;
+    let _: String = redis::cmd("SET").arg(&["x", "1"]).query(&mut con)?;
+    redis::cmd("GET").arg(&["x"]).query(&mut con)?;
+
+    // Make sure the cached stats are refreshed.
+    thread::sleep(Duration::from_millis(500));
+
+    let res: Vec<i64> = redis::cmd("events.keyspace_stats").query(&mut con)?;
+    assert!(res[0] >= 1);
+    assert!(res[1] >= 1);
+
     Ok(())
 }
 
@@ -375,6 +1099,34 @@ fn test_context_mutex() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_owned_detached_context() -> Result<()> {
+    let mut con = TestConnection::new("threads");
+
+    for expected in 1..=2 {
+        let res: i64 = redis::cmd("detached_incr")
+            .arg(&["counter"])
+            .query(&mut con)
+            .with_context(|| "failed to run detached_incr")?;
+        assert_eq!(res, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_gil_cell() -> Result<()> {
+    let mut con = TestConnection::new("threads");
+
+    let res: String = redis::cmd("init_once_data").arg("foo").query(&mut con)?;
+    assert_eq!(&res, "foo");
+
+    let res: String = redis::cmd("init_once_data").arg("bar").query(&mut con)?;
+    assert_eq!(&res, "foo");
+
+    Ok(())
+}
+
 #[test]
 fn test_server_event() -> Result<()> {
     let mut con = TestConnection::new("server_events");
@@ -395,6 +1147,19 @@ fn test_server_event() -> Result<()> {
 
     assert_eq!(res, 2);
 
+    // No flushes are counted while unsubscribed from the flush event
+    redis::cmd("flush_events").arg(0).query(&mut con)?;
+    // Unsubscribing again does nothing
+    redis::cmd("flush_events").arg(0).query(&mut con)?;
+    redis::cmd("flushall").query(&mut con)?;
+    let res: i64 = redis::cmd("num_flushed").query(&mut con)?;
+    assert_eq!(res, 2);
+
+    redis::cmd("flush_events").arg(1).query(&mut con)?;
+    redis::cmd("flushall").query(&mut con)?;
+    let res: i64 = redis::cmd("num_flushed").query(&mut con)?;
+    assert_eq!(res, 3);
+
     redis::cmd("config")
         .arg(&["set", "maxmemory", "1"])
         .query(&mut con)
@@ -420,6 +1185,36 @@ fn test_server_event() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_config_changed_event_batching() -> Result<()> {
+    let mut con = TestConnection::new("server_events");
+
+    let (events_before, _): (i64, i64) = redis::cmd("config_changed_events").query(&mut con)?;
+
+    redis::cmd("config")
+        .arg(&[
+            "set",
+            "maxmemory",
+            "1",
+            "maxmemory-samples",
+            "6",
+            "hz",
+            "20",
+        ])
+        .query(&mut con)
+        .with_context(|| "failed to run config set")?;
+
+    // All the configurations changed by a single CONFIG SET are reported together
+    let (events, last_size): (i64, i64) = redis::cmd("config_changed_events").query(&mut con)?;
+    assert_eq!(events, events_before + 1);
+    assert_eq!(last_size, 3);
+
+    let res: i64 = redis::cmd("num_max_memory_changes").query(&mut con)?;
+    assert_eq!(res, 1);
+
+    Ok(())
+}
+
 #[test]
 fn test_configuration() -> Result<()> {
     let mut con = TestConnection::new("configuration");
@@ -516,141 +1311,945 @@ fn test_response() -> Result<()> {
     res.sort();
     assert_eq!(&res, &["b", "d"]);
 
+    let res: Vec<Option<String>> = redis::cmd("map.hmget")
+        .arg(&["k", "c", "x", "a"])
+        .query(&mut con)
+        .with_context(|| "failed to run map.hmget")?;
+    assert_eq!(res, vec![Some("d".to_owned()), None, Some("b".to_owned())]);
+
     Ok(())
 }
 
 #[test]
-fn test_command_proc_macro() -> Result<()> {
-    let mut con = TestConnection::new("proc_macro_commands");
+fn test_hash_get_bytes() -> Result<()> {
+    let mut con = TestConnection::new("response");
 
-    let res: Vec<String> = redis::cmd("COMMAND")
-        .arg(&["GETKEYS", "classic_keys", "x", "foo", "y", "bar"])
+    let value: &[u8] = b"a\0b\xffc\0";
+    redis::cmd("hset")
+        .arg("bytes_hash")
+        .arg("f")
+        .arg(value)
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
-
-    assert_eq!(&res, &["x", "y"]);
+        .with_context(|| "failed to run hset")?;
 
-    let res: Vec<String> = redis::cmd("COMMAND")
-        .arg(&["GETKEYS", "keyword_keys", "foo", "x", "1", "y", "2"])
+    let res: Vec<Option<Vec<u8>>> = redis::cmd("map.hmget_bytes")
+        .arg(&["bytes_hash", "f", "missing"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+        .with_context(|| "failed to run map.hmget_bytes")?;
+    assert_eq!(res, vec![Some(value.to_vec()), None]);
 
-    assert_eq!(&res, &["x", "y"]);
-
-    let res: Vec<String> = redis::cmd("COMMAND")
-        .arg(&["GETKEYS", "num_keys", "3", "x", "y", "z", "foo", "bar"])
+    let res: Option<Vec<u8>> = redis::cmd("map.hget_bytes")
+        .arg(&["bytes_hash", "f"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
-
-    assert_eq!(&res, &["x", "y", "z"]);
+        .with_context(|| "failed to run map.hget_bytes")?;
+    assert_eq!(res, Some(value.to_vec()));
 
-    let res: Vec<String> = redis::cmd("COMMAND")
-        .arg(&["GETKEYS", "num_keys", "0", "foo", "bar"])
+    let res: Option<Vec<u8>> = redis::cmd("map.hget_bytes")
+        .arg(&["missing_hash", "f"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
-
-    assert!(res.is_empty());
+        .with_context(|| "failed to run map.hget_bytes")?;
+    assert_eq!(res, None);
 
     Ok(())
 }
 
 #[test]
-fn test_redis_value_derive() -> Result<()> {
-    let mut con = TestConnection::new("proc_macro_commands");
+fn test_hash_set_multi() -> Result<()> {
+    let mut con = TestConnection::new("response");
 
-    let res: Value = redis::cmd("redis_value_derive")
+    // More pairs than a single batch of `RedisModule_HashSet` accepts.
+    let mut args = vec!["hset_hash".to_owned()];
+    for i in 0..20 {
+        args.push(format!("f{i}"));
+        args.push(format!("v{i}"));
+    }
+    let res: i64 = redis::cmd("map.hset")
+        .arg(&args)
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+        .with_context(|| "failed to run map.hset")?;
+    assert_eq!(res, 20);
 
-    assert_eq!(res.as_sequence().unwrap().len(), 22);
+    let res: i64 = redis::cmd("hlen")
+        .arg(&["hset_hash"])
+        .query(&mut con)
+        .with_context(|| "failed to run hlen")?;
+    assert_eq!(res, 20);
 
-    let res: String = redis::cmd("redis_value_derive")
-        .arg(&["test"])
+    let res: Vec<String> = redis::cmd("hmget")
+        .arg(&["hset_hash", "f0", "f19"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+        .with_context(|| "failed to run hmget")?;
+    assert_eq!(res, &["v0", "v19"]);
 
-    assert_eq!(res, "OK");
+    redis::cmd("set")
+        .arg(&["hset_string", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    let err = redis::cmd("map.hset")
+        .arg(&["hset_string", "f", "v"])
+        .query::<i64>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.kind(), redis::ErrorKind::TypeError);
 
     Ok(())
 }
 
 #[test]
-#[cfg(any(
-    feature = "min-redis-compatibility-version-7-4",
-    feature = "min-redis-compatibility-version-7-2"
-))]
-fn test_call_blocking() -> Result<()> {
-    let mut con = TestConnection::new("call");
+fn test_hash_del_multi() -> Result<()> {
+    let mut con = TestConnection::new("response");
 
-    let res: Option<String> = redis::cmd("call.blocking")
-        .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+    // More fields than a single batch of `RedisModule_HashSet` accepts.
+    let fields: Vec<String> = (0..20).map(|i| format!("f{i}")).collect();
+    for field in &fields {
+        redis::cmd("hset")
+            .arg(&["hdel_hash", field, "1"])
+            .query(&mut con)
+            .with_context(|| "failed to run hset")?;
+    }
 
-    assert_eq!(res, None);
+    let mut args = vec!["hdel_hash".to_owned(), "missing".to_owned()];
+    args.extend(fields.iter().skip(5).cloned());
+    let res: i64 = redis::cmd("map.hdel")
+        .arg(&args)
+        .query(&mut con)
+        .with_context(|| "failed to run map.hdel")?;
+    assert_eq!(res, 15);
 
-    let res: Option<String> = redis::cmd("call.blocking_from_detached_ctx")
+    let res: i64 = redis::cmd("hlen")
+        .arg(&["hdel_hash"])
         .query(&mut con)
-        .with_context(|| "failed to run string.set")?;
+        .with_context(|| "failed to run hlen")?;
+    assert_eq!(res, 5);
 
-    assert_eq!(res, None);
+    redis::cmd("set")
+        .arg(&["hdel_string", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    let err = redis::cmd("map.hdel")
+        .arg(&["hdel_string", "f0"])
+        .query::<i64>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.kind(), redis::ErrorKind::TypeError);
 
     Ok(())
 }
 
 #[test]
-fn test_open_key_with_flags() -> Result<()> {
-    let mut con = TestConnection::new("open_key_with_flags");
+fn test_response_max_depth() -> Result<()> {
+    let mut con = TestConnection::new("response");
 
-    // Avoid active expriation
-    redis::cmd("DEBUG")
-        .arg(&["SET-ACTIVE-EXPIRE", "0"])
+    let res: Value = redis::cmd("response.nested")
+        .arg(&[2, 2])
         .query(&mut con)
-        .with_context(|| "failed to run DEBUG SET-ACTIVE-EXPIRE")?;
+        .with_context(|| "failed to run response.nested")?;
+    assert_eq!(res, Value::Bulk(vec![Value::Bulk(vec![Value::Int(1)])]));
 
-    for cmd in ["open_key_with_flags.write", "open_key_with_flags.read"].into_iter() {
-        redis::cmd("set")
-            .arg(&["x", "1"])
-            .query(&mut con)
-            .with_context(|| "failed to run string.set")?;
+    let res: Result<Value, RedisError> = redis::cmd("response.nested").arg(&[3, 2]).query(&mut con);
+    assert!(res.is_err());
 
-        // Set experition time to 1 second.
-        redis::cmd("pexpire")
-            .arg(&["x", "1"])
+    Ok(())
+}
+
+#[test]
+fn test_reply_builder() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    let (all, evens, squares): (Vec<i64>, Vec<i64>, Vec<(String, i64)>) =
+        redis::cmd("response.stream")
+            .arg(5)
             .query(&mut con)
-            .with_context(|| "failed to run expire")?;
+            .with_context(|| "failed to run response.stream")?;
+    assert_eq!(all, vec![0, 1, 2, 3, 4]);
+    assert_eq!(evens, vec![0, 2, 4]);
+    // RESP2 clients get the map as a flat array of keys and values
+    assert_eq!(
+        squares,
+        vec![
+            ("0".to_owned(), 0),
+            ("1".to_owned(), 1),
+            ("2".to_owned(), 4)
+        ]
+    );
 
-        // Sleep for 2 seconds, ensure expiration time has passed.
-        thread::sleep(Duration::from_millis(500));
+    let res = con.resp3_query(&["response.stream", "0"])?;
+    assert_eq!(
+        res,
+        Resp3Value::Array(vec![
+            Resp3Value::Array(vec![]),
+            Resp3Value::Array(vec![]),
+            Resp3Value::Map(
+                (0..3)
+                    .map(|i| (
+                        Resp3Value::BulkString(i.to_string()),
+                        Resp3Value::Integer(i * i)
+                    ))
+                    .collect()
+            ),
+        ])
+    );
 
-        // Open key as read only or ReadWrite with NOEFFECTS flag.
-        let res = redis::cmd(cmd).arg(&["x"]).query(&mut con);
-        assert_eq!(res, Ok(()));
+    Ok(())
+}
 
-        // Get the number of expired keys.
-        let stats: String = redis::cmd("info").arg(&["stats"]).query(&mut con)?;
+#[test]
+fn test_response_nested_error() -> Result<()> {
+    let mut con = TestConnection::new("response");
 
-        // Find the number of expired keys, x,  according to the substring "expired_keys:{x}"
-        let expired_keys = stats
-            .match_indices("expired_keys:")
-            .next()
-            .map(|(i, _)| &stats[i..i + "expired_keys:".len() + 1])
-            .and_then(|s| s.split(':').nth(1))
-            .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(-1);
+    let res: Vec<i64> = redis::cmd("response.mixed")
+        .arg(&["1", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.mixed")?;
+    assert_eq!(res, vec![1, 2]);
+
+    // The redis client surfaces an error nested in an array (like an EXEC
+    // result with a failed command) as the error of the whole reply.
+    let err = redis::cmd("response.mixed")
+        .arg(&["1", "x", "3"])
+        .query::<Value>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("ERR"));
+    assert_eq!(err.detail(), Some("value is not an integer: x"));
+
+    // The whole array was consumed, the connection is still in sync.
+    let res: Vec<i64> = redis::cmd("response.mixed")
+        .arg(&["3"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.mixed")?;
+    assert_eq!(res, vec![3]);
 
-        // Ensure that no keys were expired.
-        assert_eq!(expired_keys, 0);
+    Ok(())
+}
 
-        // Delete key and reset stats
-        redis::cmd("del").arg(&["x"]).query(&mut con)?;
-        redis::cmd("config").arg(&["RESETSTAT"]).query(&mut con)?;
-    }
+#[test]
+fn test_response_error_with_code() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    let err = redis::cmd("response.error")
+        .arg(&["NOTFOUND", "no such item"])
+        .query::<Value>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NOTFOUND"));
+    assert_eq!(err.detail(), Some("no such item"));
+
+    // An invalid code is replaced with the generic one.
+    let err = redis::cmd("response.error")
+        .arg(&["not found", "no such item"])
+        .query::<Value>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.code(), Some("ERR"));
+    assert_eq!(err.detail(), Some("no such item"));
 
     Ok(())
 }
 
 #[test]
-fn test_expire() -> Result<()> {
+fn test_response_bool() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    // RESP2 has no boolean type, so booleans are sent as integers
+    let res: Value = redis::cmd("response.bool")
+        .arg(&["1"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.bool")?;
+    assert_eq!(res, Value::Int(1));
+
+    let res: Value = redis::cmd("response.bool")
+        .arg(&["0"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.bool")?;
+    assert_eq!(res, Value::Int(0));
+
+    let res: Vec<String> = redis::cmd("response.bool_types")
+        .query(&mut con)
+        .with_context(|| "failed to run response.bool_types")?;
+    assert_eq!(res, vec!["integer", "bool"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_response_types_downgrade() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    // RESP2 clients get maps as flat arrays, sets as arrays, booleans as
+    // integers and double keys as bulk strings.
+    let res: Value = redis::cmd("response.types")
+        .query(&mut con)
+        .with_context(|| "failed to run response.types")?;
+    let data = |s: &str| Value::Data(s.as_bytes().to_vec());
+    assert_eq!(
+        res,
+        Value::Bulk(vec![
+            Value::Bulk(vec![
+                Value::Bulk(vec![
+                    data("flag"),
+                    Value::Int(1),
+                    data("set"),
+                    Value::Bulk(vec![Value::Int(1), Value::Int(2)]),
+                ]),
+                Value::Bulk(vec![data("1.5"), Value::Int(0)]),
+            ]),
+            Value::Bulk(vec![data("set"), Value::Bulk(vec![Value::Int(1)])]),
+        ])
+    );
+
+    let res = con.resp3_query(&["response.types"])?;
+    assert_eq!(
+        res,
+        Resp3Value::Array(vec![
+            Resp3Value::Array(vec![
+                Resp3Value::Map(vec![
+                    (
+                        Resp3Value::BulkString("flag".to_owned()),
+                        Resp3Value::Boolean(true)
+                    ),
+                    (
+                        Resp3Value::BulkString("set".to_owned()),
+                        Resp3Value::Set(vec![Resp3Value::Integer(1), Resp3Value::Integer(2)])
+                    ),
+                ]),
+                Resp3Value::Map(vec![(Resp3Value::Double(1.5), Resp3Value::Boolean(false))]),
+            ]),
+            Resp3Value::Map(vec![(
+                Resp3Value::BulkString("set".to_owned()),
+                Resp3Value::Set(vec![Resp3Value::Boolean(true)])
+            )]),
+        ])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_response_attribute() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    let res: String = redis::cmd("response.attribute")
+        .query(&mut con)
+        .with_context(|| "failed to run response.attribute")?;
+    assert_eq!(res, "value");
+
+    let res: (Vec<Value>, String, String) =
+        redis::cmd("response.attribute_read")
+            .query(&mut con)
+            .with_context(|| "failed to run response.attribute_read")?;
+    assert_eq!(
+        res,
+        (
+            vec![Value::Status("ttl".to_owned()), Value::Int(10)],
+            "value".to_owned(),
+            "|{ttl: 10}| value".to_owned()
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_response_config_get() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    redis::cmd("config")
+        .arg(&["set", "maxmemory-samples", "7"])
+        .query(&mut con)
+        .with_context(|| "failed to run config set")?;
+
+    let res: Vec<(String, String)> = redis::cmd("response.config_get")
+        .arg(&["maxmemory-*"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.config_get")?;
+    assert!(res.contains(&("maxmemory-samples".to_owned(), "7".to_owned())));
+    assert!(res.windows(2).all(|w| w[0].0 < w[1].0));
+
+    Ok(())
+}
+
+#[test]
+fn test_response_markdown() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    // RESP2 clients get the plain text.
+    let res: String = redis::cmd("response.help")
+        .arg(&["markdown"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.help")?;
+    assert_eq!(res, "# Help\n\n* `response.help`: show this help");
+
+    // RESP3 clients also get the format.
+    let res: Vec<String> = redis::cmd("response.help_format")
+        .arg(&["markdown"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.help_format")?;
+    assert_eq!(
+        res,
+        vec!["mkd", "# Help\n\n* `response.help`: show this help"]
+    );
+
+    let res: Vec<String> = redis::cmd("response.help_format")
+        .arg(&["text"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.help_format")?;
+    assert_eq!(res, vec!["txt", "response.help: show this help"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_response_verbatim_format() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    let res: String = redis::cmd("response.verbatim")
+        .arg(&["txt", "data"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.verbatim")?;
+    assert_eq!(&res, "data");
+
+    let res: Result<String, RedisError> = redis::cmd("response.verbatim")
+        .arg(&["t\nt", "data"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    // The connection must still be usable after the rejected reply.
+    let res: String = redis::cmd("response.verbatim")
+        .arg(&["mkd", "data"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.verbatim")?;
+    assert_eq!(&res, "data");
+
+    Ok(())
+}
+
+#[test]
+fn test_response_double_keys() -> Result<()> {
+    let mut con = TestConnection::new("response");
+
+    // RESP2 clients get the keys as fixed precision bulk strings, ordered by score.
+    let res: Vec<String> = redis::cmd("response.leaderboard")
+        .arg(&["10", "carol", "1.5", "alice", "-2", "bob"])
+        .query(&mut con)
+        .with_context(|| "failed to run response.leaderboard")?;
+    assert_eq!(&res, &["-2.00", "bob", "1.50", "alice", "10.00", "carol"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_command_proc_macro() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    let res: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "classic_keys", "x", "foo", "y", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(&res, &["x", "y"]);
+
+    let res: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "keyword_keys", "foo", "x", "1", "y", "2"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(&res, &["x", "y"]);
+
+    let res: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "num_keys", "3", "x", "y", "z", "foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(&res, &["x", "y", "z"]);
+
+    let res: Vec<String> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "num_keys", "0", "foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert!(res.is_empty());
+
+    // Like SPUBLISH, a command whose only key spec is `NotKey` has no keys:
+    // Redis uses the argument for cluster routing but `GETKEYS` rejects it.
+    let res: Result<Vec<String>, RedisError> = redis::cmd("COMMAND")
+        .arg(&["GETKEYS", "not_key_channel", "channel", "message"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("The command has no key arguments"));
+
+    let res: Vec<String> = redis::cmd("current_keys")
+        .arg(&["x", "foo", "y", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run current_keys")?;
+
+    assert_eq!(&res, &["x", "y"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_command_metrics() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    for arg in ["ok", "ok", "fail"] {
+        let _: RedisResult<String> = redis::cmd("metered").arg(arg).query(&mut con);
+    }
+
+    let res: String = redis::cmd("INFO")
+        .arg("server_events")
+        .query(&mut con)
+        .with_context(|| "failed to run INFO")?;
+
+    assert!(res.contains("cmd_metered_calls:3"));
+    assert!(res.contains("cmd_metered_errors:1"));
+    assert!(res.contains("cmd_metered_usec:"));
+
+    Ok(())
+}
+
+#[test]
+fn test_command_replicate_effects() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    for expected in 1..=2 {
+        let res: i64 = redis::cmd("replicated_incr")
+            .arg("counter")
+            .query(&mut con)
+            .with_context(|| "failed to run replicated_incr")?;
+        assert_eq!(res, expected);
+    }
+
+    let res: i64 = redis::cmd("GET").arg("counter").query(&mut con)?;
+    assert_eq!(res, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_command_enforce_arity() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    let res: String = redis::cmd("arity_checked")
+        .arg(&["foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run arity_checked")?;
+    assert_eq!(&res, "foo");
+
+    let res: Result<String, RedisError> = redis::cmd("arity_checked").query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("wrong number of arguments"));
+
+    Ok(())
+}
+
+#[test]
+fn test_command_deny_during_loading() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    // The server is not loading, so the command runs as usual.
+    let res: String = redis::cmd("loaded_only")
+        .query(&mut con)
+        .with_context(|| "failed to run loaded_only")?;
+    assert_eq!(&res, "OK");
+
+    Ok(())
+}
+
+#[test]
+fn test_redis_value_derive() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    let res: Value = redis::cmd("redis_value_derive")
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(res.as_sequence().unwrap().len(), 22);
+
+    let res: String = redis::cmd("redis_value_derive")
+        .arg(&["test"])
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(res, "OK");
+
+    Ok(())
+}
+
+/// Index the entries of a RESP3 map by their string keys.
+fn resp3_map(value: &Resp3Value) -> HashMap<String, Resp3Value> {
+    match value {
+        Resp3Value::Map(entries) => entries
+            .iter()
+            .map(|(k, v)| match k {
+                Resp3Value::BulkString(k) | Resp3Value::SimpleString(k) => (k.clone(), v.clone()),
+                _ => panic!("unexpected map key {k:?}"),
+            })
+            .collect(),
+        _ => panic!("expected a map, got {value:?}"),
+    }
+}
+
+#[test]
+fn test_redis_value_derive_resp3() -> Result<()> {
+    let con = TestConnection::new("proc_macro_commands");
+
+    let res = resp3_map(&con.resp3_query(&["redis_value_derive"])?);
+
+    // The flatten `inner` field is replaced by its own fields.
+    let mut keys: Vec<&str> = res.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "f",
+            "hash_map",
+            "hash_set",
+            "i",
+            "i1",
+            "ordered_map",
+            "ordered_set",
+            "s",
+            "u",
+            "v",
+            "v2"
+        ]
+    );
+
+    assert_eq!(res["i"], Resp3Value::Integer(10));
+    assert_eq!(res["f"], Resp3Value::Double(1.1));
+    assert_eq!(res["s"], Resp3Value::BulkString("s".to_owned()));
+    assert_eq!(res["u"], Resp3Value::Integer(20));
+    assert_eq!(
+        res["v"],
+        Resp3Value::Array(vec![
+            Resp3Value::Integer(1),
+            Resp3Value::Integer(2),
+            Resp3Value::Integer(3)
+        ])
+    );
+    assert_eq!(res["i1"], Resp3Value::Integer(1));
+
+    let v2 = match &res["v2"] {
+        Resp3Value::Array(v2) => v2.iter().map(resp3_map).collect::<Vec<_>>(),
+        v2 => panic!("expected an array, got {v2:?}"),
+    };
+    assert_eq!(v2.len(), 2);
+    assert_eq!(v2[0]["i1"], Resp3Value::Integer(1));
+    assert_eq!(v2[1]["i1"], Resp3Value::Integer(2));
+
+    let hash_map = resp3_map(&res["hash_map"]);
+    assert_eq!(hash_map["key"], Resp3Value::BulkString("val".to_owned()));
+    let ordered_map = resp3_map(&res["ordered_map"]);
+    assert_eq!(
+        resp3_map(&ordered_map["key"])["i1"],
+        Resp3Value::Integer(10)
+    );
+
+    for set in ["hash_set", "ordered_set"] {
+        assert_eq!(
+            res[set],
+            Resp3Value::Set(vec![Resp3Value::BulkString("key".to_owned())])
+        );
+    }
+
+    // Flatten fields of flatten fields are included too.
+    let res = resp3_map(&con.resp3_query(&["redis_value_derive_nested"])?);
+    assert_eq!(res.len(), 3);
+    assert_eq!(res["o"], Resp3Value::Integer(1));
+    assert_eq!(res["m"], Resp3Value::Integer(2));
+    assert_eq!(res["i1"], Resp3Value::Integer(3));
+
+    // Insertion ordered structs keep the declaration order, with the flatten
+    // fields inlined at their position (sorted, as `middle` is not ordered).
+    let res = con.resp3_query(&["redis_value_derive_ordered"])?;
+    let key = |k: &str| Resp3Value::BulkString(k.to_owned());
+    assert_eq!(
+        res,
+        Resp3Value::Map(vec![
+            (key("z"), Resp3Value::Integer(1)),
+            (key("i1"), Resp3Value::Integer(3)),
+            (key("m"), Resp3Value::Integer(2)),
+            (key("a"), Resp3Value::Integer(4)),
+        ])
+    );
+
+    let res = resp3_map(&con.resp3_query(&["redis_value_derive_renamed"])?);
+    assert_eq!(res.len(), 3);
+    assert_eq!(res["maxLen"], Resp3Value::Integer(1));
+    assert_eq!(res["last-seen"], Resp3Value::Integer(2));
+    assert_eq!(res["plain"], Resp3Value::Integer(3));
+
+    let res = resp3_map(&con.resp3_query(&["redis_value_derive_skipped"])?);
+    assert_eq!(res.len(), 2);
+    assert_eq!(res["kept"], Resp3Value::Integer(1));
+    assert_eq!(res["present"], Resp3Value::Integer(2));
+
+    let res = resp3_map(&con.resp3_query(&["redis_value_derive_optional", "5"])?);
+    assert_eq!(res["value"], Resp3Value::Integer(5));
+    let res = resp3_map(&con.resp3_query(&["redis_value_derive_optional"])?);
+    assert_eq!(res["value"], Resp3Value::Null);
+
+    Ok(())
+}
+
+#[test]
+fn test_redis_hash_derive() -> Result<()> {
+    let mut con = TestConnection::new("proc_macro_commands");
+
+    redis::cmd("hset")
+        .arg(&["src", "name", "n1", "limit", "5", "owner", "alice"])
+        .query(&mut con)
+        .with_context(|| "failed to run hset")?;
+    redis::cmd("redis_hash_copy")
+        .arg(&["src", "dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run redis_hash_copy")?;
+    let res: HashMap<String, String> = redis::cmd("hgetall")
+        .arg(&["dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run hgetall")?;
+    assert_eq!(res.len(), 3);
+    assert_eq!(res["name"], "n1");
+    assert_eq!(res["limit"], "6");
+    assert_eq!(res["owner"], "alice");
+
+    // A missing optional field is removed from the destination.
+    redis::cmd("hdel")
+        .arg(&["src", "owner"])
+        .query(&mut con)
+        .with_context(|| "failed to run hdel")?;
+    redis::cmd("redis_hash_copy")
+        .arg(&["src", "dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run redis_hash_copy")?;
+    let res: HashMap<String, String> = redis::cmd("hgetall")
+        .arg(&["dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run hgetall")?;
+    assert_eq!(res.len(), 2);
+    assert!(!res.contains_key("owner"));
+
+    // Values may contain NUL bytes, they round-trip through `load` and `store`.
+    redis::cmd("hset")
+        .arg(&["src", "name", "n\0ul"])
+        .query(&mut con)
+        .with_context(|| "failed to run hset")?;
+    redis::cmd("redis_hash_copy")
+        .arg(&["src", "dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run redis_hash_copy")?;
+    let res: String = redis::cmd("hget")
+        .arg(&["dst", "name"])
+        .query(&mut con)
+        .with_context(|| "failed to run hget")?;
+    assert_eq!(res, "n\0ul");
+
+    redis::cmd("hset")
+        .arg(&["src", "limit", "abc"])
+        .query(&mut con)
+        .with_context(|| "failed to run hset")?;
+    let res: Result<(), RedisError> = redis::cmd("redis_hash_copy")
+        .arg(&["src", "dst"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("Failed parsing hash field 'limit'"));
+
+    let res: Result<(), RedisError> = redis::cmd("redis_hash_copy")
+        .arg(&["missing", "dst"])
+        .query(&mut con);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("Missing hash field 'name'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_block() -> Result<()> {
+    let mut con = TestConnection::new("block");
+
+    let res: String = redis::cmd("block").query(&mut con)?;
+    assert_eq!(res, "42");
+
+    // Blocking is not allowed inside MULTI, the command replies right away.
+    let _: String = redis::cmd("MULTI").query(&mut con)?;
+    let _: String = redis::cmd("block").query(&mut con)?;
+    let res: Vec<String> = redis::cmd("EXEC").query(&mut con)?;
+    assert_eq!(res, ["42"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_block_timeout() -> Result<()> {
+    let mut con = TestConnection::new("block");
+
+    let res: String = redis::cmd("block.timeout")
+        .arg(&[100, 1000])
+        .query(&mut con)
+        .with_context(|| "failed to run block.timeout")?;
+    assert_eq!(res, "timeout");
+
+    // The resources are freed once the work is done, even though the client
+    // already timed out.
+    let freed: i64 = redis::cmd("block.freed").query(&mut con)?;
+    assert_eq!(freed, 0);
+    wait_for_block_freed(&mut con, 1)?;
+
+    let res: String = redis::cmd("block.timeout")
+        .arg(&[10_000, 10])
+        .query(&mut con)
+        .with_context(|| "failed to run block.timeout")?;
+    assert_eq!(res, "done");
+    wait_for_block_freed(&mut con, 2)?;
+
+    Ok(())
+}
+
+/// Poll `block.freed` until it reaches `expected`, the blocked clients'
+/// resources being freed by a background thread.
+fn wait_for_block_freed(con: &mut TestConnection, expected: i64) -> Result<()> {
+    let start = SystemTime::now();
+    loop {
+        let freed: i64 = redis::cmd("block.freed").query(con)?;
+        if freed == expected {
+            return Ok(());
+        }
+        let duration = SystemTime::now().duration_since(start)?;
+        if duration > Duration::from_secs(10) {
+            return Err(anyhow::Error::msg(format!(
+                "Failed waiting for {expected} freed blocked clients, got {freed}"
+            )));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_block_client_id() -> Result<()> {
+    let mut con = TestConnection::new("block");
+
+    let id: u64 = redis::cmd("CLIENT").arg("ID").query(&mut con)?;
+    let res: u64 = redis::cmd("block.client_id")
+        .query(&mut con)
+        .with_context(|| "failed to run block.client_id")?;
+    assert_eq!(res, id);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(any(
+    feature = "min-redis-compatibility-version-7-4",
+    feature = "min-redis-compatibility-version-7-2"
+))]
+fn test_call_blocking() -> Result<()> {
+    let mut con = TestConnection::new("call");
+
+    let res: Option<String> = redis::cmd("call.blocking")
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(res, None);
+
+    let res: Option<String> = redis::cmd("call.blocking_from_detached_ctx")
+        .query(&mut con)
+        .with_context(|| "failed to run string.set")?;
+
+    assert_eq!(res, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_open_key_with_flags() -> Result<()> {
+    let mut con = TestConnection::new("open_key_with_flags");
+
+    // Avoid active expriation
+    redis::cmd("DEBUG")
+        .arg(&["SET-ACTIVE-EXPIRE", "0"])
+        .query(&mut con)
+        .with_context(|| "failed to run DEBUG SET-ACTIVE-EXPIRE")?;
+
+    for cmd in ["open_key_with_flags.write", "open_key_with_flags.read"].into_iter() {
+        redis::cmd("set")
+            .arg(&["x", "1"])
+            .query(&mut con)
+            .with_context(|| "failed to run string.set")?;
+
+        // Set experition time to 1 second.
+        redis::cmd("pexpire")
+            .arg(&["x", "1"])
+            .query(&mut con)
+            .with_context(|| "failed to run expire")?;
+
+        // Sleep for 2 seconds, ensure expiration time has passed.
+        thread::sleep(Duration::from_millis(500));
+
+        // Open key as read only or ReadWrite with NOEFFECTS flag.
+        let res = redis::cmd(cmd).arg(&["x"]).query(&mut con);
+        assert_eq!(res, Ok(()));
+
+        // Get the number of expired keys.
+        let stats: String = redis::cmd("info").arg(&["stats"]).query(&mut con)?;
+
+        // Find the number of expired keys, x,  according to the substring "expired_keys:{x}"
+        let expired_keys = stats
+            .match_indices("expired_keys:")
+            .next()
+            .map(|(i, _)| &stats[i..i + "expired_keys:".len() + 1])
+            .and_then(|s| s.split(':').nth(1))
+            .and_then(|s| s.parse::<i32>().ok())
+            .unwrap_or(-1);
+
+        // Ensure that no keys were expired.
+        assert_eq!(expired_keys, 0);
+
+        // Delete key and reset stats
+        redis::cmd("del").arg(&["x"]).query(&mut con)?;
+        redis::cmd("config").arg(&["RESETSTAT"]).query(&mut con)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_expire_duration_units() -> Result<()> {
+    let mut con = TestConnection::new("expire");
+
+    redis::cmd("set").arg(&["key", "value"]).query(&mut con)?;
+
+    for (ttl, max_millis) in [
+        ("1500", 1500),
+        ("1500ms", 1500),
+        ("10s", 10_000),
+        ("2h", 7_200_000),
+    ] {
+        redis::cmd("expire.for")
+            .arg(&["key", ttl])
+            .query(&mut con)
+            .with_context(|| "failed to run expire.for")?;
+        let pttl: i64 = redis::cmd("pttl").arg(&["key"]).query(&mut con)?;
+        assert!(
+            pttl > max_millis - 1000 && pttl <= max_millis,
+            "{ttl}: {pttl}"
+        );
+    }
+
+    let err = redis::cmd("expire.for")
+        .arg(&["key", "10w"])
+        .query::<()>(&mut con)
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid duration '10w'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_expire() -> Result<()> {
     let port: u16 = 6502;
     let _guards = vec![start_redis_server_with_module("expire", port)
         .with_context(|| "failed to start redis server")?];
@@ -687,6 +2286,146 @@ fn test_expire() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_expire_checked() -> Result<()> {
+    let mut con = TestConnection::new("expire");
+
+    redis::cmd("set")
+        .arg(&["expire_checked", "value"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+
+    // Sub-millisecond TTL is rejected and the key is kept
+    let res: Result<(), RedisError> = redis::cmd("expire.micros")
+        .arg(&["expire_checked", "500"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let pttl: i64 = redis::cmd("pttl")
+        .arg(&["expire_checked"])
+        .query(&mut con)?;
+    assert_eq!(pttl, -1);
+
+    redis::cmd("expire.micros")
+        .arg(&["expire_checked", "100000000"])
+        .query(&mut con)
+        .with_context(|| "failed to run expire.micros")?;
+
+    let pttl: i64 = redis::cmd("pttl")
+        .arg(&["expire_checked"])
+        .query(&mut con)?;
+    assert!(pttl > 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_expire_missing_key() -> Result<()> {
+    let mut con = TestConnection::new("expire");
+
+    let res: Result<(), RedisError> = redis::cmd("expire.cmd")
+        .arg(&["expire_missing", "100"])
+        .query(&mut con);
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("key does not exist"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dump_with_metadata() -> Result<()> {
+    let mut con = TestConnection::new("expire");
+
+    redis::cmd("rpush")
+        .arg(&["copy_src", "a", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run rpush")?;
+    redis::cmd("pexpire")
+        .arg(&["copy_src", "100000"])
+        .query(&mut con)
+        .with_context(|| "failed to run pexpire")?;
+
+    let expire_at: i64 = redis::cmd("expire.copy")
+        .arg(&["copy_src", "copy_dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run expire.copy")?;
+
+    let src_expire_at: i64 = redis::cmd("pexpiretime")
+        .arg(&["copy_src"])
+        .query(&mut con)?;
+    let dst_expire_at: i64 = redis::cmd("pexpiretime")
+        .arg(&["copy_dst"])
+        .query(&mut con)?;
+    assert_eq!(expire_at, src_expire_at);
+    assert_eq!(dst_expire_at, src_expire_at);
+
+    let res: Vec<String> = redis::cmd("lrange")
+        .arg(&["copy_dst", "0", "-1"])
+        .query(&mut con)?;
+    assert_eq!(res, vec!["a", "b"]);
+
+    // Restoring over an existing key fails without replace
+    let res: Result<i64, RedisError> = redis::cmd("expire.copy")
+        .arg(&["copy_src", "copy_dst"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    let res: Result<i64, RedisError> = redis::cmd("expire.copy")
+        .arg(&["copy_missing", "copy_dst2"])
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_expire_copy_ttl() -> Result<()> {
+    let mut con = TestConnection::new("expire");
+
+    redis::cmd("set")
+        .arg(&["ttl_src", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    redis::cmd("set")
+        .arg(&["ttl_dst", "1"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+    redis::cmd("pexpire")
+        .arg(&["ttl_src", "100000"])
+        .query(&mut con)
+        .with_context(|| "failed to run pexpire")?;
+
+    let ttl: i64 = redis::cmd("expire.copy_ttl")
+        .arg(&["ttl_src", "ttl_dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run expire.copy_ttl")?;
+    assert!(ttl > 0 && ttl <= 100000);
+
+    let src_expire_at: i64 = redis::cmd("pexpiretime")
+        .arg(&["ttl_src"])
+        .query(&mut con)?;
+    let dst_expire_at: i64 = redis::cmd("pexpiretime")
+        .arg(&["ttl_dst"])
+        .query(&mut con)?;
+    assert_eq!(dst_expire_at, src_expire_at);
+
+    // A key without a TTL removes the TTL of the destination
+    redis::cmd("persist")
+        .arg(&["ttl_src"])
+        .query(&mut con)
+        .with_context(|| "failed to run persist")?;
+    let ttl: i64 = redis::cmd("expire.copy_ttl")
+        .arg(&["ttl_src", "ttl_dst"])
+        .query(&mut con)
+        .with_context(|| "failed to run expire.copy_ttl")?;
+    assert_eq!(ttl, -1);
+
+    let res: i64 = redis::cmd("pttl").arg(&["ttl_dst"]).query(&mut con)?;
+    assert_eq!(res, -1);
+
+    Ok(())
+}
+
 #[test]
 fn test_defrag() -> Result<()> {
     let port: u16 = 6503;
@@ -748,3 +2487,313 @@ fn test_defrag() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_dict() -> Result<()> {
+    let mut con = TestConnection::new("dict");
+
+    for (key, value) in [("b", "2"), ("a", "1"), ("c", "3")] {
+        let old: Option<String> = redis::cmd("dict.set")
+            .arg(&[key, value])
+            .query(&mut con)
+            .with_context(|| "failed to run dict.set")?;
+        assert_eq!(old, None);
+    }
+
+    let old: Option<String> = redis::cmd("dict.set")
+        .arg(&["b", "two"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.set")?;
+    assert_eq!(old.as_deref(), Some("2"));
+
+    let res: Option<String> = redis::cmd("dict.get")
+        .arg(&["b"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.get")?;
+    assert_eq!(res.as_deref(), Some("two"));
+
+    let deleted: bool = redis::cmd("dict.del")
+        .arg(&["c"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.del")?;
+    assert!(deleted);
+    let deleted: bool = redis::cmd("dict.del")
+        .arg(&["c"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.del")?;
+    assert!(!deleted);
+
+    let res: Vec<String> = redis::cmd("dict.list")
+        .query(&mut con)
+        .with_context(|| "failed to run dict.list")?;
+    assert_eq!(&res, &["a", "1", "b", "two"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_dict_interned_values() -> Result<()> {
+    let mut con = TestConnection::new("dict");
+
+    let mut shared = Vec::new();
+    for (key, value) in [("a", "x"), ("b", "y"), ("c", "x"), ("d", "x")] {
+        let res: bool = redis::cmd("dict.iset")
+            .arg(&[key, value])
+            .query(&mut con)
+            .with_context(|| "failed to run dict.iset")?;
+        shared.push(res);
+    }
+    assert_eq!(shared, [false, false, true, true]);
+
+    let interned: i64 = redis::cmd("dict.interned").query(&mut con)?;
+    assert_eq!(interned, 2);
+
+    let res: Vec<String> = redis::cmd("dict.list")
+        .query(&mut con)
+        .with_context(|| "failed to run dict.list")?;
+    assert_eq!(&res, &["a", "x", "b", "y", "c", "x", "d", "x"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_dict_memory_usage() -> Result<()> {
+    let mut con = TestConnection::new("dict");
+
+    let empty: i64 = redis::cmd("dict.memory").query(&mut con)?;
+
+    let value = "x".repeat(100);
+    for i in 0..100 {
+        let _: Option<String> = redis::cmd("dict.set")
+            .arg(&[format!("key{i}"), value.clone()])
+            .query(&mut con)
+            .with_context(|| "failed to run dict.set")?;
+    }
+
+    // The values alone take at least 100 * 100 bytes.
+    let full: i64 = redis::cmd("dict.memory").query(&mut con)?;
+    assert!(full > empty + 100 * 100);
+
+    Ok(())
+}
+
+#[test]
+fn test_dict_range() -> Result<()> {
+    let mut con = TestConnection::new("dict");
+
+    for key in ["user:1", "user:2", "admin:1", "user:3", "zone:1"] {
+        redis::cmd("dict.set")
+            .arg(&[key, "x"])
+            .query::<()>(&mut con)
+            .with_context(|| "failed to run dict.set")?;
+    }
+
+    let res: Vec<String> = redis::cmd("dict.range")
+        .arg(&["user:2", "ASC"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.range")?;
+    assert_eq!(&res, &["user:2", "user:3", "zone:1"]);
+
+    let res: Vec<String> = redis::cmd("dict.range")
+        .arg(&["user:15", "DESC"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.range")?;
+    assert_eq!(&res, &["user:1", "admin:1"]);
+
+    let res: Vec<String> = redis::cmd("dict.prefix")
+        .arg(&["user:"])
+        .query(&mut con)
+        .with_context(|| "failed to run dict.prefix")?;
+    assert_eq!(&res, &["user:1", "user:2", "user:3"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_free_effort_lazy_free() -> Result<()> {
+    let mut con = TestConnection::new("data_type");
+
+    let lazyfreed_objects = |con: &mut redis::Connection| -> Result<Option<i64>> {
+        let info: String = redis::cmd("info").arg(&["memory"]).query(con)?;
+        Ok(info
+            .lines()
+            .find_map(|l| l.strip_prefix("lazyfreed_objects:"))
+            .and_then(|v| v.trim().parse().ok()))
+    };
+
+    // Servers that do not report lazy freed objects can not be checked.
+    let before = match lazyfreed_objects(&mut con)? {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+
+    redis::cmd("alloc.set")
+        .arg(&["big", "1000000"])
+        .query(&mut con)
+        .with_context(|| "failed to run alloc.set")?;
+    redis::cmd("unlink")
+        .arg(&["big"])
+        .query(&mut con)
+        .with_context(|| "failed to run unlink")?;
+
+    let start = SystemTime::now();
+    while lazyfreed_objects(&mut con)? == Some(before) {
+        if SystemTime::now().duration_since(start)? > Duration::from_secs(5) {
+            return Err(anyhow::Error::msg("Value was not freed lazily"));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_command_filter() -> Result<()> {
+    let mut con = TestConnection::new("command_filter");
+
+    redis::cmd("set")
+        .arg(&["foo", "bar"])
+        .query(&mut con)
+        .with_context(|| "failed to run set")?;
+
+    // GET is rewritten to filter.myget, whose own GET call is not filtered again.
+    let res: String = redis::cmd("get")
+        .arg(&["foo"])
+        .query(&mut con)
+        .with_context(|| "failed to run get")?;
+    assert_eq!(res, "filtered:bar");
+
+    let res: Option<String> = redis::cmd("get")
+        .arg(&["missing"])
+        .query(&mut con)
+        .with_context(|| "failed to run get")?;
+    assert_eq!(res, None);
+
+    let res: Vec<String> = redis::cmd("filter.args")
+        .arg(&["a", "old", "drop", "b"])
+        .query(&mut con)
+        .with_context(|| "failed to run filter.args")?;
+    assert_eq!(&res, &["inserted", "a", "new", "b"]);
+
+    // The filter registered by filter.echo_on rewrites the argument of
+    // filter.echo, until it is unregistered by filter.echo_off.
+    for (toggle, expected) in [
+        (None, "x"),
+        (Some("filter.echo_on"), "intercepted"),
+        (Some("filter.echo_off"), "x"),
+    ] {
+        if let Some(toggle) = toggle {
+            let _: String = redis::cmd(toggle)
+                .query(&mut con)
+                .with_context(|| format!("failed to run {toggle}"))?;
+        }
+        let res: String = redis::cmd("filter.echo")
+            .arg(&["x"])
+            .query(&mut con)
+            .with_context(|| "failed to run filter.echo")?;
+        assert_eq!(res, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(any(
+    feature = "min-redis-compatibility-version-7-4",
+    feature = "min-redis-compatibility-version-7-2"
+))]
+fn test_command_filter_client_id() -> Result<()> {
+    let mut con = TestConnection::new("command_filter");
+
+    let id: u64 = redis::cmd("client")
+        .arg(&["id"])
+        .query(&mut con)
+        .with_context(|| "failed to run client id")?;
+    let res: Vec<String> = redis::cmd("filter.client_id")
+        .query(&mut con)
+        .with_context(|| "failed to run filter.client_id")?;
+    assert_eq!(res, vec![id.to_string()]);
+
+    Ok(())
+}
+
+#[test]
+fn test_try_alloc() -> Result<()> {
+    let mut con = TestConnection::new("try_alloc");
+
+    let res: i64 = redis::cmd("try_alloc.fill")
+        .arg(&[1024])
+        .query(&mut con)
+        .with_context(|| "failed to run try_alloc.fill")?;
+    assert_eq!(res, 1024);
+
+    // An allocation that can not be satisfied is rejected, the server keeps running.
+    let res: RedisResult<i64> = redis::cmd("try_alloc.fill")
+        .arg(&[1u64 << 60])
+        .query(&mut con);
+    assert!(res.err().unwrap().to_string().contains("not enough memory"));
+
+    let res: String = redis::cmd("PING").query(&mut con)?;
+    assert_eq!(res, "PONG");
+
+    Ok(())
+}
+
+#[test]
+fn test_rdb_load_encoding_version() -> Result<()> {
+    let mut con = TestConnection::new("type_migration");
+
+    let _: String = redis::cmd("profile.set")
+        .arg(&["alice", "Alice", "3"])
+        .query(&mut con)?;
+
+    // Values saved with the current encoding version survive a reload.
+    let _: String = redis::cmd("DEBUG").arg("RELOAD").query(&mut con)?;
+    let res: (String, i64) = redis::cmd("profile.get").arg("alice").query(&mut con)?;
+    assert_eq!(res, ("Alice".to_owned(), 3));
+
+    // A DUMP payload of a module value is made of the `RDB_TYPE_MODULE_2`
+    // type, the module type id (ending with the 10 bits encoding version),
+    // the module data terminated by an EOF opcode, and a footer with the
+    // RDB version and a checksum.
+    let dump: Vec<u8> = redis::cmd("DUMP").arg("alice").query(&mut con)?;
+    assert_eq!(&dump[..2], &[7, 0x81]);
+    let module_id = u64::from_be_bytes(dump[2..10].try_into()?);
+    assert_eq!(module_id & 1023, 2);
+    let footer = &dump[dump.len() - 10..dump.len() - 8];
+
+    let payload = |encver: u64| {
+        let mut payload = vec![7, 0x81];
+        payload.extend(((module_id & !1023) | encver).to_be_bytes());
+        // A version 1 value, only the name as a string (opcode 5).
+        payload.extend([5, 3]);
+        payload.extend(b"Bob");
+        payload.push(0);
+        payload.extend(footer);
+        payload.extend([0; 8]);
+        payload
+    };
+
+    let _: String = redis::cmd("DEBUG")
+        .arg(&["SET-SKIP-CHECKSUM-VALIDATION", "1"])
+        .query(&mut con)?;
+
+    // A value saved by version 1 is upgraded on load.
+    let _: String = redis::cmd("RESTORE")
+        .arg("bob")
+        .arg(0)
+        .arg(payload(1))
+        .query(&mut con)?;
+    let res: (String, i64) = redis::cmd("profile.get").arg("bob").query(&mut con)?;
+    assert_eq!(res, ("Bob".to_owned(), 0));
+
+    // A value saved by a newer version can not be loaded.
+    let res: RedisResult<String> = redis::cmd("RESTORE")
+        .arg("bob3")
+        .arg(0)
+        .arg(payload(3))
+        .query(&mut con);
+    assert!(res.is_err());
+
+    Ok(())
+}
@@ -2,6 +2,8 @@ use anyhow::{Context, Result};
 
 use redis::Connection;
 use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::atomic::AtomicU16;
@@ -18,6 +20,7 @@ pub fn start_redis(module_name: &str, port: u16) -> Result<Vec<ChildGuard>, &'st
 pub struct TestConnection {
     _guards: Vec<ChildGuard>,
     connection: Connection,
+    port: u16,
 }
 
 static TEST_PORT: AtomicU16 = AtomicU16::new(6479);
@@ -31,8 +34,93 @@ impl TestConnection {
         Self {
             _guards: start_redis(module_name, port).expect("Redis instance started."),
             connection: get_redis_connection(port).expect("Established connection to server."),
+            port,
         }
     }
+
+    /// Open another connection to the same server.
+    pub fn new_connection(&self) -> Result<Connection> {
+        get_redis_connection(self.port)
+    }
+
+    /// Run a command on a new RESP3 connection (`HELLO 3`) and return its
+    /// reply. `redis` only speaks RESP2, so the reply is parsed here.
+    pub fn resp3_query(&self, args: &[&str]) -> Result<Resp3Value> {
+        let stream = TcpStream::connect(("127.0.0.1", self.port))?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        for command in [&["HELLO", "3"][..], args] {
+            let mut request = format!("*{}\r\n", command.len());
+            for arg in command {
+                request.push_str(&format!("${}\r\n{arg}\r\n", arg.len()));
+            }
+            writer.write_all(request.as_bytes())?;
+        }
+
+        read_resp3_value(&mut reader)?;
+        read_resp3_value(&mut reader)
+    }
+}
+
+/// A RESP3 reply, see [TestConnection::resp3_query].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resp3Value {
+    SimpleString(String),
+    BulkString(String),
+    Error(String),
+    Integer(i64),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+    VerbatimString(String),
+    Null,
+    Array(Vec<Resp3Value>),
+    Set(Vec<Resp3Value>),
+    Map(Vec<(Resp3Value, Resp3Value)>),
+}
+
+fn read_resp3_value(reader: &mut BufReader<TcpStream>) -> Result<Resp3Value> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let line = line.trim_end_matches("\r\n");
+    let (kind, rest) = line.split_at(1);
+
+    let read_blob = |reader: &mut BufReader<TcpStream>, len: &str| -> Result<String> {
+        let mut buf = vec![0; len.parse::<usize>()? + 2];
+        reader.read_exact(&mut buf)?;
+        buf.truncate(buf.len() - 2);
+        Ok(String::from_utf8(buf)?)
+    };
+    let read_values = |reader: &mut BufReader<TcpStream>, len: usize| {
+        (0..len)
+            .map(|_| read_resp3_value(reader))
+            .collect::<Result<Vec<_>>>()
+    };
+
+    Ok(match kind {
+        "+" => Resp3Value::SimpleString(rest.to_owned()),
+        "-" => Resp3Value::Error(rest.to_owned()),
+        ":" => Resp3Value::Integer(rest.parse()?),
+        "," => Resp3Value::Double(rest.parse()?),
+        "#" => Resp3Value::Boolean(rest == "t"),
+        "(" => Resp3Value::BigNumber(rest.to_owned()),
+        "_" => Resp3Value::Null,
+        "$" => Resp3Value::BulkString(read_blob(reader, rest)?),
+        "=" => Resp3Value::VerbatimString(read_blob(reader, rest)?),
+        "*" => Resp3Value::Array(read_values(reader, rest.parse()?)?),
+        "~" => Resp3Value::Set(read_values(reader, rest.parse()?)?),
+        "%" => {
+            let values = read_values(reader, rest.parse::<usize>()? * 2)?;
+            let mut values = values.into_iter();
+            let mut map = Vec::new();
+            while let (Some(key), Some(val)) = (values.next(), values.next()) {
+                map.push((key, val));
+            }
+            Resp3Value::Map(map)
+        }
+        _ => anyhow::bail!("Unsupported RESP3 type '{kind}'"),
+    })
 }
 
 impl std::ops::Deref for TestConnection {